@@ -0,0 +1,153 @@
+//! Derive macro for the `FromRandomBytes` trait of the
+//! [`fast-erasure-shake-rng` crate]. Don't use this crate directly, but enable
+//! the `derive` feature of `fast-erasure-shake-rng` instead.
+//!
+//! The macro is implemented on top of the bare `proc_macro` API, so it doesn't
+//! pull in any (parsing) dependencies.
+//!
+//! [`fast-erasure-shake-rng` crate]: https://crates.io/crates/fast-erasure-shake-rng
+#![forbid(rust_2018_compatibility, unsafe_code)]
+#![deny(future_incompatible, rust_2018_idioms)]
+
+use proc_macro::{Delimiter, Spacing, TokenStream, TokenTree};
+
+/// Derive `FromRandomBytes` for a struct all of whose fields implement
+/// `FromRandomBytes`. The fields are generated in declaration order.
+///
+/// Generic structs, enums and unions are not supported.
+#[proc_macro_derive(FromRandomBytes)]
+pub fn derive_from_random_bytes(input: TokenStream) -> TokenStream {
+    match expand(input) {
+        Ok(output) => output,
+        Err(msg) => format!("::core::compile_error!({:?});", msg)
+            .parse()
+            .expect("compile_error invocation is valid"),
+    }
+}
+
+/// The fields of a struct.
+enum Fields {
+    /// A struct with named fields, `struct S { a: A, b: B }`.
+    Named(Vec<String>),
+    /// A tuple struct `struct S(A, B);` with the given number of fields.
+    Unnamed(usize),
+    /// A unit struct `struct S;`.
+    Unit,
+}
+
+fn expand(input: TokenStream) -> Result<TokenStream, &'static str> {
+    let mut tokens = input.into_iter();
+    // skip attributes and visibility up to the `struct` keyword
+    let name = loop {
+        match tokens.next() {
+            Some(TokenTree::Ident(ident)) => match ident.to_string().as_str() {
+                "struct" => match tokens.next() {
+                    Some(TokenTree::Ident(name)) => break name.to_string(),
+                    _ => return Err("expected struct name"),
+                },
+                "enum" | "union" => {
+                    return Err("`FromRandomBytes` can only be derived for structs")
+                },
+                _ => {},
+            },
+            Some(_) => {},
+            None => return Err("expected a struct"),
+        }
+    };
+    let fields = match tokens.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+            Fields::Named(named_fields(group.stream())?)
+        },
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+            Fields::Unnamed(split_top_level_commas(group.stream()).len())
+        },
+        Some(TokenTree::Punct(punct)) if punct.as_char() == ';' => Fields::Unit,
+        Some(TokenTree::Punct(punct)) if punct.as_char() == '<' => {
+            return Err("`FromRandomBytes` can not be derived for generic structs")
+        },
+        _ => return Err("unsupported struct definition"),
+    };
+
+    let path = "::fast_erasure_shake_rng";
+    let field = format!("{}::FromRandomBytes::from_random_bytes(rng)", path);
+    let body = match fields {
+        Fields::Named(names) => {
+            let inits: Vec<String> = names
+                .iter()
+                .map(|name| format!("{}: {}", name, field))
+                .collect();
+            format!("Self {{ {} }}", inits.join(", "))
+        },
+        Fields::Unnamed(len) => format!("Self({})", vec![field; len].join(", ")),
+        Fields::Unit => "{ let _ = rng; Self }".to_string(),
+    };
+    format!(
+        "impl {path}::FromRandomBytes for {name} {{
+            fn from_random_bytes(rng: &mut {path}::RngState) -> Self {{
+                {body}
+            }}
+        }}",
+        path = path,
+        name = name,
+        body = body,
+    )
+    .parse()
+    .map_err(|_| "failed to generate implementation")
+}
+
+/// Split a comma separated list of fields at the commas which are not nested
+/// in a type's generic arguments. Trailing empty items are dropped.
+fn split_top_level_commas(stream: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut items = vec![Vec::new()];
+    let mut angle_depth = 0usize;
+    let mut prev_joint_dash = false;
+    for token in stream {
+        if let TokenTree::Punct(punct) = &token {
+            match punct.as_char() {
+                ',' if angle_depth == 0 => {
+                    items.push(Vec::new());
+                    prev_joint_dash = false;
+                    continue;
+                },
+                '<' => angle_depth += 1,
+                // don't count the `>` of a `->` in function pointer types
+                '>' if !prev_joint_dash => angle_depth = angle_depth.saturating_sub(1),
+                _ => {},
+            }
+            prev_joint_dash = punct.as_char() == '-' && punct.spacing() == Spacing::Joint;
+        } else {
+            prev_joint_dash = false;
+        }
+        items.last_mut().expect("items is never empty").push(token);
+    }
+    items.retain(|item| !item.is_empty());
+    items
+}
+
+/// Extract the field names from the body of a struct with named fields.
+fn named_fields(stream: TokenStream) -> Result<Vec<String>, &'static str> {
+    split_top_level_commas(stream)
+        .into_iter()
+        .map(|field| {
+            // the name is the identifier right before the first lone `:`; attributes and
+            // visibility restrictions are groups, so they are skipped automatically
+            let mut name = None;
+            let mut prev_joint_colon = false;
+            for token in field {
+                match token {
+                    TokenTree::Ident(ident) => name = Some(ident.to_string()),
+                    TokenTree::Punct(punct) if punct.as_char() == ':' => {
+                        if punct.spacing() == Spacing::Alone && !prev_joint_colon {
+                            return name.ok_or("expected field name");
+                        }
+                        prev_joint_colon = punct.spacing() == Spacing::Joint;
+                        continue;
+                    },
+                    _ => {},
+                }
+                prev_joint_colon = false;
+            }
+            Err("expected field type")
+        })
+        .collect()
+}
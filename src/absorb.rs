@@ -0,0 +1,266 @@
+use zeroize::Zeroizing;
+
+use crate::{RngState, RATE_BYTES};
+
+/// Streams the encoding of structured data into the state, see
+/// [`RngState::absorb`]. Passed to [`AbsorbInto::absorb_into`].
+pub struct Absorber<'r> {
+    rng: &'r mut RngState,
+    buffer: Zeroizing<[u8; RATE_BYTES]>,
+    filled: usize,
+}
+
+impl Absorber<'_> {
+    /// Absorb raw `bytes`, without length prefix. Only use this for data of a
+    /// fixed length; prefix variable length data with its length, e.g. by
+    /// absorbing it as slice.
+    pub fn write_bytes(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            let n = core::cmp::min(bytes.len(), RATE_BYTES - self.filled);
+            self.buffer[self.filled..self.filled + n].copy_from_slice(&bytes[..n]);
+            self.filled += n;
+            bytes = &bytes[n..];
+            if self.filled == RATE_BYTES {
+                self.rng.absorb_block(&self.buffer);
+                self.filled = 0;
+            }
+        }
+    }
+
+    /// Absorb a length prefix, as 64-bit little-endian integer.
+    pub fn write_len(&mut self, len: usize) {
+        self.write_bytes(&(len as u64).to_le_bytes());
+    }
+}
+
+/// Types which can be absorbed into the state of the RNG using
+/// [`RngState::absorb`], with an unambiguous encoding: different values of the
+/// same type always have different encodings.
+///
+/// Integers are encoded as fixed size little-endian (`usize` and `isize` as
+/// 64-bit), `bool` as one byte, slices, strings and vectors with a length
+/// prefix, `Option` with a tag byte and tuples as the concatenation of their
+/// elements. Implement this for own types by absorbing all fields in order.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "getrandom")] {
+/// use fast_erasure_shake_rng::{AbsorbInto, Absorber, RngState};
+///
+/// struct Session<'a> {
+///     id: u64,
+///     peer: &'a str,
+/// }
+///
+/// impl AbsorbInto for Session<'_> {
+///     fn absorb_into(&self, absorber: &mut Absorber<'_>) {
+///         self.id.absorb_into(absorber);
+///         self.peer.absorb_into(absorber);
+///     }
+/// }
+///
+/// let mut rng = RngState::new_from_getrandom().unwrap();
+/// rng.absorb(&Session {
+///     id: 7,
+///     peer: "example.com",
+/// });
+/// # }
+/// ```
+pub trait AbsorbInto {
+    /// Absorb the encoding of `self`.
+    fn absorb_into(&self, absorber: &mut Absorber<'_>);
+
+    /// Absorb the encodings of all elements of `slice`, without length
+    /// prefix. Can be overridden for a more efficient encoding, which must be
+    /// the same as absorbing all elements in order.
+    fn absorb_slice_into(slice: &[Self], absorber: &mut Absorber<'_>)
+    where
+        Self: Sized,
+    {
+        for value in slice {
+            value.absorb_into(absorber);
+        }
+    }
+}
+
+macro_rules! impl_absorb_int {
+    ($($t:ty),*) => {$(
+        impl AbsorbInto for $t {
+            fn absorb_into(&self, absorber: &mut Absorber<'_>) {
+                absorber.write_bytes(&self.to_le_bytes());
+            }
+        }
+    )*};
+}
+
+impl_absorb_int!(u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl AbsorbInto for u8 {
+    fn absorb_into(&self, absorber: &mut Absorber<'_>) {
+        absorber.write_bytes(&[*self]);
+    }
+
+    fn absorb_slice_into(slice: &[Self], absorber: &mut Absorber<'_>) {
+        absorber.write_bytes(slice);
+    }
+}
+
+impl AbsorbInto for usize {
+    fn absorb_into(&self, absorber: &mut Absorber<'_>) {
+        (*self as u64).absorb_into(absorber);
+    }
+}
+
+impl AbsorbInto for isize {
+    fn absorb_into(&self, absorber: &mut Absorber<'_>) {
+        (*self as i64).absorb_into(absorber);
+    }
+}
+
+impl AbsorbInto for bool {
+    fn absorb_into(&self, absorber: &mut Absorber<'_>) {
+        u8::from(*self).absorb_into(absorber);
+    }
+}
+
+impl<T: AbsorbInto> AbsorbInto for [T] {
+    fn absorb_into(&self, absorber: &mut Absorber<'_>) {
+        absorber.write_len(self.len());
+        T::absorb_slice_into(self, absorber);
+    }
+}
+
+impl<T: AbsorbInto, const N: usize> AbsorbInto for [T; N] {
+    fn absorb_into(&self, absorber: &mut Absorber<'_>) {
+        self.as_slice().absorb_into(absorber);
+    }
+}
+
+impl AbsorbInto for str {
+    fn absorb_into(&self, absorber: &mut Absorber<'_>) {
+        self.as_bytes().absorb_into(absorber);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: AbsorbInto> AbsorbInto for alloc::vec::Vec<T> {
+    fn absorb_into(&self, absorber: &mut Absorber<'_>) {
+        self.as_slice().absorb_into(absorber);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl AbsorbInto for alloc::string::String {
+    fn absorb_into(&self, absorber: &mut Absorber<'_>) {
+        self.as_str().absorb_into(absorber);
+    }
+}
+
+impl<T: AbsorbInto> AbsorbInto for Option<T> {
+    fn absorb_into(&self, absorber: &mut Absorber<'_>) {
+        match self {
+            None => 0u8.absorb_into(absorber),
+            Some(value) => {
+                1u8.absorb_into(absorber);
+                value.absorb_into(absorber);
+            },
+        }
+    }
+}
+
+impl<T: AbsorbInto + ?Sized> AbsorbInto for &T {
+    fn absorb_into(&self, absorber: &mut Absorber<'_>) {
+        (**self).absorb_into(absorber);
+    }
+}
+
+macro_rules! impl_absorb_tuple {
+    ($($name:ident)+) => {
+        impl<$($name: AbsorbInto),+> AbsorbInto for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn absorb_into(&self, absorber: &mut Absorber<'_>) {
+                let ($($name,)+) = self;
+                $($name.absorb_into(absorber);)+
+            }
+        }
+    };
+}
+
+impl_absorb_tuple!(A);
+impl_absorb_tuple!(A B);
+impl_absorb_tuple!(A B C);
+impl_absorb_tuple!(A B C D);
+impl_absorb_tuple!(A B C D E);
+impl_absorb_tuple!(A B C D E F);
+
+impl RngState {
+    /// Hash the unambiguous encoding of the structured data `value` into the
+    /// state, e.g. session IDs, counters or configuration for hedging. See
+    /// [`AbsorbInto`] for the encoding. Equivalent to [`Self::seed`] with the
+    /// encoding, but streamed without allocating.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// rng.absorb(&("session", 42u64, Some([1u8, 2, 3])));
+    /// # }
+    /// ```
+    pub fn absorb<T: AbsorbInto + ?Sized>(&mut self, value: &T) {
+        let mut absorber = Absorber {
+            rng: self,
+            buffer: Zeroizing::new([0; RATE_BYTES]),
+            filled: 0,
+        };
+        value.absorb_into(&mut absorber);
+        let Absorber {
+            rng,
+            buffer,
+            filled,
+        } = absorber;
+        rng.absorb_partial_block_padded(&buffer[..filled]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RngState;
+
+    fn output_after_absorb<T: super::AbsorbInto + ?Sized>(value: &T) -> [u8; 32] {
+        let mut rng = RngState::new_unseeded();
+        rng.absorb(value);
+        rng.get_random_bytes()
+    }
+
+    fn output_after_seed(seed: &[u8]) -> [u8; 32] {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(seed);
+        rng.get_random_bytes()
+    }
+
+    #[test]
+    fn absorb_equals_seed_of_encoding() {
+        let mut expected = [0; 8 + 100 + 1 + 4];
+        expected[0] = 100;
+        expected[8..108].fill(0xab);
+        expected[108] = 1;
+        expected[109..].copy_from_slice(&7u32.to_le_bytes());
+        assert_eq!(
+            output_after_absorb(&([0xabu8; 100], Some(7u32))),
+            output_after_seed(&expected)
+        );
+    }
+
+    #[test]
+    fn encoding_is_unambiguous() {
+        let split_a: (&str, &str) = ("ab", "c");
+        let split_b: (&str, &str) = ("a", "bc");
+        assert_ne!(output_after_absorb(&split_a), output_after_absorb(&split_b));
+        assert_ne!(
+            output_after_absorb(&None::<u8>),
+            output_after_absorb(&Some(0u8))
+        );
+    }
+}
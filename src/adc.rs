@@ -0,0 +1,158 @@
+use crate::{EntropySource, EntropySourceError, HashConditioner, VonNeumann};
+
+/// Default assessed min-entropy per raw output byte, in bits.
+const DEFAULT_BITS_PER_BYTE: u8 = 1;
+
+/// Raw noise [`EntropySource`] collecting the least significant bits of
+/// conversions of a floating (unconnected) ADC pin, giving cheap MCUs without
+/// a TRNG a documented seeding path.
+///
+/// The samples are read using a closure, which adapts any HAL: e.g. with
+/// embedded-hal 0.2 `OneShot`, `|| nb::block!(adc.read(&mut pin))`; on HALs
+/// with an inherent ADC API, call it directly. Every raw output byte packs the
+/// lowest `lsbs_per_sample` bits (default 1) of as many conversions as needed.
+///
+/// The raw bits are biased and correlated, so use [`Self::conditioned`],
+/// which debiases them with [`VonNeumann`] and hashes them with a
+/// [`HashConditioner`]. Raw bytes are credited with a conservative 1 bit of
+/// min-entropy by default, see [`Self::with_min_entropy_per_byte`]; assess
+/// your board (e.g. with the NIST SP 800-90B estimators on a raw capture)
+/// before raising it. Combine with
+/// [`RngBuilder::health_tests`](crate::RngBuilder::health_tests) to detect a
+/// pin which stopped floating.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::{AdcNoise, RngState};
+///
+/// # struct Adc(u32);
+/// # impl Adc {
+/// #     fn read_floating_pin(&mut self) -> Result<u16, ()> {
+/// #         self.0 = self.0.wrapping_mul(1_103_515_245).wrapping_add(12345);
+/// #         Ok((self.0 >> 16) as u16)
+/// #     }
+/// # }
+/// # let mut adc = Adc(1);
+/// let mut noise = AdcNoise::new(|| adc.read_floating_pin()).conditioned();
+/// let mut rng = RngState::new_unseeded();
+/// rng.seed_from_entropy_source(&mut noise).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct AdcNoise<F> {
+    read_sample: F,
+    lsbs_per_sample: u8,
+    bits_per_byte: u8,
+}
+
+impl<F: FnMut() -> Result<u16, E>, E> AdcNoise<F> {
+    /// Collect the least significant bit of every sample read by
+    /// `read_sample`.
+    pub fn new(read_sample: F) -> Self {
+        Self {
+            read_sample,
+            lsbs_per_sample: 1,
+            bits_per_byte: DEFAULT_BITS_PER_BYTE,
+        }
+    }
+
+    /// Collect the lowest `lsbs` bits of every sample instead of only the
+    /// least significant bit, for ADCs with several bits of noise.
+    ///
+    /// # Panics
+    /// Panics if `lsbs` is not in the range `1..=8`.
+    #[must_use]
+    pub fn with_lsbs_per_sample(mut self, lsbs: u8) -> Self {
+        assert!((1..=8).contains(&lsbs), "lsbs per sample out of range");
+        self.lsbs_per_sample = lsbs;
+        self
+    }
+
+    /// Credit `bits` of min-entropy per raw output byte instead of the default
+    /// 1 bit.
+    ///
+    /// # Panics
+    /// Panics if `bits` is larger than 8.
+    #[must_use]
+    pub fn with_min_entropy_per_byte(mut self, bits: u8) -> Self {
+        assert!(bits <= 8, "a byte has at most 8 bits of entropy");
+        self.bits_per_byte = bits;
+        self
+    }
+
+    /// Debias and condition the raw bits, giving a full entropy source.
+    pub fn conditioned(self) -> HashConditioner<VonNeumann<Self>> {
+        HashConditioner::new(VonNeumann::new(self))
+    }
+
+    /// Unwrap the sample reading closure.
+    pub fn into_inner(self) -> F {
+        self.read_sample
+    }
+}
+
+impl<F: FnMut() -> Result<u16, E>, E> EntropySource for AdcNoise<F> {
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+        let lsbs = u32::from(self.lsbs_per_sample);
+        let mask = (1u16 << lsbs) - 1;
+        for byte in dest {
+            let mut acc = 0u16;
+            let mut bits = 0;
+            while bits < 8 {
+                let sample = (self.read_sample)()
+                    .map_err(|_| EntropySourceError::new("ADC conversion failed"))?;
+                acc = (acc << lsbs) | (sample & mask);
+                bits += lsbs;
+            }
+            *byte = acc.to_le_bytes()[0];
+        }
+        Ok(())
+    }
+
+    fn label(&self) -> &str {
+        "adc-noise"
+    }
+
+    fn entropy_credit(&self, len: usize) -> u64 {
+        len as u64 * u64::from(self.bits_per_byte)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AdcNoise;
+    use crate::{EntropySource, RngState};
+
+    #[test]
+    fn packs_lsbs() {
+        let mut samples = [1u16, 0, 1, 1, 0, 0, 1, 0].into_iter().cycle();
+        let mut noise = AdcNoise::new(|| Ok::<_, ()>(samples.next().unwrap()));
+        let mut buf = [0; 2];
+        noise.fill_entropy(&mut buf).unwrap();
+        assert_eq!(buf, [0b1011_0010; 2]);
+
+        let mut samples = [0xfff3u16, 0x0002].into_iter().cycle();
+        let mut noise =
+            AdcNoise::new(|| Ok::<_, ()>(samples.next().unwrap())).with_lsbs_per_sample(4);
+        noise.fill_entropy(&mut buf).unwrap();
+        assert_eq!(buf, [0x32; 2]);
+    }
+
+    #[test]
+    fn conditioned_noise_seeds_rng() {
+        let mut state = 1u32;
+        let mut noise = AdcNoise::new(|| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            Ok::<_, ()>(state.to_le_bytes()[2].into())
+        })
+        .conditioned();
+        assert!(noise.is_full_entropy());
+        let mut rng = RngState::new_unseeded();
+        rng.seed_from_entropy_source(&mut noise).unwrap();
+    }
+
+    #[test]
+    fn stuck_pin_fails() {
+        let mut noise = AdcNoise::new(|| Ok::<_, ()>(0x0fff)).conditioned();
+        assert!(noise.fill_entropy(&mut [0; 32]).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use std::io::Read;
+
+use crate::{EntropySource, EntropySourceError, HashConditioner};
+
+/// Default assessed min-entropy per sample, in bits.
+const DEFAULT_BITS_PER_SAMPLE: u64 = 1;
+
+/// Bytes per signed 16-bit little-endian sample.
+const SAMPLE_LEN: usize = 2;
+
+/// Raw noise [`EntropySource`] reading microphone or line-in noise as signed
+/// 16-bit little-endian PCM from `R`, for air-gapped key generation machines
+/// wanting a physical source beyond the OS RNG.
+///
+/// `R` is any reader of raw captured audio, e.g. the output of
+/// `arecord -f S16_LE -t raw` (ALSA) spawned as child process, or a capture
+/// file recorded beforehand. Leave the input unmuted and unconnected or
+/// pointed at a noisy environment; the noise of the analog front end is what
+/// provides the entropy.
+///
+/// The raw samples are credited with a conservative 1 bit of min-entropy per
+/// sample by default, see [`Self::with_min_entropy_per_sample`]. Use
+/// [`Self::conditioned`] to run them through a [`HashConditioner`] before
+/// absorbing them. Reads where all samples are equal, as produced by muted or
+/// disconnected inputs, fail instead of being credited.
+///
+/// # Examples
+/// ```no_run
+/// use std::process::{Command, Stdio};
+///
+/// use fast_erasure_shake_rng::{AudioNoise, RngState};
+///
+/// let mut arecord = Command::new("arecord")
+///     .args([
+///         "-q", "-f", "S16_LE", "-r", "48000", "-c", "1", "-t", "raw", "-d", "2",
+///     ])
+///     .stdout(Stdio::piped())
+///     .spawn()?;
+/// let mut noise = AudioNoise::new(arecord.stdout.take().unwrap()).conditioned();
+/// let mut rng = RngState::new_from_getrandom().unwrap();
+/// rng.seed_from_entropy_source(&mut noise).unwrap();
+/// arecord.kill()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "audio")))]
+#[derive(Debug)]
+pub struct AudioNoise<R> {
+    reader: R,
+    bits_per_sample: u64,
+}
+
+impl<R: Read> AudioNoise<R> {
+    /// Read signed 16-bit little-endian PCM samples from `reader`.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            bits_per_sample: DEFAULT_BITS_PER_SAMPLE,
+        }
+    }
+
+    /// Credit `bits` of min-entropy per sample instead of the default 1 bit.
+    /// Only raise this based on an assessment of your hardware, e.g. using the
+    /// NIST SP 800-90B estimators on a capture.
+    ///
+    /// # Panics
+    /// Panics if `bits` is larger than 16.
+    #[must_use]
+    pub fn with_min_entropy_per_sample(mut self, bits: u8) -> Self {
+        assert!(bits <= 16, "a 16-bit sample has at most 16 bits of entropy");
+        self.bits_per_sample = u64::from(bits);
+        self
+    }
+
+    /// Run the samples through a [`HashConditioner`], giving a full entropy
+    /// source.
+    pub fn conditioned(self) -> HashConditioner<Self> {
+        HashConditioner::new(self)
+    }
+
+    /// Unwrap the reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read> EntropySource for AudioNoise<R> {
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+        self.reader
+            .read_exact(dest)
+            .map_err(|_| EntropySourceError::new("failed to read audio samples"))?;
+        let mut samples = dest.chunks_exact(SAMPLE_LEN);
+        if let Some(first) = samples.next() {
+            if samples.len() > 0 && samples.all(|sample| sample == first) {
+                return Err(EntropySourceError::new("audio input is silent or muted"));
+            }
+        }
+        Ok(())
+    }
+
+    fn label(&self) -> &str {
+        "audio-noise"
+    }
+
+    fn entropy_credit(&self, len: usize) -> u64 {
+        (len / SAMPLE_LEN) as u64 * self.bits_per_sample
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::AudioNoise;
+    use crate::{EntropySource, RngState};
+
+    /// Capture of a noisy input: a deterministic stand-in for real samples.
+    fn capture(len: usize) -> Vec<u8> {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"audio tests");
+        let mut capture = vec![0; len];
+        rng.fill_random_bytes(&mut capture);
+        capture
+    }
+
+    #[test]
+    fn conditioned_noise_seeds_rng() {
+        let capture = capture(1 << 16);
+        let mut noise = AudioNoise::new(capture.as_slice()).conditioned();
+        assert!(noise.is_full_entropy());
+        let mut rng = RngState::new_unseeded();
+        rng.seed_from_entropy_source(&mut noise).unwrap();
+        assert_eq!(
+            AudioNoise::new(&[][..])
+                .with_min_entropy_per_sample(4)
+                .entropy_credit(10),
+            20
+        );
+    }
+
+    #[test]
+    fn rejects_silence_and_short_captures() {
+        let silence = [0x12, 0x00].repeat(64);
+        let mut noise = AudioNoise::new(silence.as_slice());
+        assert!(noise.fill_entropy(&mut [0; 64]).is_err());
+        let short = capture(10);
+        assert!(AudioNoise::new(short.as_slice())
+            .fill_entropy(&mut [0; 64])
+            .is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::time::Duration;
+
+use crate::xof::Shake256;
+
+/// Length in bytes of the input hash of a [`SeedEvent`].
+pub const SEED_EVENT_HASH_LEN: usize = 32;
+
+/// Record of a single seeding event of a [`ManagedRng`](crate::ManagedRng).
+///
+/// Only a hash of the seeding input is recorded, never the input itself. Note
+/// that a hash of a low entropy input (like a password) still allows an
+/// attacker to brute-force the input, so treat the transcript as sensitive
+/// when seeding with such inputs.
+#[cfg_attr(docsrs, doc(cfg(feature = "audit")))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SeedEvent {
+    timestamp: Option<Duration>,
+    source: String,
+    input_len: u64,
+    input_hash: [u8; SEED_EVENT_HASH_LEN],
+}
+
+impl SeedEvent {
+    /// Time of the event as duration since the UNIX epoch. Only available with
+    /// the `std` feature.
+    pub fn timestamp(&self) -> Option<Duration> {
+        self.timestamp
+    }
+
+    /// Label of the entropy source or kind of input, see
+    /// [`EntropySource::label`](crate::EntropySource::label).
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Number of input bytes absorbed.
+    pub fn input_len(&self) -> u64 {
+        self.input_len
+    }
+
+    /// SHAKE256 hash of the input bytes.
+    pub fn input_hash(&self) -> &[u8; SEED_EVENT_HASH_LEN] {
+        &self.input_hash
+    }
+}
+
+impl fmt::Display for SeedEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.timestamp {
+            Some(t) => write!(f, "{}.{:09}", t.as_secs(), t.subsec_nanos())?,
+            None => f.write_str("-")?,
+        }
+        write!(f, " {} {} ", self.source, self.input_len)?;
+        self.input_hash
+            .iter()
+            .try_for_each(|b| write!(f, "{:02x}", b))
+    }
+}
+
+/// Transcript of all seeding events of a [`ManagedRng`](crate::ManagedRng),
+/// enabled using
+/// [`RngBuilder::audit_transcript`](crate::RngBuilder::audit_transcript).
+///
+/// Displays as a report with one line per event, containing the timestamp, the
+/// source label, the input length and the hex encoded input hash.
+#[cfg_attr(docsrs, doc(cfg(feature = "audit")))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SeedTranscript {
+    events: Vec<SeedEvent>,
+}
+
+impl SeedTranscript {
+    /// The recorded events, oldest first.
+    pub fn events(&self) -> &[SeedEvent] {
+        &self.events
+    }
+
+    pub(crate) fn push(&mut self, event: SeedEvent) {
+        self.events.push(event);
+    }
+}
+
+impl fmt::Display for SeedTranscript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.events.iter().try_for_each(|e| writeln!(f, "{}", e))
+    }
+}
+
+/// Hashes the input of a seeding event while it is being absorbed.
+pub(crate) struct EventRecorder {
+    hasher: Shake256,
+    input_len: u64,
+}
+
+impl EventRecorder {
+    pub(crate) fn new() -> Self {
+        let mut hasher = Shake256::new();
+        hasher.absorb_prefixed(b"fast-erasure-shake-rng seed event");
+        Self {
+            hasher,
+            input_len: 0,
+        }
+    }
+
+    pub(crate) fn update(&mut self, input: &[u8]) {
+        self.hasher.absorb(input);
+        self.input_len += input.len() as u64;
+    }
+
+    /// Finish the event, timestamping it if `timed` (reading the clock may
+    /// take a syscall).
+    pub(crate) fn finish(mut self, source: &str, timed: bool) -> SeedEvent {
+        let mut input_hash = [0; SEED_EVENT_HASH_LEN];
+        self.hasher.squeeze(&mut input_hash);
+        SeedEvent {
+            timestamp: if timed { now() } else { None },
+            source: source.into(),
+            input_len: self.input_len,
+            input_hash,
+        }
+    }
+}
+
+/// Current time as duration since the UNIX epoch, if available.
+fn now() -> Option<Duration> {
+    #[cfg(feature = "std")]
+    return std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok();
+    #[cfg(not(feature = "std"))]
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EventRecorder;
+
+    #[test]
+    fn hash_covers_all_chunks() {
+        let mut chunked = EventRecorder::new();
+        chunked.update(b"hello ");
+        chunked.update(b"world");
+        let mut whole = EventRecorder::new();
+        whole.update(b"hello world");
+        let (chunked, whole) = (chunked.finish("a", true), whole.finish("a", true));
+        assert_eq!(chunked.input_hash(), whole.input_hash());
+        assert_eq!(chunked.input_len(), 11);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::xof::Shake256;
+use crate::RngState;
+
+/// Length in bytes of the committed value of a draw.
+pub const DRAW_VALUE_LEN: usize = 32;
+
+/// Domain separation label of the commitment to a draw.
+const COMMIT_LABEL: &[u8] = b"fast-erasure-shake-rng draw commitment";
+/// Domain separation label of the outcome derivation of a draw.
+const OUTCOME_LABEL: &[u8] = b"fast-erasure-shake-rng draw outcome";
+
+/// Published commitment to a random draw, see [`RngState::commit_draw`].
+#[cfg_attr(docsrs, doc(cfg(feature = "beacon")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DrawCommitment([u8; 32]);
+
+impl DrawCommitment {
+    /// Recreate a commitment from its published bytes.
+    pub const fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// The bytes to publish.
+    pub const fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Verify that `reveal` opens this commitment for the draw identified by
+    /// `context`.
+    pub fn verify(&self, context: &[u8], reveal: &DrawReveal) -> bool {
+        commitment(context, &reveal.0) == self.0
+    }
+}
+
+/// The secret value of a random draw, published after the commitment to reveal
+/// the outcome, see [`RngState::commit_draw`].
+#[cfg_attr(docsrs, doc(cfg(feature = "beacon")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DrawReveal([u8; DRAW_VALUE_LEN]);
+
+impl DrawReveal {
+    /// Recreate a reveal from its published bytes.
+    pub const fn from_bytes(bytes: [u8; DRAW_VALUE_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    /// The bytes to publish when revealing.
+    pub const fn as_bytes(&self) -> &[u8; DRAW_VALUE_LEN] {
+        &self.0
+    }
+
+    /// The outcome of the draw identified by `context` as a number in the
+    /// range `0..n`, e.g. the index of the winner among `n` participants.
+    ///
+    /// The outcome is derived from the revealed value using SHAKE256 with
+    /// rejection sampling, so it is unbiased and anyone can recompute it on
+    /// any platform.
+    ///
+    /// # Panics
+    /// Panics if `n == 0`.
+    pub fn outcome_below(&self, context: &[u8], n: u64) -> u64 {
+        assert!(n != 0, "cannot draw from an empty range");
+        let mut xof = Shake256::new();
+        xof.absorb_prefixed(OUTCOME_LABEL);
+        xof.absorb_prefixed(context);
+        xof.absorb_prefixed(&self.0);
+        let mask = u64::MAX.checked_shr((n - 1).leading_zeros()).unwrap_or(0);
+        loop {
+            let mut candidate = [0; 8];
+            xof.squeeze(&mut candidate);
+            let candidate = u64::from_le_bytes(candidate) & mask;
+            if candidate < n {
+                return candidate;
+            }
+        }
+    }
+}
+
+/// Commitment to `value` for the draw identified by `context`.
+fn commitment(context: &[u8], value: &[u8; DRAW_VALUE_LEN]) -> [u8; 32] {
+    let mut xof = Shake256::new();
+    xof.absorb_prefixed(COMMIT_LABEL);
+    xof.absorb_prefixed(context);
+    xof.absorb_prefixed(value);
+    let mut out = [0; 32];
+    xof.squeeze(&mut out);
+    out
+}
+
+impl RngState {
+    /// Draw a random value for a verifiable draw (lottery, giveaway, ...),
+    /// returning a commitment to publish before the draw and the reveal to
+    /// publish afterwards. The RNG MUST be seeded prior to using this method.
+    ///
+    /// `context` identifies the draw, e.g. a draw number and a hash of the
+    /// list of participants; it is bound into the commitment so a reveal can't
+    /// be reused for another draw. Since it contains the participants, commit
+    /// only once the participant set is fixed. After publishing the reveal
+    /// anyone can check it against the commitment using
+    /// [`DrawCommitment::verify`] and recompute the outcome using
+    /// [`DrawReveal::outcome_below`]. The RNG state stays secret throughout.
+    ///
+    /// # Security
+    /// The commitment only proves that the organizer didn't change the value
+    /// after publishing it. It does not make the draw unbiasable by the
+    /// organizer: whoever holds the reveal knows the outcome before
+    /// committing, and could e.g. draw again until the outcome suits them.
+    /// Participants have to trust the organizer to publish the first
+    /// commitment they draw.
+    ///
+    /// # Examples
+    /// ```
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// // once the participants are fixed
+    /// let context = b"giveaway 2024-05, 10 participants";
+    /// let (commitment, reveal) = rng.commit_draw(context);
+    /// // publish `commitment`, later publish `reveal`
+    /// assert!(commitment.verify(context, &reveal));
+    /// let winner = reveal.outcome_below(context, 10);
+    /// assert!(winner < 10);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "beacon")))]
+    pub fn commit_draw(&mut self, context: &[u8]) -> (DrawCommitment, DrawReveal) {
+        let reveal = DrawReveal(self.get_random_bytes());
+        (DrawCommitment(commitment(context, &reveal.0)), reveal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RngState;
+
+    fn test_rng() -> RngState {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"beacon tests");
+        rng
+    }
+
+    #[test]
+    fn commitment_binds_context() {
+        let (commitment, reveal) = test_rng().commit_draw(b"draw 1");
+        assert!(commitment.verify(b"draw 1", &reveal));
+        assert!(!commitment.verify(b"draw 2", &reveal));
+    }
+
+    #[test]
+    fn outcome_is_reproducible() {
+        let (_, reveal) = test_rng().commit_draw(b"draw");
+        let outcome = reveal.outcome_below(b"draw", 1000);
+        assert!(outcome < 1000);
+        assert_eq!(reveal.outcome_below(b"draw", 1000), outcome);
+        assert_eq!(reveal.outcome_below(b"draw", 1), 0);
+    }
+}
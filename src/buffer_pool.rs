@@ -0,0 +1,128 @@
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::ops::{Deref, DerefMut};
+
+use zeroize::Zeroize;
+
+use crate::RngState;
+
+/// A pool of reusable byte buffers for repeated large random fills, e.g. in
+/// services generating many large random payloads, avoiding an allocation
+/// per fill.
+///
+/// Buffers are handed out as [`PooledBuffer`]s, which are wiped when dropped
+/// and returned to the pool, so random data never lingers in pooled memory.
+/// At most `max_pooled` buffers are kept; the pool is meant to be owned by a
+/// single thread (e.g. one per worker).
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::{BufferPool, RngState};
+///
+/// let mut rng = RngState::new_from_getrandom().unwrap();
+/// let pool = BufferPool::new(4);
+/// for _ in 0..100 {
+///     let payload = pool.fill_random(&mut rng, 64 * 1024);
+///     // send `payload` ...
+/// } // every payload is wiped and its buffer reused
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[derive(Debug)]
+pub struct BufferPool {
+    buffers: RefCell<Vec<Vec<u8>>>,
+    max_pooled: usize,
+}
+
+impl BufferPool {
+    /// Create an empty pool keeping at most `max_pooled` returned buffers.
+    pub fn new(max_pooled: usize) -> Self {
+        Self {
+            buffers: RefCell::new(Vec::new()),
+            max_pooled,
+        }
+    }
+
+    /// Take a zero-filled buffer of `len` bytes from the pool, allocating a
+    /// new one if none is available.
+    pub fn get(&self, len: usize) -> PooledBuffer<'_> {
+        let mut buffer = self.buffers.borrow_mut().pop().unwrap_or_default();
+        buffer.resize(len, 0);
+        PooledBuffer { buffer, pool: self }
+    }
+
+    /// Take a buffer of `len` bytes from the pool and fill it with random
+    /// bytes from `rng`, which MUST be seeded.
+    pub fn fill_random(&self, rng: &mut RngState, len: usize) -> PooledBuffer<'_> {
+        let mut buffer = self.get(len);
+        rng.fill_random_bytes(&mut buffer);
+        buffer
+    }
+
+    /// Number of buffers currently in the pool.
+    pub fn pooled(&self) -> usize {
+        self.buffers.borrow().len()
+    }
+}
+
+/// A byte buffer taken from a [`BufferPool`], wiped and returned to the pool
+/// on drop.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub struct PooledBuffer<'p> {
+    buffer: Vec<u8>,
+    pool: &'p BufferPool,
+}
+
+impl Deref for PooledBuffer<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.buffer
+    }
+}
+
+impl DerefMut for PooledBuffer<'_> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.buffer
+    }
+}
+
+impl Drop for PooledBuffer<'_> {
+    fn drop(&mut self) {
+        // wipes the whole capacity and truncates
+        self.buffer.zeroize();
+        let mut buffers = self.pool.buffers.borrow_mut();
+        if buffers.len() < self.pool.max_pooled {
+            buffers.push(core::mem::take(&mut self.buffer));
+        }
+    }
+}
+
+impl core::fmt::Debug for PooledBuffer<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "PooledBuffer({} bytes, [REDACTED])", self.buffer.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BufferPool;
+    use crate::RngState;
+
+    #[test]
+    fn buffers_are_reused_and_wiped() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"buffer pool tests");
+        let mut reference = RngState::new_unseeded();
+        reference.seed(b"buffer pool tests");
+        let pool = BufferPool::new(1);
+        let (a, b) = (pool.fill_random(&mut rng, 100), pool.get(10));
+        assert_eq!(*a, reference.get_random_bytes::<100>());
+        let ptr = a.as_ptr();
+        drop((a, b));
+        assert_eq!(pool.pooled(), 1);
+        let c = pool.get(50);
+        assert_eq!(c.as_ptr(), ptr);
+        assert!(c.iter().all(|&b| b == 0));
+        assert_eq!(pool.pooled(), 0);
+    }
+}
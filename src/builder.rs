@@ -0,0 +1,1010 @@
+use core::num::NonZeroUsize;
+use core::time::Duration;
+
+use crate::health::{HealthTestConfig, MIN_ALPHA_LOG2};
+use crate::{EntropySource, ManagedRng, PersistentCounter, RngError, RngEvent, RngObserver};
+
+/// Maximum number of entropy sources which can be added to an [`RngBuilder`].
+pub const MAX_ENTROPY_SOURCES: usize = 8;
+
+/// Minimum entropy in bits which must be required before output for a
+/// configuration to be approved, see [`RngBuilder::approved_mode`].
+pub const APPROVED_MIN_ENTROPY: u64 = 256;
+
+/// Maximum number of bytes per output request of NIST SP 800-90A DRBGs
+/// (`2^19` bits), a common choice for [`RngBuilder::max_request_size`].
+pub const NIST_MAX_REQUEST_BYTES: usize = 1 << 16;
+
+/// Policy for producing the output of a [`ManagedRng`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OutputPolicy {
+    /// Produce output like [`crate::RngState::fill_random_bytes`]: after the
+    /// first block, both the rate area and the zeroized capacity area are
+    /// output and the state is made forward secure once at the end of every
+    /// request.
+    #[default]
+    Fast,
+    /// Only ever output the rate area, and make the state forward secure after
+    /// every output block. The zeroized capacity area is never output. This
+    /// is roughly half as fast as [`Self::Fast`] for large requests.
+    Conservative,
+    /// Like [`Self::Fast`], but large requests are split so the state is made
+    /// forward secure at least every `n` output bytes. A state compromise
+    /// during a large request then only reveals the output of the current
+    /// chunk.
+    RatchetInterval(NonZeroUsize),
+}
+
+/// How a [`ManagedRng`] in degraded mode treats output requests while it is
+/// degraded, see [`RngBuilder::degraded_mode`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DegradedOutput {
+    /// Refuse output with [`RngError::Degraded`] until upgraded.
+    Refuse,
+    /// Produce output; callers check [`ManagedRng::is_degraded`] to label it.
+    Label,
+}
+
+/// What a [`ManagedRng`] does when a self-test fails: a health test of an
+/// entropy source or the known-answer test run in approved mode, see
+/// [`RngBuilder::failure_policy`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FailurePolicy {
+    /// Panic, for fail-stop appliances.
+    Panic,
+    /// Return the error to the caller and carry on.
+    #[default]
+    Error,
+    /// Return the error and poison the RNG: all further output requests fail
+    /// with [`RngError::Poisoned`] until it is recovered, see
+    /// [`ManagedRng::poison`].
+    Poison,
+}
+
+/// Domain separation label absorbed before the personalization string.
+const PERSONALIZATION_LABEL: &[u8] = b"fast-erasure-shake-rng personalization";
+/// Domain separation label absorbed before the hedging input.
+const HEDGE_LABEL: &[u8] = b"fast-erasure-shake-rng hedge";
+/// Domain separation label absorbed before the persistent counter value.
+const PERSISTENT_COUNTER_LABEL: &[u8] = b"fast-erasure-shake-rng persistent counter";
+
+/// Builder for a [`ManagedRng`], configuring how the RNG is seeded and how it
+/// produces output in one expression.
+///
+/// By default the RNG is seeded from the OS RNG (if the `getrandom` feature is
+/// enabled), uses [`OutputPolicy::Fast`] and runs no health tests.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "getrandom")] {
+/// use fast_erasure_shake_rng::{HealthTestConfig, OutputPolicy, RngBuilder};
+///
+/// let mut rng = RngBuilder::new()
+///     .personalization(b"example.com session keys")
+///     .hedge(b"host-42")
+///     .output_policy(OutputPolicy::Conservative)
+///     .health_tests(HealthTestConfig::for_min_entropy(8))
+///     .build()
+///     .unwrap();
+/// let key = rng.get_random_bytes::<32>();
+/// # }
+/// ```
+pub struct RngBuilder<'a> {
+    personalization: &'a [u8],
+    hedge: &'a [u8],
+    persistent_counter: Option<&'a mut dyn PersistentCounter>,
+    sources: [Option<&'a mut dyn EntropySource>; MAX_ENTROPY_SOURCES],
+    #[cfg(feature = "getrandom")]
+    getrandom: bool,
+    #[cfg(all(feature = "getrandom", feature = "std"))]
+    block_until_os_seeded: bool,
+    output_policy: OutputPolicy,
+    health_tests: Option<HealthTestConfig>,
+    degraded_output: Option<DegradedOutput>,
+    required_entropy: u64,
+    required_sources: Option<(usize, u64)>,
+    max_request_size: Option<usize>,
+    approved_mode: bool,
+    failure_policy: FailurePolicy,
+    power_analysis_hardening: bool,
+    observer: Option<RngObserver>,
+    reseed_interval: Option<Duration>,
+    #[cfg(target_has_atomic = "64")]
+    output_counter: bool,
+    #[cfg(feature = "audit")]
+    audit_transcript: bool,
+    #[cfg(feature = "replay")]
+    replay: Option<crate::replay::ReplayMode>,
+}
+
+impl<'a> RngBuilder<'a> {
+    /// Create a builder with the default configuration.
+    pub fn new() -> Self {
+        Self {
+            personalization: &[],
+            hedge: &[],
+            persistent_counter: None,
+            sources: core::array::from_fn(|_| None),
+            #[cfg(feature = "getrandom")]
+            getrandom: true,
+            #[cfg(all(feature = "getrandom", feature = "std"))]
+            block_until_os_seeded: false,
+            output_policy: OutputPolicy::default(),
+            health_tests: None,
+            degraded_output: None,
+            required_entropy: 0,
+            required_sources: None,
+            max_request_size: None,
+            approved_mode: false,
+            failure_policy: FailurePolicy::default(),
+            power_analysis_hardening: false,
+            observer: None,
+            reseed_interval: None,
+            #[cfg(target_has_atomic = "64")]
+            output_counter: false,
+            #[cfg(feature = "audit")]
+            audit_transcript: false,
+            #[cfg(feature = "replay")]
+            replay: None,
+        }
+    }
+
+    /// Create a builder with the hardened "paranoid" preset, for high
+    /// assurance users who want a consistent configuration without hunting
+    /// through the individual options:
+    ///
+    /// - [`OutputPolicy::Conservative`] output,
+    /// - health tests of all entropy sources, assuming full entropy (8 bits per
+    ///   byte) with a false positive probability of `2^-40` per test, the
+    ///   smallest NIST SP 800-90B recommends (repetition count cutoff 6 and
+    ///   adaptive proportion cutoff 19), so a healthy OS RNG practically never
+    ///   fails them,
+    /// - [`APPROVED_MIN_ENTROPY`] bits of required entropy,
+    /// - requests capped at [`NIST_MAX_REQUEST_BYTES`],
+    /// - the [`FailurePolicy::Poison`] failure policy,
+    /// - power analysis hardening, see [`Self::power_analysis_hardening`].
+    ///
+    /// Like every [`ManagedRng`], the state is zeroized on drop. Memory
+    /// locking is left to the process (e.g. `mlockall`), since the RNG moves
+    /// with its owner. The individual options can still be adjusted; e.g. use
+    /// [`Self::health_tests`] to set the health tests for raw noise sources.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::RngBuilder;
+    ///
+    /// let mut rng = RngBuilder::paranoid().build().unwrap();
+    /// let key = rng.get_random_bytes::<32>();
+    /// # }
+    /// ```
+    pub fn paranoid() -> Self {
+        Self::new()
+            .output_policy(OutputPolicy::Conservative)
+            .health_tests(HealthTestConfig::for_min_entropy_and_alpha(
+                8,
+                MIN_ALPHA_LOG2,
+            ))
+            .require_entropy(APPROVED_MIN_ENTROPY)
+            .max_request_size(NIST_MAX_REQUEST_BYTES)
+            .failure_policy(FailurePolicy::Poison)
+            .power_analysis_hardening()
+    }
+
+    /// Set the personalization string: application specific data separating
+    /// this RNG instance from instances used for other purposes, e.g. an
+    /// application and protocol name. Needn't be secret.
+    pub fn personalization(mut self, personalization: &'a [u8]) -> Self {
+        self.personalization = personalization;
+        self
+    }
+
+    /// Set the hedging input: data unique to this instance, like a device
+    /// serial number, MAC address or boot time. It doesn't need to be secret
+    /// or random, but hedges against entropy sources which deliver the same
+    /// output to multiple instances, e.g. after cloning a virtual machine.
+    pub fn hedge(mut self, hedge: &'a [u8]) -> Self {
+        self.hedge = hedge;
+        self
+    }
+
+    /// Set a counter persisted across boots, which is read, incremented and
+    /// absorbed when building, so the output differs on every boot even if
+    /// all other inputs repeat. See [`PersistentCounter`].
+    pub fn persistent_counter(mut self, counter: &'a mut dyn PersistentCounter) -> Self {
+        self.persistent_counter = Some(counter);
+        self
+    }
+
+    /// Add an entropy source to seed the RNG from, in addition to the OS RNG.
+    ///
+    /// # Panics
+    /// Panics if more than [`MAX_ENTROPY_SOURCES`] sources are added.
+    pub fn entropy_source(mut self, source: &'a mut dyn EntropySource) -> Self {
+        let slot = self
+            .sources
+            .iter_mut()
+            .find(|slot| slot.is_none())
+            .expect("too many entropy sources");
+        *slot = Some(source);
+        self
+    }
+
+    /// Set whether to seed the RNG from the OS RNG. Enabled by default.
+    #[cfg(feature = "getrandom")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "getrandom")))]
+    pub fn getrandom(mut self, enable: bool) -> Self {
+        self.getrandom = enable;
+        self
+    }
+
+    /// Block until the OS RNG is initialized before seeding from it, see
+    /// [`RngState::new_from_getrandom_blocking`](crate::RngState::new_from_getrandom_blocking).
+    /// Has no effect if seeding from the OS RNG is disabled.
+    #[cfg(all(feature = "getrandom", feature = "std"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "getrandom", feature = "std"))))]
+    pub fn block_until_os_seeded(mut self) -> Self {
+        self.block_until_os_seeded = true;
+        self
+    }
+
+    /// Set the output policy.
+    pub fn output_policy(mut self, policy: OutputPolicy) -> Self {
+        self.output_policy = policy;
+        self
+    }
+
+    /// Run the continuous health tests with configuration `config` on the
+    /// output of all entropy sources, when building and on later reseeds.
+    ///
+    /// With health tests enabled, 1024 bytes are drawn from every source to
+    /// satisfy the startup testing of NIST SP 800-90B instead of the usual 64,
+    /// or 2560 bytes if the AIS 31 online tests are enabled.
+    pub fn health_tests(mut self, config: HealthTestConfig) -> Self {
+        self.health_tests = Some(config);
+        self
+    }
+
+    /// Enable degraded mode, for early boot when no full entropy source may
+    /// be available yet.
+    ///
+    /// In degraded mode, failing entropy sources don't fail the build; the RNG
+    /// is seeded from whatever is available, including the personalization
+    /// and hedging inputs. Until it has absorbed entropy from a
+    /// [full entropy](EntropySource::is_full_entropy) source the RNG is
+    /// degraded, and `output` determines whether it refuses or produces
+    /// output. A degraded RNG upgrades itself by trying the OS RNG (if
+    /// enabled) before every output request, or when reseeded from a full
+    /// entropy source using [`ManagedRng::reseed_from`].
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::{DegradedOutput, RngBuilder};
+    ///
+    /// let mut rng = RngBuilder::new()
+    ///     .hedge(b"device serial 1234")
+    ///     .degraded_mode(DegradedOutput::Refuse)
+    ///     .build()
+    ///     .unwrap();
+    /// // the OS RNG is available here, so the RNG isn't degraded
+    /// assert!(!rng.is_degraded());
+    /// # }
+    /// ```
+    pub fn degraded_mode(mut self, output: DegradedOutput) -> Self {
+        self.degraded_output = Some(output);
+        self
+    }
+
+    /// Refuse output until at least `bits` bits of entropy have been credited
+    /// to the absorbed inputs, e.g. 256. Entropy is credited per source using
+    /// [`EntropySource::entropy_credit`] and to explicit seeds using
+    /// [`ManagedRng::seed_credited`]. Output methods return
+    /// [`RngError::InsufficientEntropy`] until the threshold is reached; under
+    /// `std`, [`ManagedRng::reseed_until_credited`] blocks until it is.
+    ///
+    /// A build with credited entropy below the threshold still succeeds, so
+    /// more entropy can be added later.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::RngBuilder;
+    ///
+    /// // the OS RNG is credited with 512 bits
+    /// let mut rng = RngBuilder::new().require_entropy(256).build().unwrap();
+    /// assert!(rng.try_get_random_bytes::<32>().is_ok());
+    /// # }
+    /// ```
+    pub fn require_entropy(mut self, bits: u64) -> Self {
+        self.required_entropy = bits;
+        self
+    }
+
+    /// Refuse output until at least `count` differently labeled sources (see
+    /// [`EntropySource::label`]) have each been credited with at least
+    /// `bits_each` bits of entropy, e.g. `require_independent_sources(2, 256)`
+    /// for a two-man rule. This protects against a single compromised or
+    /// failed source at instantiation: no single source can seed the RNG on
+    /// its own. Credit is counted like for [`Self::require_entropy`]; explicit
+    /// seeds passed to [`ManagedRng::seed_credited`] count as one source
+    /// labeled `seed`. Output methods return [`RngError::InsufficientSources`]
+    /// until the rule is met.
+    ///
+    /// Sources are only told apart by their labels, so give independent
+    /// sources distinct labels.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::{EntropySource, EntropySourceError, RngBuilder, RngError};
+    ///
+    /// /// A hardware TRNG delivering full entropy.
+    /// struct Trng;
+    ///
+    /// impl EntropySource for Trng {
+    ///     fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+    ///         # dest.fill(0x5a);
+    ///         // read the TRNG here
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn label(&self) -> &str {
+    ///         "trng"
+    ///     }
+    ///
+    ///     fn is_full_entropy(&self) -> bool {
+    ///         true
+    ///     }
+    /// }
+    ///
+    /// // the OS RNG and the TRNG must both contribute
+    /// let mut trng = Trng;
+    /// let mut rng = RngBuilder::new()
+    ///     .entropy_source(&mut trng)
+    ///     .require_independent_sources(2, 256)
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(rng.try_get_random_bytes::<32>().is_ok());
+    /// # }
+    /// ```
+    pub fn require_independent_sources(mut self, count: usize, bits_each: u64) -> Self {
+        self.required_sources = Some((count, bits_each));
+        self
+    }
+
+    /// Refuse output requests of more than `max_bytes` bytes, e.g.
+    /// [`NIST_MAX_REQUEST_BYTES`], enforcing DRBG-like usage discipline on
+    /// callers. Oversized requests make the `try_` output methods of
+    /// [`ManagedRng`] return [`RngError::RequestTooLarge`]. Unlimited by
+    /// default.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::{RngBuilder, RngError};
+    ///
+    /// let mut rng = RngBuilder::new().max_request_size(32).build().unwrap();
+    /// assert!(rng.try_get_random_bytes::<32>().is_ok());
+    /// assert_eq!(
+    ///     rng.try_get_random_bytes::<33>(),
+    ///     Err(RngError::RequestTooLarge {
+    ///         requested: 33,
+    ///         max: 32
+    ///     })
+    /// );
+    /// # }
+    /// ```
+    pub fn max_request_size(mut self, max_bytes: usize) -> Self {
+        self.max_request_size = Some(max_bytes);
+        self
+    }
+
+    /// Set the interval of time-based reseeding with
+    /// [`ManagedRng::reseed_if_due`], measured by a
+    /// [`MonotonicClock`](crate::MonotonicClock). Disabled by default.
+    pub fn reseed_interval(mut self, interval: Duration) -> Self {
+        self.reseed_interval = Some(interval);
+        self
+    }
+
+    /// Absorb a process-wide counter into the state before every output
+    /// request, so that copies of the state in the same process (e.g. from a
+    /// careless `memcpy` or a duplicated struct) diverge from their first
+    /// output on. Costs one extra application of the permutation per request.
+    /// Disabled by default.
+    ///
+    /// The counter is absorbed into the rate area like any seed, and the
+    /// following permutation mixes it into the whole state before output is
+    /// taken from the rate and zeroized capacity areas. It adds no entropy
+    /// and doesn't touch the capacity areas otherwise, so the erasure after
+    /// each request works as without the counter.
+    ///
+    /// The counter lives in process memory, so it is duplicated along with
+    /// the state by `fork` or by cloning a virtual machine; reseed from the OS
+    /// RNG to recover from those.
+    #[cfg(target_has_atomic = "64")]
+    pub fn output_counter(mut self) -> Self {
+        self.output_counter = true;
+        self
+    }
+
+    /// Run in approved mode, for modules embedding this crate which expose a
+    /// FIPS 140-3 style approved mode of operation.
+    ///
+    /// A configuration is approved if health tests are enabled, at least
+    /// [`APPROVED_MIN_ENTROPY`] bits of entropy are required before output,
+    /// requests are limited to at most [`NIST_MAX_REQUEST_BYTES`], no entropy
+    /// is replayed and the RNG is not degraded. In approved mode,
+    /// building with a configuration which is not approved fails and output
+    /// is refused with [`RngError::NotApproved`] while the RNG is degraded.
+    /// Building in approved mode also runs a known-answer test of the
+    /// permutation, see [`Self::failure_policy`]. Regardless of the mode,
+    /// [`ManagedRng::last_output_approved`] serves as service indicator for
+    /// every output operation.
+    ///
+    /// Whether the crate's construction is acceptable to a validation
+    /// authority is up to the embedding module; this only enforces the
+    /// configuration.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::{
+    ///     HealthTestConfig, RngBuilder, APPROVED_MIN_ENTROPY, NIST_MAX_REQUEST_BYTES,
+    /// };
+    ///
+    /// let mut rng = RngBuilder::new()
+    ///     .health_tests(HealthTestConfig::for_min_entropy(8))
+    ///     .require_entropy(APPROVED_MIN_ENTROPY)
+    ///     .max_request_size(NIST_MAX_REQUEST_BYTES)
+    ///     .approved_mode()
+    ///     .build()
+    ///     .unwrap();
+    /// let key = rng.get_random_bytes::<32>();
+    /// assert!(rng.last_output_approved());
+    /// # }
+    /// ```
+    pub fn approved_mode(mut self) -> Self {
+        self.approved_mode = true;
+        self
+    }
+
+    /// Set what happens when a self-test fails: the health tests of the
+    /// entropy sources (see [`Self::health_tests`]) or the known-answer test
+    /// of the permutation run when building in approved mode. Defaults to
+    /// [`FailurePolicy::Error`], which suits libraries; appliances may prefer
+    /// fail-stop behavior with [`FailurePolicy::Panic`] or
+    /// [`FailurePolicy::Poison`].
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::{FailurePolicy, HealthTestConfig, RngBuilder};
+    ///
+    /// let mut rng = RngBuilder::new()
+    ///     .health_tests(HealthTestConfig::for_min_entropy(8))
+    ///     .failure_policy(FailurePolicy::Poison)
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(!rng.is_poisoned());
+    /// # }
+    /// ```
+    pub fn failure_policy(mut self, policy: FailurePolicy) -> Self {
+        self.failure_policy = policy;
+        self
+    }
+
+    /// Harden the RNG against simple and differential power analysis (SPA,
+    /// DPA), for smartcard and secure element style deployments where an
+    /// attacker measures power consumption or electromagnetic emanation.
+    /// Disabled by default.
+    ///
+    /// Every application of the permutation to the state is hidden between a
+    /// random number (0 to 3 on each side) of dummy permutations of a decoy
+    /// state, and the bytes absorbed into or output from the state are
+    /// processed starting at a random offset. This desynchronizes the traces
+    /// of repeated operations, so an attacker can no longer align and average
+    /// them. The decoy is initialized from the seeded RNG, so the initial
+    /// seeding by [`Self::build`] itself is not hardened. Otherwise the
+    /// output only differs from that without hardening by the decoy
+    /// initialization.
+    ///
+    /// The cost is about three extra permutations per real permutation, so
+    /// hardened seeding and output are roughly four times slower, and more
+    /// variable in duration. These countermeasures raise the number of traces
+    /// needed for an attack; they are no substitute for hardware
+    /// countermeasures.
+    pub fn power_analysis_hardening(mut self) -> Self {
+        self.power_analysis_hardening = true;
+        self
+    }
+
+    /// Report instantiation, reseeds, entropy source failures and refused
+    /// output requests to `observer`, e.g. to forward them to a logging or
+    /// tracing framework. See [`RngObserver`] for an example.
+    pub fn observer(mut self, observer: RngObserver) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Set whether to record a transcript of all seeding events, retrievable
+    /// using [`ManagedRng::transcript`]. Disabled by default.
+    #[cfg(feature = "audit")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "audit")))]
+    pub fn audit_transcript(mut self, enable: bool) -> Self {
+        self.audit_transcript = enable;
+        self
+    }
+
+    /// Capture all entropy obtained from entropy sources into a replay log,
+    /// retrievable using [`ManagedRng::replay_log`]. NOT FOR PRODUCTION USE,
+    /// see [`ReplayLog`](crate::ReplayLog).
+    #[cfg(feature = "replay")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "replay")))]
+    pub fn capture_replay(mut self) -> Self {
+        self.replay = Some(crate::replay::ReplayMode::Capture(crate::ReplayLog::new()));
+        self
+    }
+
+    /// Take all entropy from the captured `log` instead of the entropy
+    /// sources, reconstructing the captured RNG. The builder must be
+    /// configured exactly like the one the log was captured with. NOT FOR
+    /// PRODUCTION USE, see [`ReplayLog`](crate::ReplayLog).
+    #[cfg(feature = "replay")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "replay")))]
+    pub fn replay(mut self, log: crate::ReplayLog) -> Self {
+        self.replay = Some(crate::replay::ReplayMode::Playback(log));
+        self
+    }
+
+    /// Build the RNG, seeding it from all configured entropy sources.
+    ///
+    /// # Errors
+    /// Returns an error if an entropy source or the persistent counter fails
+    /// or the output of a source fails a health test, or if no entropy source
+    /// is configured at all. In approved
+    /// mode, returns [`RngError::NotApproved`] if the configuration is not
+    /// approved and [`RngError::SelfTest`] if the known-answer test fails.
+    ///
+    /// # Panics
+    /// Panics if a self-test fails with [`FailurePolicy::Panic`].
+    pub fn build(mut self) -> Result<ManagedRng, RngError> {
+        let mut rng = ManagedRng::new_unseeded();
+        rng.output_policy = self.output_policy;
+        rng.health_tests = self.health_tests;
+        rng.degraded_output = self.degraded_output;
+        rng.required_entropy = self.required_entropy;
+        rng.required_sources = self.required_sources;
+        rng.max_request_size = self.max_request_size;
+        rng.approved_mode = self.approved_mode;
+        rng.failure_policy = self.failure_policy;
+        rng.observer = self.observer;
+        rng.reseed_interval = self.reseed_interval;
+        #[cfg(target_has_atomic = "64")]
+        {
+            rng.output_counter = self.output_counter;
+        }
+        rng.mark_degraded();
+        if self.approved_mode && !crate::managed::permutation_known_answer_test() {
+            return Err(rng.fail(RngError::SelfTest));
+        }
+        #[cfg(feature = "getrandom")]
+        {
+            rng.upgrade_from_getrandom = self.getrandom;
+        }
+        #[cfg(feature = "audit")]
+        if self.audit_transcript {
+            rng.enable_transcript();
+        }
+        #[cfg(feature = "replay")]
+        if let Some(mode) = self.replay.take() {
+            rng.set_replay(mode);
+        }
+        if !self.personalization.is_empty() {
+            rng.absorb_input(
+                "personalization",
+                PERSONALIZATION_LABEL,
+                self.personalization,
+            );
+        }
+        if !self.hedge.is_empty() {
+            rng.absorb_input("hedge", HEDGE_LABEL, self.hedge);
+        }
+        if let Some(counter) = self.persistent_counter.take() {
+            let value = counter.read_and_increment()?;
+            rng.absorb_input(
+                "persistent counter",
+                PERSISTENT_COUNTER_LABEL,
+                &value.to_le_bytes(),
+            );
+        }
+
+        // in degraded mode failing sources are skipped
+        let tolerate_failures = self.degraded_output.is_some();
+        let mut seeded = tolerate_failures;
+        #[cfg(feature = "getrandom")]
+        if self.getrandom {
+            #[cfg(feature = "std")]
+            if self.block_until_os_seeded && crate::wait_for_os_entropy().is_err() {
+                return Err(RngError::EntropySource(crate::EntropySourceError::new(
+                    "waiting for the OS RNG failed",
+                )));
+            }
+            match rng.absorb_source(&mut crate::OsEntropy) {
+                Ok(()) => seeded = true,
+                Err(_) if tolerate_failures => {},
+                Err(e) => return Err(e),
+            }
+        }
+        for source in self.sources.iter_mut().flatten() {
+            match rng.absorb_source(source) {
+                Ok(()) => seeded = true,
+                Err(_) if tolerate_failures => {},
+                Err(e) => return Err(e),
+            }
+        }
+        if !seeded {
+            return Err(RngError::NoEntropySource);
+        }
+        // a degraded RNG may still be upgraded, so only check the static part
+        if self.approved_mode && !rng.approved_configuration() {
+            return Err(RngError::NotApproved);
+        }
+        if self.power_analysis_hardening {
+            rng.enable_hardening();
+        }
+        rng.notify(RngEvent::Instantiated);
+
+        Ok(rng)
+    }
+}
+
+impl Default for RngBuilder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RngBuilder;
+    use crate::{
+        EntropySource, EntropySourceError, HealthTestConfig, HealthTestFailure, RngError, RngEvent,
+    };
+
+    /// Deterministic "entropy" source for tests.
+    struct Counter(u8);
+
+    impl EntropySource for Counter {
+        fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+            for b in dest {
+                *b = self.0;
+                self.0 = self.0.wrapping_add(1);
+            }
+            Ok(())
+        }
+    }
+
+    /// Source failing the health tests.
+    struct StuckSource;
+
+    impl EntropySource for StuckSource {
+        fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+            dest.fill(0);
+            Ok(())
+        }
+    }
+
+    fn builder<'a>() -> RngBuilder<'a> {
+        let builder = RngBuilder::new();
+        #[cfg(feature = "getrandom")]
+        let builder = builder.getrandom(false);
+        builder
+    }
+
+    #[test]
+    fn no_entropy_source() {
+        assert_eq!(builder().build().err(), Some(RngError::NoEntropySource));
+    }
+
+    #[test]
+    fn personalization_separates_instances() {
+        let (mut s1, mut s2) = (Counter(0), Counter(0));
+        let mut rng1 = builder().entropy_source(&mut s1).build().unwrap();
+        let mut rng2 = builder()
+            .personalization(b"other")
+            .entropy_source(&mut s2)
+            .build()
+            .unwrap();
+        assert_ne!(rng1.get_random_bytes::<32>(), rng2.get_random_bytes::<32>());
+    }
+
+    #[test]
+    fn stuck_source_fails_health_tests() {
+        struct Stuck;
+        impl EntropySource for Stuck {
+            fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+                dest.fill(0);
+                Ok(())
+            }
+        }
+
+        let mut stuck = Stuck;
+        let res = builder()
+            .entropy_source(&mut stuck)
+            .health_tests(HealthTestConfig::default())
+            .build();
+        assert_eq!(
+            res.err(),
+            Some(RngError::HealthTest(HealthTestFailure::RepetitionCount))
+        );
+    }
+
+    #[test]
+    fn degraded_until_full_entropy() {
+        struct Full;
+        impl EntropySource for Full {
+            fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+                dest.fill(0x33);
+                Ok(())
+            }
+
+            fn is_full_entropy(&self) -> bool {
+                true
+            }
+        }
+
+        let mut weak = Counter(0);
+        let mut rng = builder()
+            .entropy_source(&mut weak)
+            .degraded_mode(super::DegradedOutput::Refuse)
+            .build()
+            .unwrap();
+        assert!(rng.is_degraded());
+        assert_eq!(rng.try_get_random_bytes::<8>(), Err(RngError::Degraded));
+        rng.reseed_from(&mut Full).unwrap();
+        assert!(!rng.is_degraded());
+        assert!(rng.try_get_random_bytes::<8>().is_ok());
+    }
+
+    #[test]
+    fn output_refused_below_entropy_threshold() {
+        struct HalfBit;
+        impl EntropySource for HalfBit {
+            fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+                dest.fill(0x55);
+                Ok(())
+            }
+
+            fn entropy_credit(&self, len: usize) -> u64 {
+                len as u64 / 2
+            }
+        }
+
+        let mut source = HalfBit;
+        let mut rng = builder()
+            .entropy_source(&mut source)
+            .require_entropy(64)
+            .build()
+            .unwrap();
+        assert_eq!(rng.credited_entropy(), 32);
+        assert_eq!(
+            rng.try_get_random_bytes::<8>(),
+            Err(RngError::InsufficientEntropy {
+                credited: 32,
+                required: 64
+            })
+        );
+        rng.reseed_from(&mut source).unwrap();
+        assert!(rng.try_get_random_bytes::<8>().is_ok());
+    }
+
+    #[test]
+    fn approved_mode_and_service_indicator() {
+        let mut source = Counter(0);
+        let res = builder()
+            .entropy_source(&mut source)
+            .approved_mode()
+            .build();
+        assert_eq!(res.err(), Some(RngError::NotApproved));
+
+        let mut plain = builder().entropy_source(&mut source).build().unwrap();
+        plain.fill_random_bytes(&mut [0; 8]);
+        assert!(!plain.last_output_approved());
+
+        let mut rng = builder()
+            .entropy_source(&mut source)
+            .health_tests(HealthTestConfig::for_min_entropy(8))
+            .require_entropy(super::APPROVED_MIN_ENTROPY)
+            .max_request_size(super::NIST_MAX_REQUEST_BYTES)
+            .approved_mode()
+            .build()
+            .unwrap();
+        assert!(rng.is_approved_mode());
+        assert_eq!(
+            rng.try_get_random_bytes::<8>().err(),
+            Some(RngError::InsufficientEntropy {
+                credited: 0,
+                required: 256
+            })
+        );
+        assert!(!rng.last_output_approved());
+        rng.seed_credited(&[0x42; 32], 256);
+        assert!(rng.try_get_random_bytes::<8>().is_ok());
+        assert!(rng.last_output_approved());
+    }
+
+    #[cfg(feature = "getrandom")]
+    #[test]
+    fn paranoid_preset() {
+        let mut rng = RngBuilder::paranoid().build().unwrap();
+        assert_eq!(rng.output_policy(), super::OutputPolicy::Conservative);
+        assert!(rng.try_get_random_bytes::<32>().is_ok());
+        let mut oversized = [0; super::NIST_MAX_REQUEST_BYTES + 1];
+        assert!(matches!(
+            rng.try_fill_random_bytes(&mut oversized),
+            Err(RngError::RequestTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn two_source_rule() {
+        struct Labeled(&'static str);
+        impl EntropySource for Labeled {
+            fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+                dest.fill(0x42);
+                Ok(())
+            }
+
+            fn label(&self) -> &str {
+                self.0
+            }
+
+            fn entropy_credit(&self, len: usize) -> u64 {
+                2 * len as u64
+            }
+        }
+
+        let (mut a, mut b) = (Labeled("a"), Labeled("a"));
+        let mut rng = builder()
+            .entropy_source(&mut a)
+            .entropy_source(&mut b)
+            .require_independent_sources(2, 128)
+            .build()
+            .unwrap();
+        // equally labeled sources count as one
+        assert_eq!(
+            rng.try_get_random_bytes::<8>().err(),
+            Some(RngError::InsufficientSources {
+                qualified: 1,
+                required: 2
+            })
+        );
+        rng.seed_credited(&[0x17; 8], 64);
+        assert!(rng.try_get_random_bytes::<8>().is_err());
+        rng.seed_credited(&[0x17; 8], 64);
+        assert!(rng.try_get_random_bytes::<8>().is_ok());
+    }
+
+    #[test]
+    fn observer_sees_events() {
+        extern crate std;
+        use std::cell::RefCell;
+        use std::vec::Vec;
+
+        std::thread_local! {
+            static EVENTS: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+        }
+        fn record(event: &RngEvent<'_>) {
+            let name = match event {
+                RngEvent::Instantiated => "instantiated",
+                RngEvent::Reseeded { .. } => "reseeded",
+                RngEvent::SourceFailed { .. } => "source failed",
+                RngEvent::OutputRefused(_) => "output refused",
+                RngEvent::Poisoned => "poisoned",
+                RngEvent::Recovered => "recovered",
+            };
+            EVENTS.with(|events| events.borrow_mut().push(name));
+        }
+
+        let mut source = Counter(0);
+        let mut rng = builder()
+            .entropy_source(&mut source)
+            .max_request_size(8)
+            .observer(record)
+            .build()
+            .unwrap();
+        assert!(rng.try_get_random_bytes::<9>().is_err());
+        EVENTS.with(|events| {
+            assert_eq!(
+                *events.borrow(),
+                ["reseeded", "instantiated", "output refused"]
+            );
+        });
+    }
+
+    #[test]
+    fn poison_policy_refuses_output_after_health_test_failure() {
+        let mut source = Counter(0);
+        let mut rng = builder()
+            .entropy_source(&mut source)
+            .health_tests(HealthTestConfig::default())
+            .failure_policy(super::FailurePolicy::Poison)
+            .build()
+            .unwrap();
+        assert!(rng.try_get_random_bytes::<8>().is_ok());
+        assert!(matches!(
+            rng.reseed_from(&mut StuckSource),
+            Err(RngError::HealthTest(_))
+        ));
+        assert!(rng.is_poisoned());
+        assert_eq!(rng.try_get_random_bytes::<8>(), Err(RngError::Poisoned));
+        // reseeding alone doesn't recover, a failing recovery keeps it poisoned
+        let mut good = Counter(100);
+        rng.reseed_from(&mut good).unwrap();
+        assert!(rng.is_poisoned());
+        assert!(rng.recover_from(&mut StuckSource).is_err());
+        assert!(rng.is_poisoned());
+        rng.recover_from(&mut good).unwrap();
+        assert!(!rng.is_poisoned());
+        assert!(rng.try_get_random_bytes::<8>().is_ok());
+    }
+
+    #[test]
+    #[should_panic = "RNG self-test failed"]
+    fn panic_policy_panics_on_health_test_failure() {
+        let mut source = Counter(0);
+        let mut rng = builder()
+            .entropy_source(&mut source)
+            .health_tests(HealthTestConfig::default())
+            .failure_policy(super::FailurePolicy::Panic)
+            .build()
+            .unwrap();
+        let _ = rng.reseed_from(&mut StuckSource);
+    }
+
+    #[test]
+    fn permutation_known_answer_test_passes() {
+        assert!(crate::managed::permutation_known_answer_test());
+    }
+
+    #[test]
+    fn power_analysis_hardening_builds_and_outputs() {
+        let mut source = Counter(0);
+        let mut rng = builder()
+            .entropy_source(&mut source)
+            .output_policy(super::OutputPolicy::Conservative)
+            .power_analysis_hardening()
+            .build()
+            .unwrap();
+        rng.seed(b"more");
+        rng.ratchet();
+        assert_ne!(rng.get_random_bytes::<100>(), [0; 100]);
+    }
+
+    #[test]
+    fn persistent_counter_separates_boots() {
+        struct Boots(u64);
+        impl crate::PersistentCounter for Boots {
+            fn read_and_increment(&mut self) -> Result<u64, EntropySourceError> {
+                self.0 += 1;
+                Ok(self.0 - 1)
+            }
+        }
+
+        let mut boots = Boots(0);
+        let mut outputs = [[0; 32]; 2];
+        for output in &mut outputs {
+            let mut source = Counter(0);
+            *output = builder()
+                .entropy_source(&mut source)
+                .persistent_counter(&mut boots)
+                .build()
+                .unwrap()
+                .get_random_bytes();
+        }
+        assert_eq!(boots.0, 2);
+        assert_ne!(outputs[0], outputs[1]);
+    }
+}
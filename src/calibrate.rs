@@ -0,0 +1,115 @@
+use std::time::{Duration, Instant};
+
+use crate::limits::{optimal_request_size, BLOCK_BYTES, FIRST_BLOCK_BYTES};
+use crate::RngState;
+
+/// Size of the requests timed to measure the throughput.
+const THROUGHPUT_REQUEST_LEN: usize = optimal_request_size(1 << 14);
+
+/// Number of requests timed to measure the throughput.
+const THROUGHPUT_REQUESTS: u32 = 64;
+
+/// Number of 32-byte requests timed to measure the cost of a key.
+const KEY_REQUESTS: u32 = 4096;
+
+/// Upper bound of the recommended chunk size.
+const MAX_RECOMMENDED_CHUNK: usize = 1 << 16;
+
+/// Performance of the RNG on the current machine, measured by [`calibrate`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CalibrationReport {
+    /// Throughput of large requests in bytes per second.
+    pub bytes_per_second: u64,
+    /// Time of a single application of the Keccak-f\[1600\] permutation.
+    pub permutation: Duration,
+    /// Time of a request for 32 bytes, e.g. a key: one permutation plus the
+    /// per-call overhead.
+    pub key_32_bytes: Duration,
+    /// Fixed cost of every request on top of the permutations it applies.
+    pub per_call_overhead: Duration,
+    /// Smallest request size, aligned to the block structure (see
+    /// [`optimal_request_size`]), at which the fixed cost of a request is at
+    /// most 10% of its total cost. Buffering wrappers should request output
+    /// in chunks of at least this size.
+    pub recommended_chunk_size: usize,
+}
+
+/// Measure the throughput and per-call overhead of the RNG on the current
+/// machine, e.g. at startup to choose buffer sizes. Takes a few milliseconds.
+///
+/// The measurements are noisy, especially on loaded machines; treat the report
+/// as an estimate.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::calibrate;
+///
+/// let report = calibrate();
+/// println!(
+///     "{} MB/s, request at least {} bytes at a time",
+///     report.bytes_per_second / 1_000_000,
+///     report.recommended_chunk_size,
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn calibrate() -> CalibrationReport {
+    let mut rng = RngState::new_unseeded();
+    rng.seed(b"fast-erasure-shake-rng calibration");
+
+    // warm up caches and branch predictors
+    let mut buf = std::vec![0u8; THROUGHPUT_REQUEST_LEN];
+    rng.fill_random_bytes(&mut buf);
+
+    let start = Instant::now();
+    for _ in 0..THROUGHPUT_REQUESTS {
+        rng.fill_random_bytes(core::hint::black_box(&mut buf));
+    }
+    let throughput_time = start.elapsed().max(Duration::from_nanos(1));
+    let total_bytes = THROUGHPUT_REQUEST_LEN as u128 * u128::from(THROUGHPUT_REQUESTS);
+    let bytes_per_second =
+        u64::try_from(total_bytes * 1_000_000_000 / throughput_time.as_nanos()).unwrap_or(u64::MAX);
+    let blocks_per_request = 1 + (THROUGHPUT_REQUEST_LEN - FIRST_BLOCK_BYTES) / BLOCK_BYTES;
+    let permutations = THROUGHPUT_REQUESTS * u32::try_from(blocks_per_request).unwrap();
+    let permutation = throughput_time / permutations;
+
+    let start = Instant::now();
+    for _ in 0..KEY_REQUESTS {
+        core::hint::black_box(rng.get_random_bytes::<32>());
+    }
+    let key_32_bytes = start.elapsed() / KEY_REQUESTS;
+    let per_call_overhead = key_32_bytes.saturating_sub(permutation);
+
+    // a request of `n` blocks costs `n` permutations plus the fixed cost
+    let fixed = per_call_overhead + permutation;
+    let blocks = (10 * fixed.as_nanos()).div_ceil(permutation.as_nanos().max(1));
+    let len = FIRST_BLOCK_BYTES as u128 + blocks.saturating_sub(1) * BLOCK_BYTES as u128;
+    let recommended_chunk_size = usize::try_from(len)
+        .unwrap_or(MAX_RECOMMENDED_CHUNK)
+        .min(MAX_RECOMMENDED_CHUNK);
+
+    CalibrationReport {
+        bytes_per_second,
+        permutation,
+        key_32_bytes,
+        per_call_overhead,
+        recommended_chunk_size: optimal_request_size(recommended_chunk_size),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::calibrate;
+    use crate::limits::optimal_request_size;
+
+    #[test]
+    fn report_is_consistent() {
+        let report = calibrate();
+        assert!(report.bytes_per_second > 0);
+        assert!(report.key_32_bytes >= report.per_call_overhead);
+        assert_eq!(
+            optimal_request_size(report.recommended_chunk_size),
+            report.recommended_chunk_size
+        );
+    }
+}
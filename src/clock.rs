@@ -0,0 +1,180 @@
+use core::time::Duration;
+
+use crate::{EntropySource, ManagedRng, RngError};
+
+/// A monotonic clock counting ticks, for time-based reseeding without the
+/// standard library, e.g. implemented with a timer peripheral. With the `std`
+/// feature, [`StdClock`] implements it using [`std::time::Instant`].
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::MonotonicClock;
+///
+/// /// A 32 kHz real time counter.
+/// struct Rtc;
+///
+/// impl MonotonicClock for Rtc {
+///     fn now_ticks(&self) -> u64 {
+///         # 0
+///         // read the counter register here
+///     }
+///
+///     fn ticks_per_second(&self) -> u64 {
+///         32_768
+///     }
+/// }
+/// ```
+pub trait MonotonicClock {
+    /// Current time in ticks since an arbitrary fixed point. Must never
+    /// decrease.
+    fn now_ticks(&self) -> u64;
+
+    /// Number of ticks per second.
+    fn ticks_per_second(&self) -> u64;
+}
+
+impl<C: MonotonicClock + ?Sized> MonotonicClock for &C {
+    fn now_ticks(&self) -> u64 {
+        (**self).now_ticks()
+    }
+
+    fn ticks_per_second(&self) -> u64 {
+        (**self).ticks_per_second()
+    }
+}
+
+/// [`MonotonicClock`] counting nanoseconds since its creation, using
+/// [`std::time::Instant`].
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StdClock {
+    start: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl StdClock {
+    /// Create a clock starting at zero ticks now.
+    pub fn new() -> Self {
+        Self {
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for StdClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl MonotonicClock for StdClock {
+    fn now_ticks(&self) -> u64 {
+        u64::try_from(self.start.elapsed().as_nanos()).unwrap_or(u64::MAX)
+    }
+
+    fn ticks_per_second(&self) -> u64 {
+        1_000_000_000
+    }
+}
+
+/// Number of ticks of a clock with `ticks_per_second` in `duration`,
+/// saturating.
+fn duration_to_ticks(duration: Duration, ticks_per_second: u64) -> u64 {
+    let ticks = duration.as_nanos() * u128::from(ticks_per_second) / 1_000_000_000;
+    u64::try_from(ticks).unwrap_or(u64::MAX)
+}
+
+impl ManagedRng {
+    /// Reseed the RNG from `source` if the reseed interval set by
+    /// [`RngBuilder::reseed_interval`](crate::RngBuilder::reseed_interval) has
+    /// passed according to `clock` since the last reseed by this method.
+    /// Returns whether the RNG was reseeded. Call this regularly, e.g. before
+    /// output requests or from a periodic task.
+    ///
+    /// The first call only starts the interval. Always pass the same clock.
+    ///
+    /// # Errors
+    /// Returns the error of [`Self::reseed_from`] if the reseed fails, in
+    /// which case the next call tries again.
+    pub fn reseed_if_due<C: MonotonicClock + ?Sized, S: EntropySource + ?Sized>(
+        &mut self,
+        clock: &C,
+        source: &mut S,
+    ) -> Result<bool, RngError> {
+        let Some(interval) = self.reseed_interval else {
+            return Ok(false);
+        };
+        let now = clock.now_ticks();
+        let Some(last) = self.last_reseed_ticks else {
+            self.last_reseed_ticks = Some(now);
+            return Ok(false);
+        };
+        if now.saturating_sub(last) < duration_to_ticks(interval, clock.ticks_per_second()) {
+            return Ok(false);
+        }
+        self.reseed_from(source)?;
+        self.last_reseed_ticks = Some(now);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+    use core::time::Duration;
+
+    use super::MonotonicClock;
+    use crate::{EntropySource, EntropySourceError, RngBuilder};
+
+    /// Manually advanced millisecond clock.
+    struct FakeClock(Cell<u64>);
+
+    impl MonotonicClock for FakeClock {
+        fn now_ticks(&self) -> u64 {
+            self.0.get()
+        }
+
+        fn ticks_per_second(&self) -> u64 {
+            1_000
+        }
+    }
+
+    struct Constant;
+
+    impl EntropySource for Constant {
+        fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+            dest.fill(0x42);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn reseeds_after_interval() {
+        let mut source = Constant;
+        let builder = RngBuilder::new().entropy_source(&mut source);
+        #[cfg(feature = "getrandom")]
+        let builder = builder.getrandom(false);
+        let mut rng = builder
+            .reseed_interval(Duration::from_secs(10))
+            .build()
+            .unwrap();
+        let clock = FakeClock(Cell::new(5_000));
+        assert!(!rng.reseed_if_due(&clock, &mut Constant).unwrap());
+        clock.0.set(14_999);
+        assert!(!rng.reseed_if_due(&clock, &mut Constant).unwrap());
+        clock.0.set(15_000);
+        assert!(rng.reseed_if_due(&clock, &mut Constant).unwrap());
+        assert!(!rng.reseed_if_due(&clock, &mut Constant).unwrap());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn std_clock_is_monotonic() {
+        let clock = super::StdClock::new();
+        let a = clock.now_ticks();
+        assert!(clock.now_ticks() >= a);
+    }
+}
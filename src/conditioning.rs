@@ -0,0 +1,287 @@
+use crate::xof::Shake256;
+use crate::{EntropySource, EntropySourceError};
+
+/// Number of raw bytes read from the inner source at a time.
+const RAW_CHUNK_LEN: usize = 64;
+
+/// A von Neumann debiaser for raw noise sources, itself an [`EntropySource`].
+///
+/// Every pair of raw bits is turned into one output bit (`01` into `0`, `10`
+/// into `1`) or discarded (`00`, `11`). This removes any bias from independent
+/// raw bits, at the cost of reading at least 2 and on average 4 or more raw
+/// bits per output bit. It doesn't remove correlations between bits; use a
+/// [`HashConditioner`] on top for sources with dependencies.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::{EntropySource, EntropySourceError, VonNeumann};
+///
+/// /// A ring oscillator sampled once per byte, with biased LSBs.
+/// struct RingOscillator;
+///
+/// impl EntropySource for RingOscillator {
+///     fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+///         # dest.iter_mut().enumerate().for_each(|(i, b)| *b = (i * 37 % 11) as u8);
+///         // sample the hardware here
+///         Ok(())
+///     }
+/// }
+///
+/// let mut debiased = VonNeumann::new(RingOscillator);
+/// let mut buf = [0; 16];
+/// debiased.fill_entropy(&mut buf).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct VonNeumann<S> {
+    source: S,
+}
+
+impl<S: EntropySource> VonNeumann<S> {
+    /// Debias the output of `source`.
+    pub fn new(source: S) -> Self {
+        Self { source }
+    }
+
+    /// Unwrap the raw source.
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+}
+
+impl<S: EntropySource> EntropySource for VonNeumann<S> {
+    /// Fill `dest` with debiased bits.
+    ///
+    /// # Errors
+    /// Fails if the raw source fails, or if it delivers only equal bit pairs
+    /// for 4096 raw bytes in a row (e.g. because it is stuck).
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+        /// Maximum number of raw chunks without any output bit.
+        const MAX_IDLE_CHUNKS: usize = 4096 / RAW_CHUNK_LEN;
+
+        let mut raw = zeroize::Zeroizing::new([0u8; RAW_CHUNK_LEN]);
+        let (mut byte, mut bits, mut pos) = (0u8, 0, 0);
+        let mut idle_chunks = 0;
+        while pos < dest.len() {
+            if idle_chunks == MAX_IDLE_CHUNKS {
+                return Err(EntropySourceError::new(
+                    "von Neumann debiaser got no output",
+                ));
+            }
+            self.source.fill_entropy(raw.as_mut())?;
+            idle_chunks += 1;
+            for &raw_byte in raw.iter() {
+                for shift in [6, 4, 2, 0] {
+                    let pair = (raw_byte >> shift) & 0b11;
+                    if pair == 0b01 || pair == 0b10 {
+                        byte = (byte << 1) | (pair >> 1);
+                        bits += 1;
+                        idle_chunks = 0;
+                        if bits == 8 {
+                            dest[pos] = byte;
+                            (byte, bits) = (0, 0);
+                            pos += 1;
+                            if pos == dest.len() {
+                                return Ok(());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn label(&self) -> &str {
+        self.source.label()
+    }
+
+    /// Credit at most the credit of the minimal 2 raw bytes consumed per
+    /// output byte.
+    fn entropy_credit(&self, len: usize) -> u64 {
+        let credit = self.source.entropy_credit(len.saturating_mul(2));
+        core::cmp::min(credit, 8 * len as u64)
+    }
+}
+
+/// Number of output bytes per conditioning block of a [`HashConditioner`].
+const CONDITIONED_BLOCK_LEN: usize = 32;
+
+/// Maximum number of raw bytes conditioned into one output block.
+const MAX_RAW_PER_BLOCK: usize = 4096;
+
+/// Domain separation label of the hash conditioner.
+const CONDITIONER_LABEL: &[u8] = b"fast-erasure-shake-rng hash conditioner";
+
+/// A vetted hash based conditioning component (NIST SP 800-90B, section
+/// 3.1.5.1.1) using SHAKE256, itself an [`EntropySource`] with entropy
+/// accounting.
+///
+/// Every 32-byte output block is the hash of enough raw output of the inner
+/// source to be credited with at least 320 bits (the output length plus 64
+/// bits), as estimated by the inner source's
+/// [`entropy_credit`](EntropySource::entropy_credit), but at most 4096 raw
+/// bytes. Output blocks conditioned from at least 320 bits are credited as
+/// full entropy, following SP 800-90B, section 3.1.5.1.2; otherwise the inner
+/// credit is passed on, capped at 255 bits per block.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::{EntropySource, EntropySourceError, HashConditioner};
+///
+/// /// ADC noise with an assessed min-entropy of 2 bits per sample.
+/// struct AdcNoise;
+///
+/// impl EntropySource for AdcNoise {
+///     fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+///         # dest.fill(0x5a);
+///         // sample the ADC here
+///         Ok(())
+///     }
+///
+///     fn entropy_credit(&self, len: usize) -> u64 {
+///         2 * len as u64
+///     }
+/// }
+///
+/// let conditioned = HashConditioner::new(AdcNoise);
+/// assert!(conditioned.is_full_entropy());
+/// assert_eq!(conditioned.entropy_credit(32), 256);
+/// ```
+#[derive(Debug)]
+pub struct HashConditioner<S> {
+    source: S,
+    /// Number of raw bytes conditioned into every output block.
+    raw_per_block: usize,
+}
+
+impl<S: EntropySource> HashConditioner<S> {
+    /// Condition the output of `source`.
+    pub fn new(source: S) -> Self {
+        let required = 8 * CONDITIONED_BLOCK_LEN as u64 + 64;
+        let mut raw_per_block = RAW_CHUNK_LEN;
+        while raw_per_block < MAX_RAW_PER_BLOCK && source.entropy_credit(raw_per_block) < required {
+            raw_per_block += RAW_CHUNK_LEN;
+        }
+        Self {
+            source,
+            raw_per_block,
+        }
+    }
+
+    /// Entropy credited to every output block, in bits.
+    fn block_credit(&self) -> u64 {
+        let input_credit = self.source.entropy_credit(self.raw_per_block);
+        if input_credit >= 8 * CONDITIONED_BLOCK_LEN as u64 + 64 {
+            8 * CONDITIONED_BLOCK_LEN as u64
+        } else {
+            core::cmp::min(input_credit, 8 * CONDITIONED_BLOCK_LEN as u64 - 1)
+        }
+    }
+
+    /// Unwrap the raw source.
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+}
+
+impl<S: EntropySource> EntropySource for HashConditioner<S> {
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+        let mut raw = zeroize::Zeroizing::new([0u8; RAW_CHUNK_LEN]);
+        for block in dest.chunks_mut(CONDITIONED_BLOCK_LEN) {
+            let mut xof = Shake256::new();
+            xof.absorb_prefixed(CONDITIONER_LABEL);
+            for _ in 0..self.raw_per_block / RAW_CHUNK_LEN {
+                self.source.fill_entropy(raw.as_mut())?;
+                xof.absorb(raw.as_ref());
+            }
+            xof.squeeze(block);
+        }
+        Ok(())
+    }
+
+    fn label(&self) -> &str {
+        self.source.label()
+    }
+
+    fn is_full_entropy(&self) -> bool {
+        self.block_credit() == 8 * CONDITIONED_BLOCK_LEN as u64
+    }
+
+    fn entropy_credit(&self, len: usize) -> u64 {
+        let blocks = len.div_ceil(CONDITIONED_BLOCK_LEN) as u64;
+        core::cmp::min(8 * len as u64, blocks * self.block_credit())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HashConditioner, VonNeumann};
+    use crate::{EntropySource, EntropySourceError};
+
+    /// Biased source: every raw bit pair is `10` with probability 1/4 and
+    /// `00` otherwise, credited with one bit per byte.
+    struct Biased(u32);
+
+    impl EntropySource for Biased {
+        fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+            for b in dest {
+                self.0 = self.0.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                // keep the high bit of each pair, drop the low bits
+                *b = (self.0 >> 16).to_le_bytes()[0] & 0b1010_1010;
+            }
+            Ok(())
+        }
+
+        fn entropy_credit(&self, len: usize) -> u64 {
+            len as u64
+        }
+    }
+
+    #[test]
+    fn von_neumann_fills_and_credits() {
+        let mut debiased = VonNeumann::new(Biased(1));
+        let mut buf = [0; 64];
+        debiased.fill_entropy(&mut buf).unwrap();
+        assert!(buf.iter().any(|&b| b != 0));
+        assert_eq!(debiased.entropy_credit(8), 16);
+    }
+
+    #[test]
+    fn von_neumann_stuck_source_fails() {
+        struct Stuck;
+        impl EntropySource for Stuck {
+            fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+                dest.fill(0xff);
+                Ok(())
+            }
+        }
+        assert!(VonNeumann::new(Stuck).fill_entropy(&mut [0; 4]).is_err());
+    }
+
+    #[test]
+    fn hash_conditioner_accounting() {
+        // 1 bit per raw byte: 320 raw bytes per block for full entropy
+        let conditioner = HashConditioner::new(Biased(1));
+        assert_eq!(conditioner.raw_per_block, 320);
+        assert!(conditioner.is_full_entropy());
+        assert_eq!(conditioner.entropy_credit(40), 320);
+
+        struct Weak;
+        impl EntropySource for Weak {
+            fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+                dest.fill(1);
+                Ok(())
+            }
+
+            fn entropy_credit(&self, len: usize) -> u64 {
+                len as u64 / 64
+            }
+        }
+        let mut weak = HashConditioner::new(Weak);
+        assert!(!weak.is_full_entropy());
+        assert_eq!(weak.entropy_credit(32), 64);
+        let mut buf = [0; 40];
+        weak.fill_entropy(&mut buf).unwrap();
+        assert_ne!(buf[..32], buf[8..]);
+    }
+}
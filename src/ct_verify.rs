@@ -0,0 +1,78 @@
+//! Hooks for dudect-style verification that the output path runs in
+//! constant time, i.e. its execution time depends only on the requested
+//! length and not on the (secret) state.
+//!
+//! A dudect harness, e.g. using the `dudect-bencher` crate, repeatedly picks
+//! a random [`InputClass`], prepares a state with [`prepare_state`] and times
+//! [`measured_output`] on it. A statistically significant difference between
+//! the timing distributions of the two classes indicates a leak.
+//!
+//! ```
+//! use fast_erasure_shake_rng::ct_verify::{measured_output, prepare_state, InputClass};
+//! use fast_erasure_shake_rng::RngState;
+//!
+//! let mut class_rng = RngState::new_from_getrandom().unwrap();
+//! let mut out = [0; 300];
+//! for _ in 0..1000 {
+//!     let class = if class_rng.get_random_bytes::<1>()[0] & 1 == 0 {
+//!         InputClass::Fixed
+//!     } else {
+//!         InputClass::Random
+//!     };
+//!     let mut state = prepare_state(class, &mut class_rng);
+//!     // start timer
+//!     measured_output(&mut state, &mut out);
+//!     // stop timer, record the duration for `class`
+//! }
+//! ```
+
+use crate::RngState;
+
+/// Input class of a dudect measurement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InputClass {
+    /// A fixed state, the same for every measurement.
+    Fixed,
+    /// A fresh random state for every measurement.
+    Random,
+}
+
+/// Seed of the state of [`InputClass::Fixed`].
+const FIXED_SEED: &[u8] = b"fast-erasure-shake-rng ct-verify fixed state";
+
+/// Prepare the state to measure for `class`, drawing the random state from
+/// `rng`.
+pub fn prepare_state(class: InputClass, rng: &mut RngState) -> RngState {
+    let mut state = RngState::new_unseeded();
+    match class {
+        InputClass::Fixed => state.seed(FIXED_SEED),
+        InputClass::Random => state.seed(&rng.get_random_bytes::<64>()),
+    }
+    state
+}
+
+/// The operation to measure: [`RngState::fill_random_bytes`], kept from being
+/// optimized away or inlined into the harness.
+#[inline(never)]
+pub fn measured_output(state: &mut RngState, dest: &mut [u8]) {
+    core::hint::black_box(&mut *state).fill_random_bytes(core::hint::black_box(&mut *dest));
+    core::hint::black_box(dest);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{measured_output, prepare_state, InputClass};
+    use crate::RngState;
+
+    #[test]
+    fn classes_differ() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"ct verify tests");
+        let (mut a, mut b) = ([0; 100], [0; 100]);
+        measured_output(&mut prepare_state(InputClass::Fixed, &mut rng), &mut a);
+        measured_output(&mut prepare_state(InputClass::Fixed, &mut rng), &mut b);
+        assert_eq!(a, b);
+        measured_output(&mut prepare_state(InputClass::Random, &mut rng), &mut b);
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,284 @@
+use crate::RngState;
+
+/// Largest numerator and denominator (in lowest terms) of the variance
+/// accepted by [`RngState::discrete_gaussian`], exclusive. This bounds the
+/// denominator `2·a·b·t²` of the acceptance probability below `2^127`.
+const MAX_VARIANCE_TERM: u64 = 1 << 62;
+
+/// Greatest common divisor of `a` and `b`.
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Integer square root `floor(sqrt(n))`, bit by bit.
+fn isqrt(n: u64) -> u64 {
+    let mut root = 0;
+    for bit in (0..32).rev() {
+        let candidate: u64 = root | (1 << bit);
+        // `candidate < 2^32`, so the square doesn't overflow
+        if candidate * candidate <= n {
+            root = candidate;
+        }
+    }
+    root
+}
+
+/// Full 256-bit product of `x` and `y`, as high and low half.
+fn widening_mul(x: u128, y: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (x1, x0) = (x >> 64, x & MASK);
+    let (y1, y0) = (y >> 64, y & MASK);
+    let (p00, p01, p10, p11) = (x0 * y0, x0 * y1, x1 * y0, x1 * y1);
+    // sum of three values below 2^64, so it doesn't overflow
+    let mid = (p00 >> 64) + (p01 & MASK) + (p10 & MASK);
+    let lo = (p00 & MASK) | (mid << 64);
+    let hi = p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+    (hi, lo)
+}
+
+impl RngState {
+    /// Sample from Bernoulli(`num / den`), for `num <= den`.
+    fn bernoulli_rational(&mut self, num: u128, den: u128) -> bool {
+        self.random_u128_below(den) < num
+    }
+
+    /// Sample from Bernoulli(exp(-`num / den`)) exactly (Canonne, Kamath and
+    /// Steinke, Algorithm 1).
+    fn bernoulli_exp_neg(&mut self, num: u128, den: u128) -> bool {
+        // exp(-γ) = exp(-1)^floor(γ) * exp(-(γ - floor(γ)))
+        for _ in 0..num / den {
+            if !self.bernoulli_exp_neg_at_most_one(1, 1) {
+                return false;
+            }
+        }
+        self.bernoulli_exp_neg_at_most_one(num % den, den)
+    }
+
+    /// Sample from Bernoulli(exp(-`num / den`)) for the 256-bit numerator
+    /// `num = num_hi · 2^128 + num_lo`.
+    fn bernoulli_exp_neg_wide(&mut self, mut num_hi: u128, mut num_lo: u128, den: u128) -> bool {
+        // peel off factors exp(-1) until the numerator fits in a `u128`; every
+        // factor fails with probability 1 - exp(-1), so this ends quickly
+        while num_hi != 0 {
+            if !self.bernoulli_exp_neg_at_most_one(1, 1) {
+                return false;
+            }
+            let (lo, borrow) = num_lo.overflowing_sub(den);
+            (num_hi, num_lo) = (num_hi - u128::from(borrow), lo);
+        }
+        self.bernoulli_exp_neg(num_lo, den)
+    }
+
+    /// Sample from Bernoulli(exp(-`num / den`)) for `num <= den`: the parity
+    /// of the first `k` for which Bernoulli(γ / k) fails.
+    fn bernoulli_exp_neg_at_most_one(&mut self, num: u128, den: u128) -> bool {
+        let mut k: u128 = 1;
+        while self.bernoulli_rational(num, den.saturating_mul(k)) {
+            k += 1;
+        }
+        k % 2 == 1
+    }
+
+    /// Sample from the discrete Laplace distribution with scale `t / s`
+    /// (Canonne, Kamath and Steinke, Algorithm 2).
+    fn discrete_laplace_inner(&mut self, s: u128, t: u128) -> i64 {
+        loop {
+            let u = self.random_u128_below(t);
+            if !self.bernoulli_exp_neg(u, t) {
+                continue;
+            }
+            let mut v: u128 = 0;
+            while self.bernoulli_exp_neg(1, 1) {
+                v += 1;
+            }
+            let y = (u + t * v) / s;
+            let negative = self.get_random_bytes::<1>()[0] & 1 == 1;
+            if negative && y == 0 {
+                continue;
+            }
+            let y = i64::try_from(y).expect("discrete Laplace sample out of range");
+            return if negative { -y } else { y };
+        }
+    }
+
+    /// Output a sample of the discrete Laplace distribution with scale
+    /// `scale_num / scale_den`, i.e. an integer `x` with probability
+    /// proportional to `exp(-|x| / scale)`. This is the noise of the discrete
+    /// Laplace mechanism for ε-differential privacy: add a sample with scale
+    /// `Δ / ε` to an integer query with sensitivity `Δ`. The RNG MUST be seeded
+    /// prior to using this method.
+    ///
+    /// The sampler is exact: it uses only integer arithmetic, so unlike
+    /// floating point samplers it doesn't leak through rounding artifacts. Its
+    /// running time is random and depends on the sample, so it doesn't protect
+    /// against timing side channels.
+    ///
+    /// # Panics
+    /// Panics if `scale_num` or `scale_den` is zero, or if the sample doesn't
+    /// fit in an `i64` (only possible for astronomically large scales).
+    ///
+    /// # Examples
+    /// ```
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// // counting query (sensitivity 1) with ε = 0.5
+    /// let true_count: i64 = 1234;
+    /// let noisy_count = true_count + rng.discrete_laplace(2, 1);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "dp")))]
+    pub fn discrete_laplace(&mut self, scale_num: u64, scale_den: u64) -> i64 {
+        assert!(
+            scale_num != 0 && scale_den != 0,
+            "scale must be a positive fraction"
+        );
+        self.discrete_laplace_inner(u128::from(scale_den), u128::from(scale_num))
+    }
+
+    /// Output a sample of the discrete Gaussian distribution with variance
+    /// parameter `σ² = sigma2_num / sigma2_den`, i.e. an integer `x` with
+    /// probability proportional to `exp(-x² / (2σ²))` (Canonne, Kamath and
+    /// Steinke, Algorithm 3). This is the noise of the discrete Gaussian
+    /// mechanism for (zero-concentrated) differential privacy. The RNG MUST
+    /// be seeded prior to using this method.
+    ///
+    /// Like [`Self::discrete_laplace`] the sampler is exact, but its running
+    /// time depends on the sample.
+    ///
+    /// # Panics
+    /// Panics if `sigma2_num` or `sigma2_den` is zero, or if the numerator or
+    /// denominator of `sigma2_num / sigma2_den` in lowest terms is `2^62` or
+    /// larger. E.g. `σ² = u64::MAX / u64::MAX` is fine, but
+    /// `σ² = 2^63 / 3` is rejected.
+    ///
+    /// # Examples
+    /// ```
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// // σ² = 12.5
+    /// let noise = rng.discrete_gaussian(25, 2);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "dp")))]
+    pub fn discrete_gaussian(&mut self, sigma2_num: u64, sigma2_den: u64) -> i64 {
+        assert!(
+            sigma2_num != 0 && sigma2_den != 0,
+            "variance must be a positive fraction"
+        );
+        let g = gcd(sigma2_num, sigma2_den);
+        let (a, b) = (sigma2_num / g, sigma2_den / g);
+        assert!(
+            a < MAX_VARIANCE_TERM && b < MAX_VARIANCE_TERM,
+            "variance numerator or denominator too large"
+        );
+        // t = floor(σ) + 1
+        let t = u128::from(isqrt(a / b)) + 1;
+        let (a, b) = (u128::from(a), u128::from(b));
+        // below 8·a² < 2^127 if a >= b (as t² <= 4·a/b), and below 2^125 if
+        // a < b (as t = 1)
+        let den = 2 * a * b * t * t;
+        loop {
+            let y = self.discrete_laplace_inner(1, t);
+            // accept with probability exp(-(|y| - σ²/t)² / (2σ²)), where
+            // (|y| - σ²/t)² / (2σ²) = (|y|·b·t - a)² / (2·a·b·t²)
+            let abs_y = u128::from(y.unsigned_abs());
+            let Some(scaled) = abs_y.checked_mul(b * t) else {
+                // |y|·b·t >= 2^128, so the acceptance probability is below
+                // exp(-2^127)
+                continue;
+            };
+            let diff = scaled.abs_diff(a);
+            let (num_hi, num_lo) = widening_mul(diff, diff);
+            if self.bernoulli_exp_neg_wide(num_hi, num_lo, den) {
+                return y;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{isqrt, widening_mul};
+    use crate::RngState;
+
+    fn test_rng() -> RngState {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"dp tests");
+        rng
+    }
+
+    #[test]
+    fn bernoulli_exp_neg_frequency() {
+        let mut rng = test_rng();
+        // exp(-1.5) ≈ 0.2231
+        let hits = (0..20_000).filter(|_| rng.bernoulli_exp_neg(3, 2)).count();
+        assert!((4_100..4_830).contains(&hits), "{}", hits);
+    }
+
+    #[test]
+    fn discrete_laplace_moments() {
+        let mut rng = test_rng();
+        let samples: [i64; 10_000] = core::array::from_fn(|_| rng.discrete_laplace(3, 1));
+        // mean 0; P(0) = tanh(1 / 6) ≈ 0.165
+        let sum: i64 = samples.iter().sum();
+        assert!(sum.abs() < 1_000, "{}", sum);
+        let zeros = samples.iter().filter(|&&x| x == 0).count();
+        assert!((1_450..1_850).contains(&zeros), "{}", zeros);
+    }
+
+    #[test]
+    fn discrete_gaussian_moments() {
+        let mut rng = test_rng();
+        let samples: [i64; 10_000] = core::array::from_fn(|_| rng.discrete_gaussian(4, 1));
+        let sum: i64 = samples.iter().sum();
+        assert!(sum.abs() < 600, "{}", sum);
+        // variance ≈ σ² = 4
+        let sum_sq: i64 = samples.iter().map(|x| x * x).sum();
+        assert!((36_000..44_000).contains(&sum_sq), "{}", sum_sq);
+    }
+
+    #[test]
+    fn isqrt_floor() {
+        for (n, root) in [(0, 0), (1, 1), (3, 1), (4, 2), (99, 9), (100, 10)] {
+            assert_eq!(isqrt(n), root);
+        }
+        assert_eq!(isqrt(u64::MAX), u64::from(u32::MAX));
+    }
+
+    #[test]
+    fn widening_mul_product() {
+        assert_eq!(widening_mul(u128::MAX, u128::MAX), (u128::MAX - 1, 1));
+        assert_eq!(widening_mul(1 << 64, 1 << 64), (1, 0));
+        assert_eq!(widening_mul(3, 5), (0, 15));
+    }
+
+    #[test]
+    fn discrete_gaussian_extreme_parameters() {
+        let mut rng = test_rng();
+        // σ² = 1
+        for _ in 0..100 {
+            assert!(rng.discrete_gaussian(u64::MAX, u64::MAX).abs() < 20);
+        }
+        // σ² ≈ 2^62, σ ≈ 2^31
+        for _ in 0..100 {
+            assert!(rng.discrete_gaussian((1 << 62) - 1, 1).abs() < 1 << 36);
+        }
+        // σ² ≈ 2^-62
+        for _ in 0..100 {
+            assert_eq!(rng.discrete_gaussian(1, (1 << 62) - 1), 0);
+        }
+        // σ² ≈ 1 with large terms
+        for _ in 0..100 {
+            assert!(rng.discrete_gaussian((1 << 62) - 1, (1 << 62) - 3).abs() < 20);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn discrete_gaussian_too_large_terms() {
+        test_rng().discrete_gaussian(1 << 63, 3);
+    }
+}
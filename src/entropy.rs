@@ -0,0 +1,219 @@
+use core::fmt;
+use core::future::Future;
+
+use crate::RngState;
+
+/// Error returned by an [`EntropySource`] which failed to deliver entropy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EntropySourceError {
+    message: &'static str,
+}
+
+impl EntropySourceError {
+    /// Create a new error with a static description of the failure.
+    pub const fn new(message: &'static str) -> Self {
+        Self { message }
+    }
+
+    /// Static description of the failure.
+    pub const fn message(&self) -> &'static str {
+        self.message
+    }
+}
+
+impl fmt::Display for EntropySourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "entropy source failed: {}", self.message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EntropySourceError {}
+
+/// A source of entropy which can be used to seed the RNG, e.g. the OS RNG, a
+/// hardware noise source or a jitter based collector.
+///
+/// Sources may deliver raw (unconditioned) noise; the RNG takes care of
+/// conditioning it. Raw noise sources should be combined with health tests,
+/// see [`RngBuilder::health_tests`](crate::RngBuilder::health_tests).
+pub trait EntropySource {
+    /// Fill all of `dest` with entropy from the source.
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError>;
+
+    /// Short human readable name of the source, for diagnostics.
+    fn label(&self) -> &str {
+        "unlabeled"
+    }
+
+    /// Whether the source delivers full entropy, i.e. (conditioned) output
+    /// indistinguishable from uniformly random bytes, like the OS RNG. Raw
+    /// noise sources don't. Used to leave degraded mode, see
+    /// [`RngBuilder::degraded_mode`](crate::RngBuilder::degraded_mode).
+    fn is_full_entropy(&self) -> bool {
+        false
+    }
+
+    /// Entropy in bits to credit for `len` bytes of output of the source,
+    /// counted towards the threshold of
+    /// [`RngBuilder::require_entropy`](crate::RngBuilder::require_entropy).
+    /// Override this with a conservative estimate of the min-entropy of the
+    /// source. Defaults to 8 bits per byte for full entropy sources and 0
+    /// otherwise.
+    fn entropy_credit(&self, len: usize) -> u64 {
+        if self.is_full_entropy() {
+            8 * len as u64
+        } else {
+            0
+        }
+    }
+}
+
+impl<S: EntropySource + ?Sized> EntropySource for &mut S {
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+        (**self).fill_entropy(dest)
+    }
+
+    fn label(&self) -> &str {
+        (**self).label()
+    }
+
+    fn is_full_entropy(&self) -> bool {
+        (**self).is_full_entropy()
+    }
+
+    fn entropy_credit(&self, len: usize) -> u64 {
+        (**self).entropy_credit(len)
+    }
+}
+
+/// An asynchronous source of entropy, e.g. a hardware TRNG driver which
+/// awaits an interrupt until enough entropy is available. The asynchronous
+/// counterpart of [`EntropySource`].
+pub trait AsyncEntropySource {
+    /// Fill all of `dest` with entropy from the source.
+    fn fill_entropy(
+        &mut self,
+        dest: &mut [u8],
+    ) -> impl Future<Output = Result<(), EntropySourceError>>;
+}
+
+/// The OS RNG as an [`EntropySource`], using the [`getrandom` crate].
+///
+/// [`getrandom` crate]: https://crates.io/crates/getrandom
+#[cfg(feature = "getrandom")]
+#[cfg_attr(docsrs, doc(cfg(feature = "getrandom")))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct OsEntropy;
+
+#[cfg(feature = "getrandom")]
+impl EntropySource for OsEntropy {
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+        crate::getrandom_or_override(dest).map_err(|_| EntropySourceError::new("getrandom failed"))
+    }
+
+    fn label(&self) -> &str {
+        "getrandom"
+    }
+
+    fn is_full_entropy(&self) -> bool {
+        true
+    }
+}
+
+impl RngState {
+    /// (Re)seed the RNG with 64 bytes of entropy obtained from `source`.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::{OsEntropy, RngState};
+    ///
+    /// let mut rng = RngState::new_unseeded();
+    /// rng.seed_from_entropy_source(&mut OsEntropy).unwrap();
+    /// # }
+    /// ```
+    pub fn seed_from_entropy_source<S: EntropySource + ?Sized>(
+        &mut self,
+        source: &mut S,
+    ) -> Result<(), EntropySourceError> {
+        self.seed_with_64(|buf| source.fill_entropy(buf))
+    }
+
+    /// (Re)seed the RNG with 64 bytes of entropy obtained from the
+    /// asynchronous `source`.
+    ///
+    /// The buffer holding the entropy is zeroized after use, also if the
+    /// returned future is dropped before completion.
+    pub async fn seed_from_async_entropy_source<S: AsyncEntropySource + ?Sized>(
+        &mut self,
+        source: &mut S,
+    ) -> Result<(), EntropySourceError> {
+        let mut buffer = zeroize::Zeroizing::new([0u8; 64]);
+        source.fill_entropy(buffer.as_mut()).await?;
+        self.absorb_partial_block_padded(buffer.as_ref());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EntropySource, EntropySourceError};
+    use crate::RngState;
+
+    struct Failing;
+
+    impl EntropySource for Failing {
+        fn fill_entropy(&mut self, _dest: &mut [u8]) -> Result<(), EntropySourceError> {
+            Err(EntropySourceError::new("always fails"))
+        }
+    }
+
+    #[test]
+    fn seed_from_failing_source() {
+        let mut rng = RngState::new_unseeded();
+        let err = rng.seed_from_entropy_source(&mut Failing).unwrap_err();
+        assert_eq!(err.message(), "always fails");
+    }
+
+    #[test]
+    fn seed_from_async_source() {
+        use core::future::Future;
+        use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+        struct Ready;
+
+        impl super::AsyncEntropySource for Ready {
+            async fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+                dest.fill(0x42);
+                Ok(())
+            }
+        }
+
+        // `Waker::noop` needs Rust 1.85
+        const NOOP_VTABLE: RawWakerVTable =
+            RawWakerVTable::new(|_| NOOP_RAW_WAKER, |_| {}, |_| {}, |_| {});
+        const NOOP_RAW_WAKER: RawWaker = RawWaker::new(core::ptr::null(), &NOOP_VTABLE);
+        // SAFETY: the vtable functions ignore the data pointer and do nothing
+        let waker = unsafe { Waker::from_raw(NOOP_RAW_WAKER) };
+
+        let (mut rng, mut reference) = (RngState::new_unseeded(), RngState::new_unseeded());
+        let mut source = Ready;
+        {
+            let mut fut = core::pin::pin!(rng.seed_from_async_entropy_source(&mut source));
+            let poll = fut.as_mut().poll(&mut Context::from_waker(&waker));
+            assert_eq!(poll, Poll::Ready(Ok(())));
+        }
+        reference.seed(&[0x42; 64]);
+        assert_eq!(
+            rng.get_random_bytes::<32>(),
+            reference.get_random_bytes::<32>()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "getrandom")]
+    fn os_entropy_label() {
+        assert_eq!(super::OsEntropy.label(), "getrandom");
+        assert_eq!(Failing.label(), "unlabeled");
+    }
+}
@@ -0,0 +1,121 @@
+use core::fmt;
+
+use crate::{EntropySourceError, HealthTestFailure};
+
+/// Error returned by the operations of a [`ManagedRng`](crate::ManagedRng)
+/// and by [`RngBuilder::build`](crate::RngBuilder::build).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RngError {
+    /// An entropy source failed to deliver entropy.
+    EntropySource(EntropySourceError),
+    /// The raw output of an entropy source failed a health test.
+    HealthTest(HealthTestFailure),
+    /// No entropy source was configured, so the RNG can't be seeded.
+    NoEntropySource,
+    /// The RNG is degraded and configured to refuse output until it has
+    /// absorbed entropy from a full entropy source.
+    Degraded,
+    /// Less entropy has been credited to the absorbed inputs than required
+    /// before producing output.
+    InsufficientEntropy {
+        /// Entropy credited so far, in bits.
+        credited: u64,
+        /// Entropy required, in bits.
+        required: u64,
+    },
+    /// Fewer independent sources than required have each been credited with
+    /// the required entropy, see
+    /// [`RngBuilder::require_independent_sources`](crate::RngBuilder::require_independent_sources).
+    InsufficientSources {
+        /// Number of differently labeled sources credited with enough entropy.
+        qualified: usize,
+        /// Number of such sources required.
+        required: usize,
+    },
+    /// An output request exceeded the maximum request size, see
+    /// [`RngBuilder::max_request_size`](crate::RngBuilder::max_request_size).
+    RequestTooLarge {
+        /// Size of the request in bytes.
+        requested: usize,
+        /// Maximum request size in bytes.
+        max: usize,
+    },
+    /// The RNG is in approved mode but its configuration or state is not
+    /// approved, see
+    /// [`RngBuilder::approved_mode`](crate::RngBuilder::approved_mode).
+    NotApproved,
+    /// The RNG is sandboxed and refuses to make syscalls, see
+    /// [`ManagedRng::enter_sandbox`](crate::ManagedRng::enter_sandbox).
+    Sandboxed,
+    /// A known-answer self-test failed, see
+    /// [`RngBuilder::failure_policy`](crate::RngBuilder::failure_policy).
+    SelfTest,
+    /// The RNG is poisoned and refuses output until recovered, see
+    /// [`ManagedRng::poison`](crate::ManagedRng::poison).
+    Poisoned,
+}
+
+impl fmt::Display for RngError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EntropySource(e) => fmt::Display::fmt(e, f),
+            Self::HealthTest(e) => fmt::Display::fmt(e, f),
+            Self::NoEntropySource => f.write_str("no entropy source configured"),
+            Self::Degraded => f.write_str("RNG is degraded: not seeded from a full entropy source"),
+            Self::InsufficientEntropy { credited, required } => write!(
+                f,
+                "insufficient entropy: {} bits credited, {} bits required",
+                credited, required
+            ),
+            Self::InsufficientSources {
+                qualified,
+                required,
+            } => write!(
+                f,
+                "insufficient independent sources: {} qualified, {} required",
+                qualified, required
+            ),
+            Self::RequestTooLarge { requested, max } => write!(
+                f,
+                "request of {} bytes exceeds the maximum of {} bytes",
+                requested, max
+            ),
+            Self::NotApproved => f.write_str("RNG configuration is not approved"),
+            Self::Sandboxed => f.write_str("RNG is sandboxed: syscalls are not allowed"),
+            Self::SelfTest => f.write_str("known-answer self-test failed"),
+            Self::Poisoned => f.write_str("RNG is poisoned: output refused until recovered"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RngError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::EntropySource(e) => Some(e),
+            Self::HealthTest(e) => Some(e),
+            Self::NoEntropySource
+            | Self::Degraded
+            | Self::InsufficientEntropy { .. }
+            | Self::InsufficientSources { .. }
+            | Self::RequestTooLarge { .. }
+            | Self::NotApproved
+            | Self::Sandboxed
+            | Self::SelfTest
+            | Self::Poisoned => None,
+        }
+    }
+}
+
+impl From<EntropySourceError> for RngError {
+    fn from(e: EntropySourceError) -> Self {
+        Self::EntropySource(e)
+    }
+}
+
+impl From<HealthTestFailure> for RngError {
+    fn from(e: HealthTestFailure) -> Self {
+        Self::HealthTest(e)
+    }
+}
@@ -0,0 +1,158 @@
+use crate::{NonZeroInteger, RngState};
+
+/// Types of which a uniformly random value can be generated from the output of
+/// an [`RngState`]. Use [`RngState::random`] to generate a random value of such
+/// a type.
+///
+/// Implemented for the primitive integer types, `bool`, `char`, byte arrays
+/// `[u8; N]`, the `NonZero*` integer types and tuples (up to 12 elements) of
+/// implementors. With the `derive` feature the trait can be derived for structs
+/// whose fields all implement it.
+///
+/// # Examples
+/// Implementing the trait for a custom type:
+/// ```
+/// # #[cfg(feature = "getrandom")] {
+/// use fast_erasure_shake_rng::{FromRandomBytes, RngState};
+///
+/// struct SessionId {
+///     node: u16,
+///     nonce: [u8; 16],
+/// }
+///
+/// impl FromRandomBytes for SessionId {
+///     fn from_random_bytes(rng: &mut RngState) -> Self {
+///         Self {
+///             node: rng.random(),
+///             nonce: rng.random(),
+///         }
+///     }
+/// }
+///
+/// let mut rng = RngState::new_from_getrandom().unwrap();
+/// let id: SessionId = rng.random();
+/// # }
+/// ```
+///
+/// Deriving the trait:
+/// ```
+/// # #[cfg(feature = "derive")] {
+/// use fast_erasure_shake_rng::{FromRandomBytes, RngState};
+///
+/// #[derive(FromRandomBytes)]
+/// struct Fixture {
+///     id: u64,
+///     pub(crate) flags: (bool, bool),
+///     key: [u8; 32],
+///     small: core::num::NonZeroU8,
+/// }
+///
+/// #[derive(FromRandomBytes)]
+/// struct Pair(u32, [u8; 4]);
+///
+/// let mut rng = RngState::new_from_getrandom().unwrap();
+/// let fixture: Fixture = rng.random();
+/// # }
+/// ```
+pub trait FromRandomBytes: Sized {
+    /// Generate a uniformly random value using output from `rng`. The RNG MUST
+    /// be seeded prior to using this method.
+    fn from_random_bytes(rng: &mut RngState) -> Self;
+}
+
+macro_rules! impl_from_random_bytes_int {
+    ($($int:ty),* $(,)?) => {
+        $(
+            impl FromRandomBytes for $int {
+                fn from_random_bytes(rng: &mut RngState) -> Self {
+                    <$int>::from_ne_bytes(rng.get_random_bytes())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_random_bytes_int!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl FromRandomBytes for bool {
+    fn from_random_bytes(rng: &mut RngState) -> Self {
+        let [byte] = rng.get_random_bytes::<1>();
+        byte & 1 == 1
+    }
+}
+
+impl FromRandomBytes for char {
+    fn from_random_bytes(rng: &mut RngState) -> Self {
+        rng.random_char()
+    }
+}
+
+impl<const N: usize> FromRandomBytes for [u8; N] {
+    fn from_random_bytes(rng: &mut RngState) -> Self {
+        rng.get_random_bytes()
+    }
+}
+
+impl<T: NonZeroInteger> FromRandomBytes for T {
+    fn from_random_bytes(rng: &mut RngState) -> Self {
+        rng.random_nonzero()
+    }
+}
+
+macro_rules! impl_from_random_bytes_tuple {
+    ($($ty:ident),*) => {
+        impl<$($ty: FromRandomBytes),*> FromRandomBytes for ($($ty,)*) {
+            fn from_random_bytes(rng: &mut RngState) -> Self {
+                ($($ty::from_random_bytes(rng),)*)
+            }
+        }
+    };
+}
+
+impl_from_random_bytes_tuple!(A);
+impl_from_random_bytes_tuple!(A, B);
+impl_from_random_bytes_tuple!(A, B, C);
+impl_from_random_bytes_tuple!(A, B, C, D);
+impl_from_random_bytes_tuple!(A, B, C, D, E);
+impl_from_random_bytes_tuple!(A, B, C, D, E, F);
+impl_from_random_bytes_tuple!(A, B, C, D, E, F, G);
+impl_from_random_bytes_tuple!(A, B, C, D, E, F, G, H);
+impl_from_random_bytes_tuple!(A, B, C, D, E, F, G, H, I);
+impl_from_random_bytes_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_from_random_bytes_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_from_random_bytes_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+impl RngState {
+    /// Output a uniformly random value of type `T`. The RNG MUST be seeded
+    /// prior to using this method.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use core::num::NonZeroU64;
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let (id, key): (NonZeroU64, [u8; 32]) = rng.random();
+    /// let coin = rng.random::<bool>();
+    /// # }
+    /// ```
+    pub fn random<T: FromRandomBytes>(&mut self) -> T {
+        T::from_random_bytes(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::NonZeroU16;
+
+    use crate::RngState;
+
+    #[test]
+    fn random_tuple() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"from random bytes tests");
+        let (a, b, _, _): ([u8; 32], [u8; 32], NonZeroU16, bool) = rng.random();
+        assert_ne!(a, b);
+    }
+}
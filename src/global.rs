@@ -0,0 +1,205 @@
+#[cfg(feature = "std")]
+use std::sync::{Mutex, PoisonError};
+
+#[cfg(not(feature = "std"))]
+use crate::{AtomicSpinLock, SyncRngState};
+use crate::{RngState, SecureRng};
+
+/// The global RNG instance, seeded from the OS RNG on first use.
+#[cfg(not(feature = "std"))]
+static GLOBAL_RNG: SyncRngState<AtomicSpinLock> = SyncRngState::new();
+
+/// The global RNG instance, seeded from the OS RNG on first use, with the id of
+/// the process it was last seeded in, to detect forks.
+#[cfg(feature = "std")]
+static GLOBAL_RNG: Mutex<Option<(RngState, u32)>> = Mutex::new(None);
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    /// Whether the current thread is inside a call to `with_global_rng`.
+    static IN_GLOBAL_RNG: core::cell::Cell<bool> = const { core::cell::Cell::new(false) };
+}
+
+/// Marks the current thread as inside `with_global_rng` to detect reentrant
+/// calls, until dropped.
+#[cfg(feature = "std")]
+struct ReentrancyGuard;
+
+#[cfg(feature = "std")]
+impl ReentrancyGuard {
+    fn enter() -> Self {
+        IN_GLOBAL_RNG.with(|flag| {
+            assert!(!flag.replace(true), "with_global_rng called reentrantly");
+        });
+        Self
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for ReentrancyGuard {
+    fn drop(&mut self) {
+        IN_GLOBAL_RNG.with(|flag| flag.set(false));
+    }
+}
+
+/// Call `f` with exclusive access to the global RNG instance, which is seeded
+/// from the OS RNG on first use.
+///
+/// Concurrent calls wait for each other. The global RNG is locked for the
+/// duration of `f`, so keep `f` short. Calling `with_global_rng` from within
+/// `f` panics if the `std` feature is enabled and deadlocks otherwise.
+///
+/// # Forks
+/// With the `std` feature, the global RNG remembers the id of the process
+/// it was seeded in, and reseeds from the OS RNG when it is used in a
+/// different process, so after `fork()` parent and child produce different
+/// output. **Without the `std` feature forks are not detected**: a forked
+/// child continues with a copy of the parent's state, and both produce the
+/// same output. Enable `std` or don't use the global RNG in processes which
+/// fork.
+///
+/// # Panics
+/// Panics if the OS RNG fails to seed the global RNG on first use, or on a
+/// reentrant call when the `std` feature is enabled.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::with_global_rng;
+///
+/// let key: [u8; 32] = with_global_rng(|rng| rng.get_random_bytes());
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "getrandom")))]
+pub fn with_global_rng<R, F: FnOnce(&mut RngState) -> R>(f: F) -> R {
+    #[cfg(feature = "std")]
+    {
+        let _reentrancy = ReentrancyGuard::enter();
+        // the state stays consistent if `f` panics, so poisoning is harmless
+        let mut slot = GLOBAL_RNG.lock().unwrap_or_else(PoisonError::into_inner);
+        let pid = std::process::id();
+        let (rng, seeded_pid) = slot.get_or_insert_with(|| (new_global_rng(), pid));
+        if *seeded_pid != pid {
+            // forked child, diverge from the parent
+            rng.seed_with_getrandom()
+                .expect("failed to reseed the global RNG");
+            *seeded_pid = pid;
+        }
+        f(rng)
+    }
+    #[cfg(not(feature = "std"))]
+    GLOBAL_RNG.with_slot(|slot| f(slot.get_or_insert_with(new_global_rng)))
+}
+
+fn new_global_rng() -> RngState {
+    RngState::new_from_getrandom().expect("failed to seed the global RNG")
+}
+
+/// Zero-sized handle to the global RNG instance (see [`with_global_rng`]),
+/// for APIs which take an RNG by value, like `rand_core::OsRng`, without
+/// plumbing state around.
+///
+/// Every call locks the global RNG for its duration. Implements
+/// [`SecureRng`], and with the `rand-core` feature `RngCore` and `CryptoRng`.
+/// Like any use of the global RNG, using the handle inside
+/// [`with_global_rng`] panics or deadlocks.
+///
+/// **Unlike `OsRng`, the handle is only fork safe with the `std` feature**,
+/// see [`with_global_rng`#forks](with_global_rng#forks).
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::{FastErasureRng, SecureRng};
+///
+/// fn generate_key(mut rng: impl SecureRng) -> [u8; 32] {
+///     let mut key = [0; 32];
+///     rng.fill(&mut key);
+///     key
+/// }
+///
+/// let key = generate_key(FastErasureRng);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "getrandom")))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FastErasureRng;
+
+impl SecureRng for FastErasureRng {
+    fn fill(&mut self, dest: &mut [u8]) {
+        with_global_rng(|rng| rng.fill_random_bytes(dest));
+    }
+
+    fn reseed(&mut self, seed: &[u8]) {
+        with_global_rng(|rng| rng.seed(seed));
+    }
+}
+
+#[cfg(feature = "rand-core")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rand-core")))]
+impl rand_core::RngCore for FastErasureRng {
+    fn next_u32(&mut self) -> u32 {
+        u32::from_ne_bytes(with_global_rng(|rng| rng.get_random_bytes()))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        u64::from_ne_bytes(with_global_rng(|rng| rng.get_random_bytes()))
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.fill(dest);
+    }
+
+    /// Equivalent to `fill_bytes`. Always returns success.
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill(dest);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rand-core")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rand-core")))]
+impl rand_core::CryptoRng for FastErasureRng {}
+
+#[cfg(test)]
+mod tests {
+    use super::{with_global_rng, FastErasureRng};
+    use crate::SecureRng;
+
+    #[test]
+    fn global_rng_advances() {
+        let a: [u8; 16] = with_global_rng(|rng| rng.get_random_bytes());
+        let b: [u8; 16] = with_global_rng(|rng| rng.get_random_bytes());
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn handle_uses_global_rng() {
+        let (mut a, mut b) = ([0; 16], [0; 16]);
+        FastErasureRng.fill(&mut a);
+        FastErasureRng.fill(&mut b);
+        assert_ne!(a, b);
+        #[cfg(feature = "rand-core")]
+        assert_ne!(
+            rand_core::RngCore::next_u64(&mut FastErasureRng),
+            rand_core::RngCore::next_u64(&mut FastErasureRng)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn reseeds_in_other_process() {
+        use super::{PoisonError, GLOBAL_RNG};
+
+        // other tests may have poisoned the lock
+        let lock = || GLOBAL_RNG.lock().unwrap_or_else(PoisonError::into_inner);
+        with_global_rng(|_| ());
+        lock().as_mut().unwrap().1 = 0;
+        with_global_rng(|_| ());
+        let pid = lock().as_ref().unwrap().1;
+        assert_eq!(pid, std::process::id());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    #[should_panic(expected = "reentrantly")]
+    fn reentrant_call_panics() {
+        with_global_rng(|_| with_global_rng(|_| ()));
+    }
+}
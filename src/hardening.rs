@@ -0,0 +1,258 @@
+use crate::{u64_slice_as_ne_bytes_mut, RngState, CAPACITY_BYTES, LANES, RATE_BYTES};
+
+/// Maximum number of dummy permutations run on either side of every real
+/// application of the permutation.
+const MAX_DUMMY_PERMUTATIONS: u64 = 3;
+
+/// State of the power analysis countermeasures of a
+/// [`ManagedRng`](crate::ManagedRng), see
+/// [`RngBuilder::power_analysis_hardening`](crate::RngBuilder::power_analysis_hardening).
+///
+/// Holds a decoy Keccak state, which is permuted by the dummy operations and
+/// whose lanes provide the randomness choosing the number of dummy
+/// operations and the processing order. The decoy only decides *how* the real
+/// state is processed, never *what* is computed, so its compromise only
+/// removes the countermeasures.
+pub(crate) struct Hardening {
+    decoy: [u64; LANES],
+    /// Index of the next unused lane of the decoy.
+    next_lane: usize,
+}
+
+impl Hardening {
+    /// Create the countermeasure state, initializing the decoy from the output
+    /// of `rng`.
+    pub(crate) fn new(rng: &mut RngState) -> Self {
+        let mut decoy = [0; LANES];
+        rng.fill_random_bytes(u64_slice_as_ne_bytes_mut(&mut decoy));
+        Self {
+            decoy,
+            next_lane: LANES,
+        }
+    }
+
+    /// Next 64 bits of randomness from the decoy, permuting it whenever all
+    /// lanes are used up.
+    fn next_u64(&mut self) -> u64 {
+        if self.next_lane == LANES {
+            keccak::f1600(&mut self.decoy);
+            self.next_lane = 0;
+        }
+        let value = self.decoy[self.next_lane];
+        self.next_lane += 1;
+        value
+    }
+
+    /// Run a random number of dummy permutations on the decoy,
+    /// indistinguishable in shape from the real permutation.
+    fn dummy_permutations(&mut self) {
+        for _ in 0..self.next_u64() % (MAX_DUMMY_PERMUTATIONS + 1) {
+            keccak::f1600(&mut self.decoy);
+        }
+    }
+
+    /// Random offset at which to start processing a buffer of `len` bytes.
+    fn offset(&mut self, len: usize) -> usize {
+        if len == 0 {
+            return 0;
+        }
+        // the bias of the reduction is irrelevant for a processing order
+        #[allow(clippy::cast_possible_truncation)]
+        let value = self.next_u64() as usize;
+        value % len
+    }
+
+    /// Apply the permutation to the state of `rng`, hidden between a random
+    /// number of dummy permutations.
+    fn apply_f(&mut self, rng: &mut RngState) {
+        self.dummy_permutations();
+        rng.state.apply_f();
+        self.dummy_permutations();
+    }
+}
+
+impl Drop for Hardening {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.decoy);
+    }
+}
+
+/// Xor `src` into `dst` byte by byte, starting at index `offset` and wrapping
+/// around.
+fn xor_rotated(dst: &mut [u8], src: &[u8], offset: usize) {
+    let (src_head, src_tail) = src.split_at(offset);
+    let (dst_head, dst_tail) = dst.split_at_mut(offset);
+    for (d, s) in dst_tail.iter_mut().zip(src_tail) {
+        *d ^= s;
+    }
+    for (d, s) in dst_head.iter_mut().zip(src_head) {
+        *d ^= s;
+    }
+}
+
+/// Copy `src` to `dst` byte by byte, starting at index `offset` and wrapping
+/// around.
+fn copy_rotated(dst: &mut [u8], src: &[u8], offset: usize) {
+    let (src_head, src_tail) = src.split_at(offset);
+    let (dst_head, dst_tail) = dst.split_at_mut(offset);
+    dst_tail.copy_from_slice(src_tail);
+    dst_head.copy_from_slice(src_head);
+}
+
+impl RngState {
+    /// Hardened version of `absorb_partial_block_padded`, accepting any
+    /// length like it.
+    fn absorb_partial_block_padded_hardened(&mut self, block: &[u8], hardening: &mut Hardening) {
+        let mut blocks = block.chunks_exact(RATE_BYTES);
+        for full_block in &mut blocks {
+            let offset = hardening.offset(RATE_BYTES);
+            xor_rotated(self.state.get_rate_bytes_mut(), full_block, offset);
+            hardening.apply_f(self);
+        }
+        let block = blocks.remainder();
+        let offset = hardening.offset(block.len());
+        let rate_state = self.state.get_rate_bytes_mut();
+        xor_rotated(&mut rate_state[..block.len()], block, offset);
+        rate_state[block.len()] ^= 0b10000000;
+        rate_state[RATE_BYTES - 1] ^= 0b00000001;
+        hardening.apply_f(self);
+    }
+
+    /// Hardened version of [`Self::seed`], producing the same state.
+    pub(crate) fn seed_hardened(&mut self, seed: &[u8], hardening: &mut Hardening) {
+        self.absorb_partial_block_padded_hardened(seed, hardening);
+    }
+
+    /// Hardened version of [`Self::seed_with_64`], producing the same state.
+    /// Unhardened if `hardening` is `None`.
+    pub(crate) fn seed_with_64_hardened<E, F: FnOnce(&mut [u8]) -> Result<(), E>>(
+        &mut self,
+        hardening: Option<&mut Hardening>,
+        f: F,
+    ) -> Result<(), E> {
+        let Some(hardening) = hardening else {
+            return self.seed_with_64(f);
+        };
+        let mut buffer = zeroize::Zeroizing::new([0u8; 64]);
+        f(buffer.as_mut())?;
+        self.absorb_partial_block_padded_hardened(buffer.as_ref(), hardening);
+        Ok(())
+    }
+
+    /// Hardened version of `basic_initial_output`.
+    fn initial_output_hardened(&mut self, dest: &mut [u8], hardening: &mut Hardening) {
+        let len = core::cmp::min(dest.len(), RATE_BYTES);
+        let offset = hardening.offset(len);
+        copy_rotated(
+            &mut dest[..len],
+            &self.state.get_rate_bytes()[..len],
+            offset,
+        );
+        hardening.apply_f(self);
+    }
+
+    /// Hardened version of `basic_intermediate_output`.
+    fn intermediate_output_hardened(&mut self, dest: &mut [u8], hardening: &mut Hardening) {
+        let len = core::cmp::min(dest.len(), RATE_BYTES + CAPACITY_BYTES);
+        let offset = hardening.offset(len);
+        copy_rotated(
+            &mut dest[..len],
+            &self.state.get_rate_zeroized_capacity_bytes()[..len],
+            offset,
+        );
+        hardening.apply_f(self);
+    }
+
+    /// Hardened version of [`Self::fill_random_bytes`], producing the same
+    /// output.
+    pub(crate) fn fill_random_bytes_hardened(
+        &mut self,
+        mut dest: &mut [u8],
+        hardening: &mut Hardening,
+    ) {
+        self.initial_output_hardened(dest, hardening);
+        if dest.len() > RATE_BYTES {
+            dest = &mut dest[RATE_BYTES..];
+            loop {
+                self.intermediate_output_hardened(dest, hardening);
+                if dest.len() <= RATE_BYTES + CAPACITY_BYTES {
+                    break;
+                }
+                dest = &mut dest[RATE_BYTES + CAPACITY_BYTES..];
+            }
+        }
+        self.basic_make_forward_secure();
+    }
+
+    /// Hardened version of `fill_random_bytes_conservative`, producing the
+    /// same output.
+    pub(crate) fn fill_random_bytes_conservative_hardened(
+        &mut self,
+        dest: &mut [u8],
+        hardening: &mut Hardening,
+    ) {
+        for block in dest.chunks_mut(RATE_BYTES) {
+            self.initial_output_hardened(block, hardening);
+            self.basic_make_forward_secure();
+        }
+        if dest.is_empty() {
+            self.fill_random_bytes_hardened(dest, hardening);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Hardening;
+    use crate::RngState;
+
+    fn pair() -> (RngState, RngState, Hardening) {
+        let mut plain = RngState::new_unseeded();
+        plain.seed(b"hardening tests");
+        let mut hardened = RngState::new_unseeded();
+        hardened.seed(b"hardening tests");
+        let mut decoy_rng = RngState::new_unseeded();
+        decoy_rng.seed(b"hardening tests decoy");
+        (plain, hardened, Hardening::new(&mut decoy_rng))
+    }
+
+    #[test]
+    fn hardened_seeding_matches_plain() {
+        let (mut plain, mut hardened, mut hardening) = pair();
+        for len in [0, 1, 71, 72, 73, 200] {
+            let seed = [0x5a; 200];
+            plain.seed(&seed[..len]);
+            hardened.seed_hardened(&seed[..len], &mut hardening);
+        }
+        plain
+            .seed_with_64(|buf| {
+                buf.fill(7);
+                Ok::<_, ()>(())
+            })
+            .unwrap();
+        hardened
+            .seed_with_64_hardened(Some(&mut hardening), |buf| {
+                buf.fill(7);
+                Ok::<_, ()>(())
+            })
+            .unwrap();
+        assert_eq!(
+            plain.get_random_bytes::<32>(),
+            hardened.get_random_bytes::<32>()
+        );
+    }
+
+    #[test]
+    fn hardened_output_matches_plain() {
+        let (mut plain, mut hardened, mut hardening) = pair();
+        for len in [0, 1, 72, 73, 208, 209, 1000] {
+            let (mut expected, mut out) = ([0; 1000], [0; 1000]);
+            plain.fill_random_bytes(&mut expected[..len]);
+            hardened.fill_random_bytes_hardened(&mut out[..len], &mut hardening);
+            assert_eq!(expected, out);
+            plain.fill_random_bytes_conservative(&mut expected[..len]);
+            hardened.fill_random_bytes_conservative_hardened(&mut out[..len], &mut hardening);
+            assert_eq!(expected, out);
+        }
+    }
+}
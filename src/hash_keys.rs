@@ -0,0 +1,154 @@
+#![allow(deprecated)] // `SipHasher` is deprecated only in favour of std's unnameable hasher
+
+use core::fmt;
+use core::hash::{BuildHasher, Hasher, SipHasher};
+
+use crate::RngState;
+
+impl RngState {
+    /// Output a pair of random 64-bit keys for a keyed hash function like
+    /// SipHash, e.g. to harden hash maps against HashDoS.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let (k0, k1) = rng.hasher_keys();
+    /// # }
+    /// ```
+    pub fn hasher_keys(&mut self) -> (u64, u64) {
+        let keys: [u8; 16] = self.get_random_bytes();
+        let (k0, k1) = keys.split_at(8);
+        (
+            u64::from_ne_bytes(k0.try_into().unwrap()),
+            u64::from_ne_bytes(k1.try_into().unwrap()),
+        )
+    }
+}
+
+/// A [`BuildHasher`] creating SipHash-2-4 hashers with random keys from this
+/// crate's RNG, like `std::collections::hash_map::RandomState`. Hardens hash
+/// maps keyed by attacker controlled data against HashDoS.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "getrandom")] {
+/// use std::collections::HashMap;
+///
+/// use fast_erasure_shake_rng::{RandomState, RngState};
+///
+/// let mut rng = RngState::new_from_getrandom().unwrap();
+/// let mut map = HashMap::with_hasher(RandomState::from_rng(&mut rng));
+/// map.insert("key", 1);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct RandomState {
+    k0: u64,
+    k1: u64,
+}
+
+impl RandomState {
+    /// Create a new instance with random keys from `rng`.
+    pub fn from_rng(rng: &mut RngState) -> Self {
+        let (k0, k1) = rng.hasher_keys();
+        Self { k0, k1 }
+    }
+
+    /// Create a new instance with random keys from the global RNG, see
+    /// [`with_global_rng`](crate::with_global_rng).
+    ///
+    /// # Panics
+    /// Panics if the OS RNG fails to seed the global RNG on first use.
+    #[cfg(all(feature = "getrandom", target_has_atomic = "8"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "getrandom")))]
+    pub fn new() -> Self {
+        crate::with_global_rng(Self::from_rng)
+    }
+}
+
+#[cfg(all(feature = "getrandom", target_has_atomic = "8"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "getrandom")))]
+impl Default for RandomState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for RandomState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RandomState([REDACTED])")
+    }
+}
+
+impl BuildHasher for RandomState {
+    type Hasher = KeyedHasher;
+
+    fn build_hasher(&self) -> KeyedHasher {
+        KeyedHasher(SipHasher::new_with_keys(self.k0, self.k1))
+    }
+}
+
+/// A SipHash-2-4 hasher with keys from a [`RandomState`].
+#[derive(Clone)]
+pub struct KeyedHasher(SipHasher);
+
+impl fmt::Debug for KeyedHasher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("KeyedHasher([REDACTED])")
+    }
+}
+
+impl Hasher for KeyedHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::hash::BuildHasher;
+
+    use super::RandomState;
+    use crate::RngState;
+
+    fn test_rng() -> RngState {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"hash keys tests");
+        rng
+    }
+
+    #[test]
+    fn keys_differ_per_state() {
+        let mut rng = test_rng();
+        let (a, b) = (
+            RandomState::from_rng(&mut rng),
+            RandomState::from_rng(&mut rng),
+        );
+        assert_eq!(a.hash_one("key"), a.hash_one("key"));
+        assert_ne!(a.hash_one("key"), b.hash_one("key"));
+    }
+
+    #[test]
+    fn hasher_keys_are_independent() {
+        let (k0, k1) = test_rng().hasher_keys();
+        assert_ne!(k0, k1);
+    }
+
+    #[test]
+    fn debug_is_redacted() {
+        let state = RandomState::from_rng(&mut test_rng());
+        extern crate std;
+        assert_eq!(std::format!("{:?}", state), "RandomState([REDACTED])");
+        assert_eq!(
+            std::format!("{:?}", state.build_hasher()),
+            "KeyedHasher([REDACTED])"
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! HAZARDOUS MATERIALS: a low-level duplex interface over the Keccak state
+//! of the RNG, for protocol designers building STROBE-like constructions.
+//!
+//! The [`Duplex`] re-uses the RNG's state handling, padding and erasure, but
+//! none of its guarantees: nothing stops you from squeezing before absorbing
+//! a key, reusing a state for two purposes or forgetting to ratchet. Like the
+//! RNG, its output depends on the target's endianness and is not
+//! interoperable with standard Keccak based constructions.
+//!
+//! The state consists of the rate area (72 bytes), which is absorbed into and
+//! squeezed from, the zeroized capacity area (64 bytes), which is zeroized by
+//! [`Duplex::ratchet`], and the inner capacity area (64 bytes).
+//!
+//! [`duplicate_state`] creates an exact copy of an [`RngState`], for research
+//! and tests.
+
+use crate::internal_state::InternalState;
+use crate::{RngState, RATE_BYTES};
+
+/// Padding byte ending the padding, in the last byte of the rate.
+const PAD_END: u8 = 0b0000_0001;
+
+/// A raw duplex over the Keccak-f\[1600\] state, see the [module
+/// documentation](self). The state is zeroized on drop.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::hazmat::Duplex;
+///
+/// let mut duplex = Duplex::new();
+/// duplex.absorb_padded(b"protocol v1", 0x02);
+/// duplex.absorb_padded(b"shared secret", 0x04);
+/// let mut key = [0; 32];
+/// duplex.squeeze_block(&mut key);
+/// duplex.ratchet();
+/// ```
+pub struct Duplex {
+    state: InternalState,
+}
+
+impl Duplex {
+    /// Number of bytes absorbed or squeezed per block.
+    pub const RATE_BYTES: usize = RATE_BYTES;
+
+    /// Create a duplex with the all-zero state.
+    pub fn new() -> Self {
+        Self {
+            state: InternalState::new(),
+        }
+    }
+
+    /// Xor `data` into the start of the rate area without permuting.
+    ///
+    /// # Panics
+    /// Panics if `data` is longer than the rate.
+    fn xor_into_rate(&mut self, data: &[u8]) {
+        let rate = self.state.get_rate_bytes_mut();
+        for (s, b) in rate[..data.len()].iter_mut().zip(data) {
+            *s ^= b;
+        }
+    }
+
+    /// Absorb a full block into the rate area without padding, then permute.
+    pub fn absorb_block(&mut self, block: &[u8; RATE_BYTES]) {
+        self.xor_into_rate(block);
+        self.state.apply_f();
+    }
+
+    /// Absorb `data` of less than [`Self::RATE_BYTES`] bytes, padded with the
+    /// non-zero domain separation byte `domain` followed by pad10*1, then
+    /// permute. The RNG itself uses domain `0x80`; pick distinct domains for
+    /// distinct kinds of input.
+    ///
+    /// The least significant bit of `domain` must be clear: it is the bit
+    /// ending the padding, so for 71 bytes of `data` a domain with this bit set
+    /// would cancel the padding.
+    ///
+    /// # Panics
+    /// Panics if `data` doesn't fit in the rate with its padding, or if
+    /// `domain` is zero or odd.
+    pub fn absorb_padded(&mut self, data: &[u8], domain: u8) {
+        assert!(data.len() < RATE_BYTES, "data doesn't fit in the rate");
+        assert_ne!(domain, 0, "domain separation byte must be non-zero");
+        assert_eq!(
+            domain & PAD_END,
+            0,
+            "domain separation byte must not overlap the padding"
+        );
+        self.xor_into_rate(data);
+        let rate = self.state.get_rate_bytes_mut();
+        rate[data.len()] ^= domain;
+        rate[RATE_BYTES - 1] ^= PAD_END;
+        self.state.apply_f();
+    }
+
+    /// Squeeze up to [`Self::RATE_BYTES`] bytes from the rate area into
+    /// `out`, then permute.
+    ///
+    /// # Panics
+    /// Panics if `out` is longer than the rate.
+    pub fn squeeze_block(&mut self, out: &mut [u8]) {
+        assert!(out.len() <= RATE_BYTES, "output doesn't fit in the rate");
+        out.copy_from_slice(&self.state.get_rate_bytes()[..out.len()]);
+        self.state.apply_f();
+    }
+
+    /// Apply the permutation.
+    pub fn permute(&mut self) {
+        self.state.apply_f();
+    }
+
+    /// Permute and zeroize the zeroized capacity area, making the state
+    /// forward secure: earlier inputs and outputs can't be recovered from it.
+    pub fn ratchet(&mut self) {
+        self.state.apply_f();
+        self.state.zeroize_for_forward_security();
+    }
+}
+
+impl Default for Duplex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Duplex {
+    fn drop(&mut self) {
+        self.state.zeroize();
+    }
+}
+
+/// Create an exact duplicate of `rng`, including its full (secret) state.
+///
+/// [`RngState`] deliberately doesn't implement `Clone`: the duplicate
+/// produces exactly the same output as the original, so using both for
+/// cryptographic purposes repeats keys and nonces. Only use this to
+/// investigate the RNG or in tests, e.g. to compare two code paths on
+/// identical states.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::hazmat::duplicate_state;
+/// use fast_erasure_shake_rng::RngState;
+///
+/// let mut rng = RngState::new_from_getrandom().unwrap();
+/// let mut copy = duplicate_state(&rng);
+/// assert_eq!(rng.get_random_bytes::<16>(), copy.get_random_bytes::<16>());
+/// ```
+pub fn duplicate_state(rng: &RngState) -> RngState {
+    RngState {
+        state: rng.state.duplicate(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{duplicate_state, Duplex};
+    use crate::RngState;
+
+    #[test]
+    fn default_domain_matches_rng_seeding() {
+        let mut rng = RngState::new_unseeded();
+        let mut duplex = Duplex::new();
+        // replay the diversifier the RNG absorbs on creation
+        let diversifier =
+            b"FAST ERASURE KECCAK SPONGE/DUPLEX PRNG\0RUST CRATE fast-erasure-shake-rng 0.2.0\0\0";
+        duplex.absorb_block(diversifier[..72].try_into().unwrap());
+        duplex.absorb_padded(&diversifier[72..], 0x80);
+        rng.seed(b"hazmat tests");
+        duplex.absorb_padded(b"hazmat tests", 0x80);
+        let mut out = [0; 32];
+        duplex.squeeze_block(&mut out);
+        assert_eq!(out, rng.get_random_bytes::<32>());
+    }
+
+    #[test]
+    fn domains_separate() {
+        let (mut a, mut b) = (Duplex::new(), Duplex::new());
+        a.absorb_padded(b"x", 2);
+        b.absorb_padded(b"x", 4);
+        let (mut out_a, mut out_b) = ([0; 16], [0; 16]);
+        a.squeeze_block(&mut out_a);
+        b.squeeze_block(&mut out_b);
+        assert_ne!(out_a, out_b);
+    }
+
+    #[test]
+    fn full_padded_block_differs_from_raw_block() {
+        let data = [7; 71];
+        let mut block = [0; 72];
+        block[..71].copy_from_slice(&data);
+        let (mut padded, mut raw) = (Duplex::new(), Duplex::new());
+        padded.absorb_padded(&data, 0x02);
+        raw.absorb_block(&block);
+        let (mut out_padded, mut out_raw) = ([0; 16], [0; 16]);
+        padded.squeeze_block(&mut out_padded);
+        raw.squeeze_block(&mut out_raw);
+        assert_ne!(out_padded, out_raw);
+    }
+
+    #[test]
+    #[should_panic]
+    fn domain_overlapping_padding() {
+        // would absorb exactly like `absorb_block` of `[7; 71]` and a zero byte
+        Duplex::new().absorb_padded(&[7; 71], 0x01);
+    }
+
+    #[test]
+    fn duplicate_is_independent() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"hazmat tests");
+        let mut copy = duplicate_state(&rng);
+        assert_eq!(rng.get_random_bytes::<32>(), copy.get_random_bytes::<32>());
+        copy.seed(b"diverge");
+        assert_ne!(rng.get_random_bytes::<32>(), copy.get_random_bytes::<32>());
+    }
+}
@@ -0,0 +1,425 @@
+use core::fmt;
+
+/// Window size of the adaptive proportion test for non-binary (byte) samples,
+/// as recommended by NIST SP 800-90B, section 4.4.2.
+const APT_WINDOW: u32 = 512;
+
+/// Negative base-2 logarithm of the false positive probability `alpha` of the
+/// health tests.
+const ALPHA_LOG2: u32 = 20;
+
+/// Negative base-2 logarithm of the smallest false positive probability
+/// `alpha` recommended by NIST SP 800-90B, section 4.4.
+pub(crate) const MIN_ALPHA_LOG2: u32 = 40;
+
+/// Configuration of the continuous health tests from NIST SP 800-90B, section
+/// 4.4, which are run on the raw samples of entropy sources. Every byte
+/// delivered by a source is one sample.
+///
+/// The cutoffs are derived from an assessed min-entropy per sample with a
+/// false positive probability of `2^-20` per test, see
+/// [`Self::for_min_entropy`].
+///
+/// The online tests of AIS 31 can be run alongside, see
+/// [`Self::with_ais31_online_tests`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HealthTestConfig {
+    rct_cutoff: u32,
+    apt_cutoff: u32,
+    ais31: bool,
+}
+
+impl HealthTestConfig {
+    /// Configuration for a noise source with an assessed min-entropy of
+    /// `bits_per_byte` bits per byte sample.
+    ///
+    /// # Panics
+    /// Panics if `bits_per_byte` is not in `1..=8`.
+    ///
+    /// # Examples
+    /// ```
+    /// use fast_erasure_shake_rng::HealthTestConfig;
+    ///
+    /// let config = HealthTestConfig::for_min_entropy(1);
+    /// assert_eq!(config.rct_cutoff(), 21);
+    /// assert_eq!(config.apt_cutoff(), 311);
+    /// ```
+    pub fn for_min_entropy(bits_per_byte: u32) -> Self {
+        Self::for_min_entropy_and_alpha(bits_per_byte, ALPHA_LOG2)
+    }
+
+    /// Like [`Self::for_min_entropy`], with a false positive probability of
+    /// `2^-alpha_log2` per test.
+    pub(crate) fn for_min_entropy_and_alpha(bits_per_byte: u32, alpha_log2: u32) -> Self {
+        assert!(
+            (1..=8).contains(&bits_per_byte),
+            "min-entropy per byte must be between 1 and 8 bits"
+        );
+        Self {
+            rct_cutoff: 1 + alpha_log2.div_ceil(bits_per_byte),
+            apt_cutoff: apt_cutoff(bits_per_byte, alpha_log2),
+            ais31: false,
+        }
+    }
+
+    /// Configuration with explicit cutoffs for the repetition count test and
+    /// the adaptive proportion test (window of 512 samples). A test fails once
+    /// its count reaches the cutoff.
+    ///
+    /// # Panics
+    /// Panics if a cutoff is below 2 or if `apt_cutoff` exceeds the window
+    /// size.
+    pub fn with_cutoffs(rct_cutoff: u32, apt_cutoff: u32) -> Self {
+        assert!(
+            rct_cutoff >= 2 && apt_cutoff >= 2,
+            "cutoffs must be at least 2"
+        );
+        assert!(
+            apt_cutoff <= APT_WINDOW,
+            "APT cutoff exceeds the window size"
+        );
+        Self {
+            rct_cutoff,
+            apt_cutoff,
+            ais31: false,
+        }
+    }
+
+    /// Additionally run the online tests of BSI AIS 31 (the monobit, poker,
+    /// runs and long run tests T1 to T4) on consecutive blocks of 20000 bits,
+    /// for certification contexts requiring them.
+    ///
+    /// The AIS 31 tests assume (nearly) unbiased bits, so they are meant for
+    /// physical sources with internal post-processing, not for raw noise of
+    /// low entropy. With these tests enabled, at least 2500 bytes are drawn
+    /// from every source on seeding so a full block is tested.
+    ///
+    /// # Examples
+    /// ```
+    /// use fast_erasure_shake_rng::HealthTestConfig;
+    ///
+    /// let config = HealthTestConfig::for_min_entropy(8).with_ais31_online_tests();
+    /// assert!(config.ais31_online_tests());
+    /// ```
+    pub const fn with_ais31_online_tests(mut self) -> Self {
+        self.ais31 = true;
+        self
+    }
+
+    /// Whether the AIS 31 online tests are run, see
+    /// [`Self::with_ais31_online_tests`].
+    pub const fn ais31_online_tests(&self) -> bool {
+        self.ais31
+    }
+
+    /// Cutoff of the repetition count test.
+    pub const fn rct_cutoff(&self) -> u32 {
+        self.rct_cutoff
+    }
+
+    /// Cutoff of the adaptive proportion test.
+    pub const fn apt_cutoff(&self) -> u32 {
+        self.apt_cutoff
+    }
+}
+
+impl Default for HealthTestConfig {
+    /// Conservative configuration for a source assessed at 1 bit of
+    /// min-entropy per byte.
+    fn default() -> Self {
+        Self::for_min_entropy(1)
+    }
+}
+
+/// Adaptive proportion test cutoff `1 + CRITBINOM(W, 2^-H, 1 - alpha)`, i.e.
+/// one more than the smallest `k` for which the binomial distribution
+/// function reaches `1 - alpha`.
+fn apt_cutoff(bits_per_byte: u32, alpha_log2: u32) -> u32 {
+    let p = 1.0 / f64::from(1u32 << bits_per_byte);
+    let mut alpha = 1.0;
+    for _ in 0..alpha_log2 {
+        alpha /= 2.0;
+    }
+    let target = 1.0 - alpha;
+    // probability mass of `k = 0`, i.e. `(1 - p)^W`
+    let mut pmf = 1.0;
+    for _ in 0..APT_WINDOW {
+        pmf *= 1.0 - p;
+    }
+    let mut cdf = pmf;
+    let mut k = 0;
+    while cdf < target && k < APT_WINDOW {
+        pmf *= f64::from(APT_WINDOW - k) / f64::from(k + 1) * p / (1.0 - p);
+        k += 1;
+        cdf += pmf;
+    }
+    k + 1
+}
+
+/// Failure of a continuous health test.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HealthTestFailure {
+    /// The repetition count test detected too many identical consecutive
+    /// samples.
+    RepetitionCount,
+    /// The adaptive proportion test detected a sample value occurring too
+    /// often within a window.
+    AdaptiveProportion,
+    /// The AIS 31 monobit test (T1) detected too many or too few one bits.
+    Ais31Monobit,
+    /// The AIS 31 poker test (T2) detected non-uniform 4-bit patterns.
+    Ais31Poker,
+    /// The AIS 31 runs test (T3) detected an unexpected distribution of runs.
+    Ais31Runs,
+    /// The AIS 31 long run test (T4) detected a run of 34 or more equal bits.
+    Ais31LongRun,
+}
+
+impl fmt::Display for HealthTestFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RepetitionCount => f.write_str("repetition count health test failed"),
+            Self::AdaptiveProportion => f.write_str("adaptive proportion health test failed"),
+            Self::Ais31Monobit => f.write_str("AIS 31 monobit test failed"),
+            Self::Ais31Poker => f.write_str("AIS 31 poker test failed"),
+            Self::Ais31Runs => f.write_str("AIS 31 runs test failed"),
+            Self::Ais31LongRun => f.write_str("AIS 31 long run test failed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HealthTestFailure {}
+
+/// Number of bits in a block of the AIS 31 online tests.
+const AIS31_BLOCK_BITS: u32 = 20000;
+
+/// Inclusive bounds on the number of runs of length 1 to 5 and 6 or more (of
+/// zeros and of ones separately) in the AIS 31 runs test T3.
+const AIS31_RUN_BOUNDS: [(u32, u32); 6] = [
+    (2267, 2733),
+    (1079, 1421),
+    (502, 748),
+    (223, 402),
+    (90, 223),
+    (90, 223),
+];
+
+/// Length of a run failing the AIS 31 long run test T4.
+const AIS31_LONG_RUN: u32 = 34;
+
+/// Running state of the AIS 31 online tests T1 to T4 over the current block of
+/// 20000 bits.
+#[derive(Debug, Default)]
+struct Ais31Tests {
+    bits: u32,
+    ones: u32,
+    nibbles: [u32; 16],
+    runs: [[u32; 6]; 2],
+    run_bit: u8,
+    run_len: u32,
+}
+
+impl Ais31Tests {
+    fn end_run(&mut self) {
+        if self.run_len > 0 {
+            let index = core::cmp::min(self.run_len, 6) as usize - 1;
+            self.runs[usize::from(self.run_bit)][index] += 1;
+        }
+    }
+
+    /// Feed the 8 bits of `sample`, most significant first.
+    fn feed(&mut self, sample: u8) -> Result<(), HealthTestFailure> {
+        self.nibbles[usize::from(sample >> 4)] += 1;
+        self.nibbles[usize::from(sample & 0xf)] += 1;
+        for i in (0..8).rev() {
+            let bit = (sample >> i) & 1;
+            self.ones += u32::from(bit);
+            if self.run_len > 0 && bit == self.run_bit {
+                self.run_len += 1;
+                if self.run_len >= AIS31_LONG_RUN {
+                    return Err(HealthTestFailure::Ais31LongRun);
+                }
+            } else {
+                self.end_run();
+                self.run_bit = bit;
+                self.run_len = 1;
+            }
+        }
+        self.bits += 8;
+        if self.bits == AIS31_BLOCK_BITS {
+            self.end_run();
+            let res = self.evaluate();
+            *self = Self::default();
+            res?;
+        }
+        Ok(())
+    }
+
+    /// Evaluate T1 to T3 on a complete block.
+    fn evaluate(&self) -> Result<(), HealthTestFailure> {
+        if !(9655..=10345).contains(&self.ones) {
+            return Err(HealthTestFailure::Ais31Monobit);
+        }
+        // X = 16 / 5000 * sum(f_i^2) - 5000 must be in (1.03, 57.4)
+        let sum_squares: u64 = self
+            .nibbles
+            .iter()
+            .map(|&f| u64::from(f) * u64::from(f))
+            .sum();
+        if !(25_005_151..25_287_000).contains(&(16 * sum_squares)) {
+            return Err(HealthTestFailure::Ais31Poker);
+        }
+        for runs in &self.runs {
+            for (&count, &(min, max)) in runs.iter().zip(AIS31_RUN_BOUNDS.iter()) {
+                if !(min..=max).contains(&count) {
+                    return Err(HealthTestFailure::Ais31Runs);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Running state of the repetition count and adaptive proportion tests over a
+/// stream of byte samples.
+#[derive(Debug)]
+pub(crate) struct HealthTests {
+    config: HealthTestConfig,
+    rct_value: Option<u8>,
+    rct_count: u32,
+    apt_value: u8,
+    apt_count: u32,
+    apt_seen: u32,
+    ais31: Option<Ais31Tests>,
+}
+
+impl HealthTests {
+    pub(crate) fn new(config: HealthTestConfig) -> Self {
+        Self {
+            config,
+            rct_value: None,
+            rct_count: 0,
+            apt_value: 0,
+            apt_count: 0,
+            apt_seen: 0,
+            ais31: config.ais31.then(Ais31Tests::default),
+        }
+    }
+
+    /// Number of samples to test at startup before any output of a source is
+    /// relied upon: 1024 as required by NIST SP 800-90B, section 4.3, or a
+    /// full block of the AIS 31 tests if they are enabled.
+    pub(crate) fn startup_samples(&self) -> usize {
+        match self.ais31 {
+            Some(_) => (AIS31_BLOCK_BITS / 8) as usize,
+            None => 1024,
+        }
+    }
+
+    /// Feed a single sample through both tests.
+    fn feed(&mut self, sample: u8) -> Result<(), HealthTestFailure> {
+        if self.rct_value == Some(sample) {
+            self.rct_count += 1;
+            if self.rct_count >= self.config.rct_cutoff {
+                return Err(HealthTestFailure::RepetitionCount);
+            }
+        } else {
+            self.rct_value = Some(sample);
+            self.rct_count = 1;
+        }
+
+        if self.apt_seen == 0 {
+            self.apt_value = sample;
+            self.apt_count = 1;
+        } else if sample == self.apt_value {
+            self.apt_count += 1;
+            if self.apt_count >= self.config.apt_cutoff {
+                return Err(HealthTestFailure::AdaptiveProportion);
+            }
+        }
+        self.apt_seen = (self.apt_seen + 1) % APT_WINDOW;
+
+        if let Some(ais31) = &mut self.ais31 {
+            ais31.feed(sample)?;
+        }
+        Ok(())
+    }
+
+    /// Feed all bytes of `samples` through both tests.
+    pub(crate) fn feed_all(&mut self, samples: &[u8]) -> Result<(), HealthTestFailure> {
+        samples.iter().try_for_each(|&s| self.feed(s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HealthTestConfig, HealthTestFailure, HealthTests, MIN_ALPHA_LOG2};
+
+    #[test]
+    fn cutoffs_match_sp800_90b_table() {
+        // SP 800-90B, table 2 (W = 512, alpha = 2^-20)
+        for (h, cutoff) in [(1, 311), (2, 177), (4, 62), (8, 13)] {
+            assert_eq!(HealthTestConfig::for_min_entropy(h).apt_cutoff(), cutoff);
+        }
+        assert_eq!(HealthTestConfig::for_min_entropy(8).rct_cutoff(), 4);
+    }
+
+    #[test]
+    fn cutoffs_for_smallest_alpha() {
+        let config = HealthTestConfig::for_min_entropy_and_alpha(8, MIN_ALPHA_LOG2);
+        assert_eq!(config.rct_cutoff(), 6);
+        assert_eq!(config.apt_cutoff(), 19);
+    }
+
+    #[test]
+    fn stuck_source_fails_rct() {
+        let mut tests = HealthTests::new(HealthTestConfig::default());
+        assert_eq!(
+            tests.feed_all(&[7; 64]),
+            Err(HealthTestFailure::RepetitionCount)
+        );
+    }
+
+    #[test]
+    fn biased_source_fails_apt() {
+        let mut tests = HealthTests::new(HealthTestConfig::default());
+        let samples: [u8; 512] = core::array::from_fn(|i| u8::from(i % 3 == 1));
+        assert_eq!(
+            tests.feed_all(&samples),
+            Err(HealthTestFailure::AdaptiveProportion)
+        );
+    }
+
+    #[test]
+    fn uniform_source_passes() {
+        let mut rng = crate::RngState::new_unseeded();
+        rng.seed(b"health tests");
+        let mut tests = HealthTests::new(HealthTestConfig::for_min_entropy(8));
+        for _ in 0..8 {
+            assert_eq!(tests.feed_all(&rng.get_random_bytes::<512>()), Ok(()));
+        }
+    }
+
+    #[test]
+    fn ais31_uniform_source_passes() {
+        let mut rng = crate::RngState::new_unseeded();
+        rng.seed(b"ais31 tests");
+        let config = HealthTestConfig::for_min_entropy(8).with_ais31_online_tests();
+        let mut tests = HealthTests::new(config);
+        for _ in 0..4 {
+            assert_eq!(tests.feed_all(&rng.get_random_bytes::<2500>()), Ok(()));
+        }
+    }
+
+    #[test]
+    fn ais31_biased_source_fails_monobit() {
+        let config = HealthTestConfig::default().with_ais31_online_tests();
+        let mut tests = HealthTests::new(config);
+        let samples: [u8; 2500] = core::array::from_fn(|i| [0xfe, 0xf7][i % 2]);
+        assert_eq!(
+            tests.feed_all(&samples),
+            Err(HealthTestFailure::Ais31Monobit)
+        );
+    }
+}
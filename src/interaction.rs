@@ -0,0 +1,166 @@
+use std::time::Instant;
+
+use crate::{EntropySource, EntropySourceError, RngState};
+
+/// Domain separation label of the event pool.
+const INTERACTION_LABEL: &[u8] = b"fast-erasure-shake-rng human interaction";
+
+/// Maximum entropy credited to a single event, in bits. Event timestamps of
+/// GUI toolkits are coarse and partly predictable, so this is conservative.
+const MAX_CREDIT_PER_EVENT: u64 = 2;
+
+/// Tags distinguishing the event kinds in the pool.
+const KEY_EVENT: u8 = 0;
+const POINTER_EVENT: u8 = 1;
+
+/// Collector of keyboard and mouse events supplied by the host application,
+/// with entropy accounting, for GUI key generation ceremonies ("move your
+/// mouse until the progress bar is full").
+///
+/// Every event is hashed into a pool together with its high resolution
+/// arrival time. Each event is credited with at most 2 bits, estimated from
+/// the first, second and third order differences of the arrival times (like
+/// the Linux input entropy estimator); repeated events, like held down keys
+/// or a pointer which didn't move, are not credited. Use
+/// [`Self::credited_entropy`] to drive a progress bar, and the collector as an
+/// [`EntropySource`] once it is high enough. Output drains the pool: the
+/// credit is reset to zero.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::{InteractionCollector, ManagedRng};
+///
+/// let mut collector = InteractionCollector::new();
+/// // in the event handlers of the GUI:
+/// # for i in 0..1000 {
+/// # let (x, y) = (i * 7 % 640, i * 13 % 480);
+/// # std::thread::sleep(std::time::Duration::from_micros(i as u64 % 50 * 10));
+/// collector.record_pointer(x, y);
+/// # }
+/// // update the progress bar, until:
+/// if collector.credited_entropy() >= 256 {
+///     let mut rng = ManagedRng::builder().build().unwrap();
+///     rng.reseed_from(&mut collector).unwrap();
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub struct InteractionCollector {
+    pool: RngState,
+    start: Instant,
+    /// Previous arrival time and first and second order time differences, in
+    /// microseconds.
+    last_time: i128,
+    last_delta: i128,
+    last_delta2: i128,
+    /// Previous event, to detect repetitions.
+    last_event: Option<(u8, i32, i32)>,
+    credited_bits: u64,
+}
+
+impl InteractionCollector {
+    /// Create an empty collector.
+    pub fn new() -> Self {
+        let mut pool = RngState::new_unseeded();
+        pool.seed(INTERACTION_LABEL);
+        Self {
+            pool,
+            start: Instant::now(),
+            last_time: 0,
+            last_delta: 0,
+            last_delta2: 0,
+            last_event: None,
+            credited_bits: 0,
+        }
+    }
+
+    /// Record a key press of `key`, in any toolkit specific encoding (key
+    /// code, scan code or character).
+    pub fn record_key(&mut self, key: u32) {
+        self.record(KEY_EVENT, i32::from_ne_bytes(key.to_ne_bytes()), 0);
+    }
+
+    /// Record a pointer (mouse, touch) event at coordinates `x`, `y`.
+    pub fn record_pointer(&mut self, x: i32, y: i32) {
+        self.record(POINTER_EVENT, x, y);
+    }
+
+    fn record(&mut self, kind: u8, a: i32, b: i32) {
+        let nanos = self.start.elapsed().as_nanos();
+        self.pool.absorb(&(kind, a, b, nanos));
+        let credit = self.estimate(i128::try_from(nanos / 1000).unwrap_or(i128::MAX));
+        if self.last_event != Some((kind, a, b)) {
+            self.credited_bits = self.credited_bits.saturating_add(credit);
+        }
+        self.last_event = Some((kind, a, b));
+    }
+
+    /// Update the time differences with the arrival time `time` and return
+    /// the entropy estimate of the event.
+    fn estimate(&mut self, time: i128) -> u64 {
+        let delta = time - self.last_time;
+        let delta2 = delta - self.last_delta;
+        let delta3 = delta2 - self.last_delta2;
+        (self.last_time, self.last_delta, self.last_delta2) = (time, delta, delta2);
+        let min = delta.abs().min(delta2.abs()).min(delta3.abs());
+        // floor(log2(min)), or 0 if `min` is 0 or 1
+        let bits = u64::from(min.max(1).ilog2());
+        bits.min(MAX_CREDIT_PER_EVENT)
+    }
+
+    /// Entropy credited to the events collected since the last output, in
+    /// bits.
+    pub fn credited_entropy(&self) -> u64 {
+        self.credited_bits
+    }
+}
+
+impl Default for InteractionCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EntropySource for InteractionCollector {
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+        self.pool.fill_random_bytes(dest);
+        self.credited_bits = 0;
+        Ok(())
+    }
+
+    fn label(&self) -> &str {
+        "human-interaction"
+    }
+
+    fn entropy_credit(&self, len: usize) -> u64 {
+        core::cmp::min(self.credited_bits, 8 * len as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InteractionCollector;
+    use crate::EntropySource;
+
+    #[test]
+    fn accounting() {
+        let mut collector = InteractionCollector::new();
+        assert_eq!(collector.estimate(1000), 2);
+        assert_eq!(collector.estimate(2000), 0);
+        assert_eq!(collector.estimate(3005), 2);
+        assert_eq!(collector.entropy_credit(64), 0);
+        collector.credited_bits = 600;
+        assert_eq!(collector.entropy_credit(64), 512);
+        collector.fill_entropy(&mut [0; 64]).unwrap();
+        assert_eq!(collector.credited_entropy(), 0);
+    }
+
+    #[test]
+    fn repeated_events_are_not_credited() {
+        let mut collector = InteractionCollector::new();
+        for _ in 0..100 {
+            std::thread::sleep(std::time::Duration::from_micros(300));
+            collector.record_key(u32::from('a'));
+        }
+        assert!(collector.credited_entropy() <= 2);
+    }
+}
@@ -20,6 +20,8 @@ pub(crate) struct InternalState {
 impl InternalState {
     /// Apply keccak-f\[1600\] to the state.
     pub(crate) fn apply_f(&mut self) {
+        #[cfg(feature = "profiling")]
+        crate::profiling::count_permutation();
         keccak_f1600(&mut self.state);
     }
 
@@ -50,6 +52,23 @@ impl InternalState {
         u64_slice_as_ne_bytes(&self.state[..RATE_LANES + CAPACITY_LANES])
     }
 
+    /// Get the "rate area" plus the "zeroized capacity area" of the state as
+    /// lanes.
+    pub(crate) fn get_rate_zeroized_capacity_lanes(&self) -> &[u64] {
+        &self.state[..RATE_LANES + CAPACITY_LANES]
+    }
+
+    /// Zeroize the full state.
+    pub(crate) fn zeroize(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.state);
+    }
+
+    /// Create an exact copy of the state.
+    #[cfg(feature = "hazmat")]
+    pub(crate) fn duplicate(&self) -> Self {
+        Self { state: self.state }
+    }
+
     /// Create a new empty state.
     pub(crate) fn new() -> Self {
         Self { state: [0; LANES] }
@@ -0,0 +1,119 @@
+use std::fs::OpenOptions;
+use std::io;
+use std::os::unix::io::AsRawFd;
+
+use zeroize::Zeroize;
+
+/// Number of bytes passed to the kernel per ioctl.
+const CHUNK_LEN: usize = 256;
+
+/// `_IOW('R', 0x03, int[2])`, the number of the `RNDADDENTROPY` ioctl.
+#[cfg(any(
+    target_arch = "mips",
+    target_arch = "mips64",
+    target_arch = "powerpc",
+    target_arch = "powerpc64",
+    target_arch = "sparc",
+    target_arch = "sparc64"
+))]
+const RNDADDENTROPY: u32 = 0x8008_5203;
+/// `_IOW('R', 0x03, int[2])`, the number of the `RNDADDENTROPY` ioctl.
+#[cfg(not(any(
+    target_arch = "mips",
+    target_arch = "mips64",
+    target_arch = "powerpc",
+    target_arch = "powerpc64",
+    target_arch = "sparc",
+    target_arch = "sparc64"
+)))]
+const RNDADDENTROPY: u32 = 0x4008_5203;
+
+/// The kernel's `struct rand_pool_info` with a fixed size buffer.
+#[repr(C)]
+struct RandPoolInfo {
+    entropy_count: libc::c_int,
+    buf_size: libc::c_int,
+    buf: [u8; CHUNK_LEN],
+}
+
+/// Contribute `data` to the Linux kernel's entropy pool using the
+/// `RNDADDENTROPY` ioctl on `/dev/random`, crediting it with `entropy_bits`
+/// bits of entropy. For appliance builders using this crate as userspace
+/// entropy daemon, e.g. feeding conditioned output of a [`ManagedRng`]
+/// seeded from a jitter or hardware TRNG source.
+///
+/// Requires `CAP_SYS_ADMIN`. Only credit entropy which was actually
+/// collected and credited by this crate: over-crediting weakens the kernel
+/// RNG for the whole system. `entropy_bits` is capped at `8 * data.len()`.
+///
+/// # Errors
+/// Returns an error if `/dev/random` can't be opened or the ioctl fails,
+/// e.g. with [`io::ErrorKind::PermissionDenied`] without `CAP_SYS_ADMIN`.
+///
+/// # Examples
+/// ```no_run
+/// use fast_erasure_shake_rng::{add_kernel_entropy, ManagedRng, SecretBytes};
+///
+/// # let mut trng = fast_erasure_shake_rng::OsEntropy;
+/// let mut rng = ManagedRng::builder()
+///     .getrandom(false)
+///     .entropy_source(&mut trng)
+///     .require_entropy(256)
+///     .build()?;
+/// let conditioned: SecretBytes<32> = rng.try_get_secret_bytes()?;
+/// add_kernel_entropy(conditioned.expose_secret(), 256)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// [`ManagedRng`]: crate::ManagedRng
+#[cfg_attr(
+    docsrs,
+    doc(cfg(all(feature = "kernel-feedback", target_os = "linux")))
+)]
+pub fn add_kernel_entropy(data: &[u8], entropy_bits: u64) -> io::Result<()> {
+    let random = OpenOptions::new().write(true).open("/dev/random")?;
+    let mut remaining_bits = core::cmp::min(entropy_bits, 8 * data.len() as u64);
+    let mut info = RandPoolInfo {
+        entropy_count: 0,
+        buf_size: 0,
+        buf: [0; CHUNK_LEN],
+    };
+    let mut res = Ok(());
+    for chunk in data.chunks(CHUNK_LEN) {
+        let chunk_bits = core::cmp::min(remaining_bits, 8 * chunk.len() as u64);
+        remaining_bits -= chunk_bits;
+        // both are at most `8 * CHUNK_LEN`, so they fit
+        info.entropy_count = libc::c_int::try_from(chunk_bits).unwrap();
+        info.buf_size = libc::c_int::try_from(chunk.len()).unwrap();
+        info.buf[..chunk.len()].copy_from_slice(chunk);
+        // the ioctl number is defined as unsigned, but `Ioctl` is signed on
+        // some C libraries
+        #[allow(clippy::cast_possible_wrap, clippy::useless_conversion)]
+        let request = RNDADDENTROPY as libc::Ioctl;
+        // SAFETY: `info` is a valid `struct rand_pool_info` with `buf_size`
+        // initialized bytes in `buf`
+        let ret = unsafe { libc::ioctl(random.as_raw_fd(), request, &info as *const RandPoolInfo) };
+        if ret != 0 {
+            res = Err(io::Error::last_os_error());
+            break;
+        }
+    }
+    info.buf.zeroize();
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::add_kernel_entropy;
+
+    #[test]
+    fn unprivileged_feedback_fails_cleanly() {
+        // SAFETY: `geteuid` has no preconditions
+        if unsafe { libc::geteuid() } == 0 {
+            // as root the feedback would really be mixed into the host's pool
+            return;
+        }
+        let err = add_kernel_entropy(&[0x5a; 300], 0).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+    }
+}
@@ -13,17 +13,23 @@
 //! The RNG can always be reseeded (if you want backward security) using
 //! [`RngState::seed_with_getrandom`].
 //!
+//! To configure personalization, additional entropy sources, health tests or
+//! the output policy, build a [`ManagedRng`] using an [`RngBuilder`].
+//!
 //! # Examples
 //! Basic usage:
 //! ```
+//! # #[cfg(feature = "getrandom")] {
 //! use fast_erasure_shake_rng::RngState;
 //!
 //! let mut rng = RngState::new_from_getrandom().unwrap();
 //! let key = rng.get_random_bytes::<32>();
+//! # }
 //! ```
 //!
 //! Reseeding for backward security:
 //! ```
+//! # #[cfg(feature = "getrandom")] {
 //! use fast_erasure_shake_rng::RngState;
 //!
 //! let mut rng = RngState::new_from_getrandom().unwrap();
@@ -32,10 +38,12 @@
 //! // later:
 //! rng.seed_with_getrandom().unwrap();
 //! let key2 = rng.get_random_bytes::<1000>();
+//! # }
 //! ```
 //!
 //! Hashing in additional data, to reduce reliance on the OS RNG:
 //! ```
+//! # #[cfg(feature = "getrandom")] {
 //! use fast_erasure_shake_rng::RngState;
 //!
 //! let mut rng = RngState::new_from_getrandom().unwrap();
@@ -46,6 +54,7 @@
 //! # }
 //!
 //! let key = rng.get_random_bytes::<32>();
+//! # }
 //! ```
 //!
 //! # Determinism & Portability
@@ -67,6 +76,79 @@
 //!   [`RngState::new_from_getrandom`].
 //! - `rand-core`: Enable dependency on the [`rand_core` crate]. This enables
 //!   implementations of the Rng traits from `rand_core` for [`RngState`].
+//! - `derive`: Enable `#[derive(FromRandomBytes)]` for structs whose fields all
+//!   implement [`FromRandomBytes`].
+//! - `alloc`: Enable APIs which need heap allocation, like
+//!   [`RngState::random_permutation`].
+//! - `std`: Enable all APIs which need the standard library or an operating
+//!   system, like [`RngState::reservoir_sample`]. Implies `alloc`. Without it
+//!   the crate is `no_std`; with it you additionally get:
+//!   - `std::error::Error` implementations for all error types,
+//!   - `std::io` adapters: `std::io::Read` for [`RngState`], `std::io::Write`
+//!     for [`Absorber`] and `RngState::seed_from_reader`,
+//!   - OS dependent functionality: thread-local routing in [`ShardedRng`],
+//!     `StdClock` for time-based reseeding, `block_pipeline` with a producer
+//!     thread, `RngState::random_datetime_in`, and a blocking lock with fork
+//!     and reentrancy detection in `with_global_rng`.
+//!
+//!   Features needing files or the OS, like `seed-file` and
+//!   `kernel-feedback`, imply `std`.
+//! - `primes`: Enable generation of random (safe) primes, e.g.
+//!   [`RngState::random_prime`].
+//! - `audit`: Enable recording a transcript of all seeding events of a
+//!   [`ManagedRng`], see [`RngBuilder::audit_transcript`]. Implies `alloc`.
+//! - `beacon`: Enable verifiable draws with published commitments, see
+//!   [`RngState::commit_draw`].
+//! - `replay`: NOT FOR PRODUCTION USE. Enable capturing all entropy of a
+//!   [`ManagedRng`] to an in-memory log to reconstruct an identical RNG while
+//!   debugging, see `ReplayLog`. Implies `alloc`.
+//! - `seed-file`: Enable `SeedFile`, a persistent seed file carrying entropy
+//!   across restarts to improve early boot seeding. Implies `std`.
+//! - `seed-storage`: Enable `PersistentSeed`, the `no_std` counterpart of
+//!   `seed-file`, carrying entropy across reboots in a `SeedStorage` like flash
+//!   or EEPROM.
+//! - `kernel-feedback`: Enable `add_kernel_entropy` on Linux, contributing
+//!   entropy collected by this crate to the kernel's entropy pool. Implies
+//!   `std`.
+//! - `ct-verify`: Enable the `ct_verify` module with hooks for dudect-style
+//!   verification that the output path runs in constant time.
+//! - `hazmat`: HAZARDOUS. Enable the `hazmat` module with a low-level duplex
+//!   interface over the Keccak state, for building protocol constructions, and
+//!   exact duplication of an [`RngState`].
+//! - `dp`: Enable exact samplers of the discrete Laplace and discrete Gaussian
+//!   distributions for differential privacy, see `RngState::discrete_laplace`.
+//! - `rtos`: Enable the `rtos` module with C-callable functions exposing a
+//!   system RNG to RTOS based firmware, e.g. as Zephyr entropy driver.
+//! - `deny-weak-seeding`: Enable `StrongSeededRng`, an RNG which doesn't
+//!   implement `rand_core::SeedableRng`, so security-sensitive projects can
+//!   rule out seeding it with `seed_from_u64`, the only entry point taking an
+//!   8-byte seed, at compile time. Implies `rand-core`.
+//! - `audio`: Enable `AudioNoise`, an entropy source reading captured
+//!   microphone or line-in noise, e.g. for air-gapped key generation machines.
+//!   Implies `std`.
+//! - `uefi`: Enable `UefiRng`, an entropy source for bootloaders and other
+//!   pre-OS UEFI applications using the firmware's `EFI_RNG_PROTOCOL`, with
+//!   RDSEED as fallback on x86.
+//! - `wasi`: Enable `WasiRandom`, a provider of the `wasi:random` interfaces of
+//!   the WASI component model, for Wasm plugin hosts exposing this RNG to
+//!   guests. Implies `alloc`.
+//! - `profiling`: Attribute the output requested from the RNG and the
+//!   permutations consumed to call sites, see `profiling_report`. Costs a
+//!   global lock per request; not for production builds. Implies `std`.
+//! - `test-util`: Enable `MockRng`, a [`SecureRng`] with scripted output for
+//!   deterministic unit tests, `TestSeed`, a printable seed for reproducible
+//!   randomized tests, and `RngState::fixture`, a deterministic RNG per test.
+//! - `simulation`: NEVER ENABLE OUTSIDE OF TESTS. Enable `enable_simulation`,
+//!   an opt-in mode deriving all entropy from the OS RNG in the whole process
+//!   from one logged master `TestSeed`, for replayable deterministic simulation
+//!   testing. Refuses to compile without debug assertions. Implies `test-util`,
+//!   `getrandom` and `std`.
+//! - `test-seed-override`: NEVER ENABLE OUTSIDE OF TESTS. If the environment
+//!   variable `FAST_ERASURE_SHAKE_RNG_TEST_SEED` holds a `TestSeed`, all
+//!   entropy from the OS RNG is instead derived from it (announced on stderr),
+//!   so randomized test failures in CI can be reproduced exactly. Refuses to
+//!   compile without debug assertions, i.e. in release builds. Implies
+//!   `test-util`, `getrandom` and `std`.
 //!
 //! # RNGs and Cryptography Notes
 //! ## Attacker controlled entropy sources
@@ -115,24 +197,24 @@
 //! 64 bits each. We divide it into three "areas":
 //!
 //! 1. An area we call the "rate area", consisting of the top 9 lanes, therefore
-//! sized 576 bits.
+//!    sized 576 bits.
 //! 2. An area we call the "zeroized capacity area", consisting of the next 8
-//! lanes, therefore sized 512 bits.
+//!    lanes, therefore sized 512 bits.
 //! 3. An area we call the "capacity area", consisting of the last 8 lanes,
-//! therefore sized 512 bits.
+//!    therefore sized 512 bits.
 //!
 //! We now define three basic actions on this state, which will serve as the
 //! building blocks for all other (user facing) operations:
 //!
 //! 1. A basic action we call "input". First xor 576 bits of input data into the
-//! "rate area" of the state. Then apply keccak-f to the state.
+//!    "rate area" of the state. Then apply keccak-f to the state.
 //! 2. A basic action we call "initial-output". First output the bytes in the
-//! "rate area" as random output bytes. Then apply keccak-f.
+//!    "rate area" as random output bytes. Then apply keccak-f.
 //! 3. A basic action we call "intermediate-output". First output the bytes in
-//! the "rate area" and the "zeroized capacity area" as random output bytes.
-//! Then apply keccak-f to the state.
+//!    the "rate area" and the "zeroized capacity area" as random output bytes.
+//!    Then apply keccak-f to the state.
 //! 4. A basic action we call "make-forward-secure". Zeroize (i.e., fill with
-//! zero/null bytes) the "zeroized capacity area".
+//!    zero/null bytes) the "zeroized capacity area".
 //!
 //! The first basic actions is used to absorb entropy from inputs into the
 //! state. The next two are used squeeze output from the state. The action
@@ -220,13 +302,18 @@
 //!
 //! [`getrandom` crate]: https://crates.io/crates/getrandom
 //! [`rand_core` crate]: https://crates.io/crates/rand_core
-#![cfg_attr(doc, feature(doc_cfg))]
+#![cfg_attr(docsrs, feature(doc_cfg))]
 #![no_std]
 #![forbid(rust_2018_compatibility, unsafe_op_in_unsafe_fn)]
 #![deny(future_incompatible, rust_2018_idioms)]
 #![warn(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
 #![allow(clippy::needless_lifetimes)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 const LANES: usize = 25;
 const BITS: usize = 1600; // LANES * 2^L
 const CAPACITY_BITS: usize = 512;
@@ -253,12 +340,379 @@ fn u64_slice_as_ne_bytes_mut<'a>(slice: &'a mut [u64]) -> &'a mut [u8] {
     unsafe { core::slice::from_raw_parts_mut(slice.as_mut_ptr().cast(), len) }
 }
 
+/// Convert a slice of `u128`s into a slice of bytes (`u8`s). Result depends on
+/// endianness.
+fn u128_slice_as_ne_bytes_mut<'a>(slice: &'a mut [u128]) -> &'a mut [u8] {
+    let len: usize = core::mem::size_of_val::<[u128]>(slice);
+    unsafe { core::slice::from_raw_parts_mut(slice.as_mut_ptr().cast(), len) }
+}
+
+/// Compare two byte slices in constant time: there is no early exit on the
+/// first differing byte. Only the lengths are compared in variable time.
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a
+        .iter()
+        .zip(b.iter())
+        .fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    core::hint::black_box(diff) == 0
+}
+
+/// Fill `dest` with entropy from the OS RNG, or from a master test seed if a
+/// simulation is active (`simulation` feature) or the `test-seed-override`
+/// feature is enabled and the override is set.
+#[cfg(feature = "getrandom")]
+fn getrandom_or_override(dest: &mut [u8]) -> Result<(), getrandom::Error> {
+    #[cfg(feature = "simulation")]
+    if simulation::fill(dest) {
+        return Ok(());
+    }
+    #[cfg(feature = "test-seed-override")]
+    if seed_override::fill(dest) {
+        return Ok(());
+    }
+    getrandom::getrandom(dest)
+}
+
+/// Block until the OS RNG is initialized, on the platforms where the
+/// `getrandom` crate doesn't already wait for it: on NetBSD it uses the
+/// non-blocking `kern.arandom` sysctl, so wait by reading from `/dev/random`.
+/// On Linux, Android, the BSDs other than NetBSD, macOS and Windows the OS
+/// interface used by `getrandom` itself blocks (or never lacks entropy).
+#[cfg(all(feature = "getrandom", feature = "std"))]
+fn wait_for_os_entropy() -> Result<(), getrandom::Error> {
+    #[cfg(target_os = "netbsd")]
+    {
+        use std::io::Read;
+
+        let mut byte = zeroize::Zeroizing::new([0u8; 1]);
+        std::fs::File::open("/dev/random")
+            .and_then(|mut random| random.read_exact(byte.as_mut()))
+            .map_err(|e| {
+                e.raw_os_error()
+                    .and_then(|code| u32::try_from(code).ok())
+                    .and_then(core::num::NonZeroU32::new)
+                    .map_or(getrandom::Error::UNEXPECTED, getrandom::Error::from)
+            })?;
+    }
+    Ok(())
+}
+
 /// Module constaining a structure for the keccak state and functions to operate
 /// on it in terms of the three "areas" as defined in the top level
 /// documentation.
 mod internal_state;
 use internal_state::InternalState;
 
+/// Module containing methods to sample uniformly distributed values, like
+/// integers below a bound and Unicode scalar values.
+mod uniform;
+
+/// Module containing methods to sample random subsets, permutations and
+/// samples of collections.
+mod sampling;
+
+/// Module containing the Padmé padding scheme and random padding lengths.
+mod padding;
+pub use padding::{padme_length, Padding};
+
+/// Module containing random durations, times and jitter for backoff and
+/// scheduling.
+mod time;
+
+/// Module containing randomized network identifiers, like MAC addresses and
+/// temporary IPv6 addresses.
+mod net_ids;
+
+/// Module containing random (version 4) and time-ordered (version 7) UUIDs.
+mod uuid;
+
+/// Module containing the measurement of the RNG's performance on the current
+/// machine.
+#[cfg(feature = "std")]
+mod calibrate;
+#[cfg(feature = "std")]
+pub use calibrate::{calibrate, CalibrationReport};
+
+/// Module containing an implementation of the SHAKE256 XOF, for derivations
+/// which must be portable.
+mod xof;
+
+/// Module containing the sampling of random non-zero integers.
+mod nonzero;
+pub use nonzero::NonZeroInteger;
+
+/// Module containing the [`FromRandomBytes`] trait for types of which random
+/// values can be generated.
+mod from_random_bytes;
+/// Derive macro for the [`FromRandomBytes`] trait.
+#[cfg(feature = "derive")]
+#[cfg_attr(docsrs, doc(cfg(feature = "derive")))]
+pub use fast_erasure_shake_rng_derive::FromRandomBytes;
+pub use from_random_bytes::FromRandomBytes;
+
+/// Module containing constant-time rejection sampling of integers below a
+/// modulus.
+mod modular;
+
+/// Module containing probable-prime testing and generation.
+#[cfg(feature = "primes")]
+mod primes;
+
+/// Module containing the [`EntropySource`] trait for sources of seeding
+/// material.
+mod entropy;
+#[cfg(feature = "getrandom")]
+pub use entropy::OsEntropy;
+pub use entropy::{AsyncEntropySource, EntropySource, EntropySourceError};
+
+/// Module containing conditioning of raw noise sources: a von Neumann
+/// debiaser and a hash conditioner with entropy accounting.
+mod conditioning;
+pub use conditioning::{HashConditioner, VonNeumann};
+
+/// Module containing the ADC noise entropy source for MCUs without a TRNG.
+mod adc;
+pub use adc::AdcNoise;
+
+/// Module containing the continuous health tests of NIST SP 800-90B for raw
+/// entropy sources.
+mod health;
+pub use health::{HealthTestConfig, HealthTestFailure};
+
+/// Module containing the error type of the managed RNG.
+mod error;
+pub use error::RngError;
+
+/// Module containing the transcript of seeding events of a managed RNG.
+#[cfg(feature = "audit")]
+mod audit;
+#[cfg(feature = "audit")]
+pub use audit::{SeedEvent, SeedTranscript, SEED_EVENT_HASH_LEN};
+
+/// Module containing the capture and replay of entropy for debugging.
+#[cfg(feature = "replay")]
+mod replay;
+#[cfg(feature = "replay")]
+pub use replay::ReplayLog;
+
+/// Module containing the events of a managed RNG reported to an observer.
+mod observe;
+pub use observe::{RngEvent, RngObserver};
+
+/// Module containing counters persisted across boots, absorbed at
+/// construction.
+mod persistent_counter;
+pub use persistent_counter::PersistentCounter;
+
+/// Module containing the builder for a managed RNG.
+mod builder;
+pub use builder::{
+    DegradedOutput, FailurePolicy, OutputPolicy, RngBuilder, APPROVED_MIN_ENTROPY,
+    MAX_ENTROPY_SOURCES, NIST_MAX_REQUEST_BYTES,
+};
+
+/// Module containing the managed RNG, an [`RngState`] together with its
+/// configured policies.
+mod managed;
+pub use managed::ManagedRng;
+
+/// Module containing the power analysis countermeasures of a [`ManagedRng`].
+mod hardening;
+
+/// Module containing the machine-readable status report of a [`ManagedRng`].
+mod status;
+pub use status::RngStatus;
+
+/// Module containing a managed RNG with named sub-streams.
+#[cfg(feature = "alloc")]
+mod multi_stream;
+#[cfg(feature = "alloc")]
+pub use multi_stream::MultiStreamRng;
+
+/// Module containing the lock-free producer/consumer block pipeline.
+#[cfg(feature = "std")]
+mod pipeline;
+#[cfg(feature = "std")]
+pub use pipeline::{block_pipeline, BlockConsumer, BlockProducer};
+
+/// Module containing the pool of reusable zeroizing buffers for large fills.
+#[cfg(feature = "alloc")]
+mod buffer_pool;
+#[cfg(feature = "alloc")]
+pub use buffer_pool::{BufferPool, PooledBuffer};
+
+/// Module containing a scope guard which reseeds the RNG on drop.
+mod reseed_guard;
+pub use reseed_guard::ReseedGuard;
+
+/// Module containing an RNG wrapper protected by a spin lock, for sharing
+/// between threads or cores without the standard library.
+mod sync;
+#[cfg(target_has_atomic = "8")]
+pub use sync::AtomicSpinLock;
+pub use sync::{RawSpinLock, SyncRngState};
+
+/// Module containing a pool of independently locked RNG shards.
+#[cfg(target_has_atomic = "ptr")]
+mod sharded;
+#[cfg(target_has_atomic = "ptr")]
+pub use sharded::ShardedRng;
+
+/// Module containing monotonic clocks for time-based reseeding.
+mod clock;
+pub use clock::MonotonicClock;
+#[cfg(feature = "std")]
+pub use clock::StdClock;
+
+/// Module containing RNG handles with an output budget for untrusted code.
+#[cfg(target_has_atomic = "ptr")]
+mod quota;
+#[cfg(target_has_atomic = "ptr")]
+pub use quota::{QuotaError, QuotaRng};
+
+/// Module containing commit/reveal draws for verifiable randomness.
+#[cfg(feature = "beacon")]
+mod beacon;
+#[cfg(feature = "beacon")]
+pub use beacon::{DrawCommitment, DrawReveal, DRAW_VALUE_LEN};
+
+/// Module containing the lazily seeded global RNG instance.
+#[cfg(all(feature = "getrandom", target_has_atomic = "8"))]
+mod global;
+#[cfg(all(feature = "getrandom", target_has_atomic = "8"))]
+pub use global::{with_global_rng, FastErasureRng};
+
+/// Module containing the object safe [`SecureRng`] trait.
+mod secure_rng;
+pub use secure_rng::SecureRng;
+
+/// Module containing the [`AbsorbInto`] trait for hashing structured data into
+/// the state.
+mod absorb;
+pub use absorb::{AbsorbInto, Absorber};
+
+/// Module containing the zeroizing [`SecretBytes`] output type.
+mod secret_bytes;
+pub use secret_bytes::SecretBytes;
+
+/// Module containing deterministic and hedged nonces for signature schemes.
+mod nonce;
+pub use nonce::deterministic_nonce;
+
+/// Module containing random keys for keyed hash functions, hardening hash maps
+/// against HashDoS.
+mod hash_keys;
+pub use hash_keys::{KeyedHasher, RandomState};
+
+/// Module containing exact samplers of differential privacy noise.
+#[cfg(feature = "dp")]
+mod dp;
+
+/// Module containing keyed pseudorandom permutations of small domains, for
+/// unique non-sequential IDs.
+mod prp;
+pub use prp::{IndexPermutation, PermutedIndices};
+
+/// Module containing persistent seed files carrying entropy across restarts.
+#[cfg(feature = "seed-file")]
+mod seed_file;
+#[cfg(feature = "seed-file")]
+pub use seed_file::{SeedFile, SEED_FILE_LEN};
+
+/// Module containing persistent seeds in non-volatile storage for `no_std`
+/// devices.
+#[cfg(feature = "seed-storage")]
+mod seed_storage;
+#[cfg(feature = "seed-storage")]
+pub use seed_storage::{PersistentSeed, SeedStorage, SEED_RECORD_LEN};
+
+/// Module containing the feedback of collected entropy to the Linux kernel.
+#[cfg(all(feature = "kernel-feedback", target_os = "linux"))]
+mod kernel_feedback;
+#[cfg(all(feature = "kernel-feedback", target_os = "linux"))]
+pub use kernel_feedback::add_kernel_entropy;
+
+/// Module containing a mock RNG with scripted output for tests.
+#[cfg(feature = "test-util")]
+mod mock;
+#[cfg(feature = "test-util")]
+pub use mock::MockRng;
+
+/// Module containing printable seeds for reproducible randomized tests and
+/// deterministic fixture RNGs.
+#[cfg(feature = "test-util")]
+mod test_seed;
+#[cfg(feature = "test-util")]
+pub use test_seed::{ParseTestSeedError, TestSeed};
+
+pub mod limits;
+
+#[cfg(all(feature = "rtos", target_has_atomic = "8"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "rtos")))]
+pub mod rtos;
+
+#[cfg(feature = "ct-verify")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ct-verify")))]
+pub mod ct_verify;
+
+#[cfg(feature = "hazmat")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hazmat")))]
+pub mod hazmat;
+
+/// Module containing the override of the OS RNG by a master test seed.
+#[cfg(feature = "test-seed-override")]
+mod seed_override;
+#[cfg(feature = "test-seed-override")]
+pub use seed_override::TEST_SEED_ENV;
+
+/// Module containing the per call site profiling of RNG usage.
+#[cfg(feature = "profiling")]
+mod profiling;
+#[cfg(feature = "profiling")]
+pub use profiling::{profiling_report, reset_profiling, CallSiteUsage};
+
+/// Module containing the collector of human interaction events.
+#[cfg(feature = "std")]
+mod interaction;
+#[cfg(feature = "std")]
+pub use interaction::InteractionCollector;
+
+/// Module containing the microphone/line-in noise entropy source.
+#[cfg(feature = "audio")]
+mod audio;
+#[cfg(feature = "audio")]
+pub use audio::AudioNoise;
+
+/// Module containing the pre-boot entropy source for UEFI applications.
+#[cfg(feature = "uefi")]
+mod uefi;
+#[cfg(feature = "uefi")]
+pub use uefi::{EfiGuid, EfiRngProtocol, UefiRng, EFI_RNG_PROTOCOL_GUID};
+
+/// Module containing the `wasi:random` provider for Wasm component hosts.
+#[cfg(feature = "wasi")]
+mod wasi;
+#[cfg(feature = "wasi")]
+pub use wasi::WasiRandom;
+
+/// Module containing the whole-program deterministic simulation mode.
+#[cfg(feature = "simulation")]
+mod simulation;
+#[cfg(feature = "simulation")]
+pub use simulation::{enable_simulation, simulation_seed, SimulationActiveError};
+
+/// Module containing an RNG which can only be created from a full seed.
+#[cfg(feature = "deny-weak-seeding")]
+mod strong_seeded;
+#[cfg(feature = "deny-weak-seeding")]
+pub use strong_seeded::StrongSeededRng;
+
+#[cfg(feature = "rand-core")]
+pub use crate::rand_core::{FullRateSeed, InvalidSeedLength, Seed};
+
 /// The PRNG this crate is all about. Cryptographically secure fast-erasure
 /// deterministic pseudo-random number generator (PRNG). It is deterministic but
 /// not portable/reproducible.
@@ -275,14 +729,17 @@ use internal_state::InternalState;
 /// # Examples
 /// Basic usage:
 /// ```
+/// # #[cfg(feature = "getrandom")] {
 /// use fast_erasure_shake_rng::RngState;
 ///
 /// let mut rng = RngState::new_from_getrandom().unwrap();
 /// let key = rng.get_random_bytes::<32>();
+/// # }
 /// ```
 ///
 /// Reseeding for backward security:
 /// ```
+/// # #[cfg(feature = "getrandom")] {
 /// use fast_erasure_shake_rng::RngState;
 ///
 /// let mut rng = RngState::new_from_getrandom().unwrap();
@@ -291,6 +748,7 @@ use internal_state::InternalState;
 /// // later:
 /// rng.seed_with_getrandom().unwrap();
 /// let key2 = rng.get_random_bytes::<1000>();
+/// # }
 /// ```
 ///
 /// # Determinism & Portability
@@ -324,18 +782,22 @@ impl RngState {
     // (i.e. the last input chunk) and `absorb_block` handles intermediate full
     // chunks
 
-    /// Absorb a partial block `block` of < `RATE_BYTES` bytes, applying proper
-    /// padding and running the permutation.
+    /// Absorb the final input `block`, applying proper padding and running the
+    /// permutation. Normally `block` is a partial block of < `RATE_BYTES`
+    /// bytes, but any length is accepted: leading full blocks are absorbed
+    /// using [`Self::absorb_block`] and the remainder is padded. In particular
+    /// a `block` of exactly `RATE_BYTES` bytes is followed by a block holding
+    /// only the padding, as the padding rule requires.
     ///
     /// The padding is written directly into the state to avoid copying the
     /// input `block` to a larger buffer.
-    ///
-    /// # Panics
-    /// Panics if the following condition is violated: `block.len() <
-    /// RATE_BYTES`.
     #[inline]
     fn absorb_partial_block_padded(&mut self, block: &[u8]) {
-        assert!(block.len() < RATE_BYTES);
+        let mut blocks = block.chunks_exact(RATE_BYTES);
+        for full_block in &mut blocks {
+            self.absorb_block(full_block.try_into().unwrap());
+        }
+        let block = blocks.remainder();
         let rate_state = self.state.get_rate_bytes_mut();
         for (b, s) in block.iter().zip(rate_state.iter_mut()) {
             *s ^= b;
@@ -394,13 +856,14 @@ impl RngState {
     /// (Re)seed the RNG with data `seed`. `seed` can be of arbitrary length.
     /// With high entropy data, i.e. (almost) uniform random bytes, you need *at
     /// least* 16 bytes of data to properly seed the RNG.
+    ///
+    /// # Empty seeds
+    /// Seeding with an empty `seed` absorbs a block holding only the padding,
+    /// which is distinct from the encoding of every non-empty seed. It mixes
+    /// the state (like [`Self::ratchet`] without the erasure) but adds no
+    /// entropy whatsoever.
     pub fn seed(&mut self, seed: &[u8]) {
-        let mut blocks = seed.chunks_exact(RATE_BYTES);
-        for block in &mut blocks {
-            self.absorb_block(block.try_into().unwrap());
-        }
-        // handle remainder with padding
-        self.absorb_partial_block_padded(blocks.remainder());
+        self.absorb_partial_block_padded(seed);
     }
 
     /// Call the closure `f` with a buffer of 64 bytes, then (re)seed the RNG
@@ -422,12 +885,100 @@ impl RngState {
         Ok(())
     }
 
+    /// Call the closure `f` with a buffer of 64 bytes, then (re)seed the RNG
+    /// using the prefix of the buffer that `f` reports to have written. Returns
+    /// the number of bytes absorbed.
+    ///
+    /// This is like [`Self::seed_with_64`], but suitable for sources which may
+    /// deliver fewer bytes than requested: `f` returns the number of bytes it
+    /// actually wrote and the unwritten remainder of the buffer is not
+    /// absorbed. The buffer will be zeroized so the secret seeding material is
+    /// not left in memory.
+    ///
+    /// # Panics
+    /// Panics if `f` reports to have written more than 64 bytes.
+    ///
+    /// # Examples
+    /// ```
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_unseeded();
+    /// // a source that only has 20 bytes available
+    /// let absorbed = rng
+    ///     .seed_with_64_partial(|buf| {
+    ///         buf[..20].fill(0x42);
+    ///         Ok::<_, ()>(20)
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(absorbed, 20);
+    /// ```
+    pub fn seed_with_64_partial<E, F: FnOnce(&mut [u8; 64]) -> Result<usize, E>>(
+        &mut self,
+        f: F,
+    ) -> Result<usize, E> {
+        let mut buffer = zeroize::Zeroizing::new([0u8; 64]);
+        let written = f(&mut buffer)?;
+        assert!(written <= 64, "closure reported more than 64 bytes written");
+        self.absorb_partial_block_padded(&buffer[..written]);
+        Ok(written)
+    }
+
     /// (Re)seed the RNG with data from the OS RNG (e.g. the `getrandom` syscall
     /// in linux). This should be the preferred method to (re)seed the RNG.
     #[cfg(feature = "getrandom")]
-    #[cfg_attr(doc, doc(cfg(feature = "getrandom")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "getrandom")))]
     pub fn seed_with_getrandom(&mut self) -> Result<(), getrandom::Error> {
-        self.seed_with_64(getrandom::getrandom)
+        self.seed_with_64(getrandom_or_override)
+    }
+
+    /// (Re)seed the RNG with all data read from `reader` until end of file,
+    /// e.g. a seed file, `/dev/hwrng` or piped entropy, without loading it
+    /// all into memory. Equivalent to [`Self::seed`] with the concatenated
+    /// data. Returns the number of bytes absorbed.
+    ///
+    /// The data is streamed through a rate sized buffer which is zeroized
+    /// afterwards. Use [`Read::take`](std::io::Read::take) to limit the
+    /// amount read from endless sources like devices.
+    ///
+    /// # Errors
+    /// Returns the first error of `reader` other than
+    /// [`ErrorKind::Interrupted`](std::io::ErrorKind::Interrupted). The data
+    /// read before the error has been absorbed.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use std::io::Read;
+    ///
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let hwrng = std::fs::File::open("/dev/hwrng")?;
+    /// rng.seed_from_reader(hwrng.take(64))?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn seed_from_reader<R: std::io::Read>(&mut self, mut reader: R) -> std::io::Result<u64> {
+        let mut buffer = zeroize::Zeroizing::new([0u8; RATE_BYTES]);
+        let mut filled = 0;
+        let mut total = 0;
+        let res = loop {
+            match reader.read(&mut buffer[filled..]) {
+                Ok(0) => break Ok(()),
+                Ok(n) => {
+                    filled += n;
+                    total += n as u64;
+                    if filled == RATE_BYTES {
+                        self.absorb_block(&buffer);
+                        filled = 0;
+                    }
+                },
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {},
+                Err(e) => break Err(e),
+            }
+        };
+        self.absorb_partial_block_padded(&buffer[..filled]);
+        res.map(|()| total)
     }
 
     /// Create a new unseeded instance of the RNG. You MUST seed the RNG, e.g.
@@ -447,16 +998,105 @@ impl RngState {
     /// Create a new instance of the RNG, seeded with entropy from the OS RNG.
     /// This should be the preferred method to create an instance of the RNG.
     #[cfg(feature = "getrandom")]
-    #[cfg_attr(doc, doc(cfg(feature = "getrandom")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "getrandom")))]
     pub fn new_from_getrandom() -> Result<Self, getrandom::Error> {
         let mut rng = Self::new_unseeded();
         rng.seed_with_getrandom()?;
         Ok(rng)
     }
 
+    /// Like [`Self::new_from_getrandom`], but first blocks until the OS RNG
+    /// is initialized, also on platforms where the OS interface used by the
+    /// `getrandom` crate doesn't wait for it. Use this in daemons which may
+    /// start early on a freshly booted system.
+    ///
+    /// On most platforms (including Linux) `getrandom` already blocks, so
+    /// this is equivalent to [`Self::new_from_getrandom`].
+    #[cfg(all(feature = "getrandom", feature = "std"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "getrandom", feature = "std"))))]
+    pub fn new_from_getrandom_blocking() -> Result<Self, getrandom::Error> {
+        wait_for_os_entropy()?;
+        Self::new_from_getrandom()
+    }
+
+    /// Call `f` with a temporary instance of the RNG seeded from the OS RNG,
+    /// and wipe the full state of the instance afterwards (also if `f`
+    /// panics). For code paths which need some random bytes exactly once.
+    ///
+    /// # Panics
+    /// Panics if the OS RNG fails.
+    ///
+    /// # Examples
+    /// ```
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let nonce: [u8; 24] = RngState::with_temp(|rng| rng.get_random_bytes());
+    /// ```
+    #[cfg(feature = "getrandom")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "getrandom")))]
+    pub fn with_temp<R, F: FnOnce(&mut Self) -> R>(f: F) -> R {
+        /// Wipes the state of the temporary instance when dropped.
+        struct Wipe(RngState);
+
+        impl Drop for Wipe {
+            fn drop(&mut self) {
+                self.0.state.zeroize();
+            }
+        }
+
+        let mut temp = Wipe(Self::new_from_getrandom().expect("failed to seed from the OS RNG"));
+        f(&mut temp.0)
+    }
+
+    /// Create a new, independent instance of the RNG seeded with 64 bytes of
+    /// output of this instance. The RNG MUST be seeded prior to using this
+    /// method.
+    ///
+    /// Useful to give every thread or task its own generator without sharing
+    /// one instance behind a lock. Reseeding either instance doesn't affect
+    /// the other.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let mut child = rng.fork();
+    /// std::thread::spawn(move || child.get_random_bytes::<32>());
+    /// # }
+    /// ```
+    pub fn fork(&mut self) -> Self {
+        const FORK_LABEL: &[u8] = b"fast-erasure-shake-rng fork";
+        let mut child = Self::new_unseeded();
+        child.seed(FORK_LABEL);
+        child
+            .seed_with_64(|buf| {
+                self.fill_random_bytes(buf);
+                Ok::<_, core::convert::Infallible>(())
+            })
+            .unwrap();
+        child
+    }
+
     /// Fill `dest` with random bytes. The RNG MUST be seeded prior to using
     /// this method.
+    ///
+    /// # Timing
+    /// The execution time depends only on `dest.len()`: the output path only
+    /// copies, permutes and zeroizes, without branches or memory accesses
+    /// depending on the state. This can be verified with the hooks of the
+    /// `ct-verify` feature.
+    ///
+    /// # Empty requests
+    /// An empty `dest` still permutes and erases the state, i.e. it is
+    /// equivalent to [`Self::ratchet`]. Use
+    /// [`Self::fill_random_bytes_skip_empty`] where empty buffers should
+    /// cost nothing.
+    #[cfg_attr(feature = "profiling", track_caller)]
     pub fn fill_random_bytes(&mut self, mut dest: &mut [u8]) {
+        #[cfg(feature = "profiling")]
+        let _span = profiling::Span::start(dest.len());
         self.basic_initial_output(dest);
         if dest.len() > RATE_BYTES {
             dest = &mut dest[RATE_BYTES..];
@@ -473,13 +1113,125 @@ impl RngState {
         self.basic_make_forward_secure();
     }
 
+    /// XOR random bytes into `buf`, instead of overwriting it like
+    /// [`Self::fill_random_bytes`]. The RNG MUST be seeded prior to using this
+    /// method.
+    ///
+    /// The XORed bytes are exactly those [`Self::fill_random_bytes`] would
+    /// have output, taken directly from the state, so masking or blinding a
+    /// buffer or splitting it into XOR shares needs no second buffer.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let secret = *b"attack at dawn";
+    /// // split the secret into two shares
+    /// let mut share = secret;
+    /// let mut mask = [0; 14];
+    /// rng.fill_random_bytes(&mut mask);
+    /// share.iter_mut().zip(mask).for_each(|(s, m)| *s ^= m);
+    /// // or without the separate mask buffer
+    /// let mut share = secret;
+    /// rng.xor_random_bytes(&mut share);
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "profiling", track_caller)]
+    pub fn xor_random_bytes(&mut self, mut buf: &mut [u8]) {
+        #[cfg(feature = "profiling")]
+        let _span = profiling::Span::start(buf.len());
+        fn xor(buf: &mut [u8], random: &[u8]) {
+            for (b, r) in buf.iter_mut().zip(random) {
+                *b ^= r;
+            }
+        }
+
+        xor(buf, self.state.get_rate_bytes());
+        self.apply_f();
+        if buf.len() > RATE_BYTES {
+            buf = &mut buf[RATE_BYTES..];
+            loop {
+                xor(buf, self.state.get_rate_zeroized_capacity_bytes());
+                self.apply_f();
+                if buf.len() <= RATE_BYTES + CAPACITY_BYTES {
+                    break;
+                }
+                buf = &mut buf[RATE_BYTES + CAPACITY_BYTES..];
+            }
+        }
+        self.basic_make_forward_secure();
+    }
+
+    /// Like [`Self::fill_random_bytes`], but a no-op if `dest` is empty,
+    /// leaving the state untouched instead of ratcheting it. The RNG MUST be
+    /// seeded prior to using this method.
+    ///
+    /// Useful in hot loops which occasionally pass empty buffers.
+    #[inline]
+    #[cfg_attr(feature = "profiling", track_caller)]
+    pub fn fill_random_bytes_skip_empty(&mut self, dest: &mut [u8]) {
+        if !dest.is_empty() {
+            self.fill_random_bytes(dest);
+        }
+    }
+
     /// Output an array `[u8; N]` filled with random bytes. The RNG MUST be
     /// seeded prior to using this method.
+    #[cfg_attr(feature = "profiling", track_caller)]
     pub fn get_random_bytes<const N: usize>(&mut self) -> [u8; N] {
         let mut out = [0; N];
         self.fill_random_bytes(&mut out);
         out
     }
+
+    /// Output a `Vec<u8>` of `len` random bytes, for lengths only known at
+    /// runtime. The vector is allocated once and filled in a single output
+    /// request, like [`Self::fill_random_bytes`]. The RNG MUST be seeded prior
+    /// to using this method.
+    ///
+    /// # Examples
+    /// ```
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// # let len = 48;
+    /// let salt = rng.get_random_vec(len);
+    /// assert_eq!(salt.len(), len);
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    #[cfg_attr(feature = "profiling", track_caller)]
+    pub fn get_random_vec(&mut self, len: usize) -> alloc::vec::Vec<u8> {
+        let mut out = alloc::vec![0; len];
+        self.fill_random_bytes(&mut out);
+        out
+    }
+
+    /// Force an erasure point: apply the permutation and make the state
+    /// forward secure, without producing output. If the state leaks after
+    /// this call, no earlier inputs or outputs can be recovered from it.
+    ///
+    /// Output requests already end like this, so this is only needed at
+    /// security-relevant boundaries not ending in an output request, e.g.
+    /// right after seeding, or to document the boundary explicitly. The state
+    /// need not be seeded.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// rng.seed(b"long-term secret input");
+    /// rng.ratchet();
+    /// # }
+    /// ```
+    pub fn ratchet(&mut self) {
+        self.apply_f();
+        self.basic_make_forward_secure();
+    }
 }
 
 #[cfg(feature = "rand-core")]
@@ -487,7 +1239,7 @@ mod rand_core {
     use super::{u64_slice_as_ne_bytes, u64_slice_as_ne_bytes_mut, RngState};
     use rand_core::RngCore;
 
-    #[cfg_attr(doc, doc(cfg(feature = "rand_core")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rand-core")))]
     impl RngCore for RngState {
         /// Very slow due to fast erasure. Don't use.
         fn next_u32(&mut self) -> u32 {
@@ -516,45 +1268,162 @@ mod rand_core {
         }
     }
 
-    #[cfg_attr(doc, doc(cfg(feature = "rand_core")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rand-core")))]
     impl rand_core::CryptoRng for RngState {}
 
-    #[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
-    pub struct Seed([u64; 8]);
+    /// Error returned when converting a byte slice of the wrong length into a
+    /// [`Seed`] or [`FullRateSeed`].
+    #[cfg_attr(docsrs, doc(cfg(feature = "rand-core")))]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct InvalidSeedLength;
 
-    impl AsRef<[u8]> for Seed {
-        fn as_ref(&self) -> &[u8] {
-            u64_slice_as_ne_bytes(self.0.as_ref())
+    impl core::fmt::Display for InvalidSeedLength {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.write_str("seed has an invalid length")
         }
     }
 
-    impl AsMut<[u8]> for Seed {
-        fn as_mut(&mut self) -> &mut [u8] {
-            u64_slice_as_ne_bytes_mut(self.0.as_mut())
-        }
-    }
+    #[cfg(feature = "std")]
+    impl std::error::Error for InvalidSeedLength {}
 
-    impl From<[u64; 8]> for Seed {
-        fn from(other: [u64; 8]) -> Self {
-            Self(other)
-        }
+    macro_rules! seed_type {
+        ($(#[$attr:meta])* $name:ident, $lanes:literal, $bytes:literal) => {
+            $(#[$attr])*
+            ///
+            /// The seed is zeroized on drop, its `Debug` implementation doesn't
+            /// reveal the contents, and comparisons run in constant time.
+            #[cfg_attr(docsrs, doc(cfg(feature = "rand-core")))]
+            #[derive(Clone, Default)]
+            pub struct $name([u64; $lanes]);
+
+            impl core::fmt::Debug for $name {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    f.write_str(concat!(stringify!($name), "([REDACTED])"))
+                }
+            }
+
+            impl PartialEq for $name {
+                fn eq(&self, other: &Self) -> bool {
+                    crate::ct_eq(
+                        u64_slice_as_ne_bytes(self.0.as_ref()),
+                        u64_slice_as_ne_bytes(other.0.as_ref()),
+                    )
+                }
+            }
+
+            impl Eq for $name {}
+
+            impl AsRef<[u8]> for $name {
+                fn as_ref(&self) -> &[u8] {
+                    u64_slice_as_ne_bytes(self.0.as_ref())
+                }
+            }
+
+            impl AsMut<[u8]> for $name {
+                fn as_mut(&mut self) -> &mut [u8] {
+                    u64_slice_as_ne_bytes_mut(self.0.as_mut())
+                }
+            }
+
+            impl From<[u64; $lanes]> for $name {
+                fn from(other: [u64; $lanes]) -> Self {
+                    Self(other)
+                }
+            }
+
+            impl From<[u8; $bytes]> for $name {
+                fn from(other: [u8; $bytes]) -> Self {
+                    let mut seed = Self::default();
+                    seed.as_mut().copy_from_slice(&other);
+                    seed
+                }
+            }
+
+            impl TryFrom<&[u8]> for $name {
+                type Error = InvalidSeedLength;
+
+                fn try_from(other: &[u8]) -> Result<Self, Self::Error> {
+                    if other.len() != $bytes {
+                        return Err(InvalidSeedLength);
+                    }
+                    let mut seed = Self::default();
+                    seed.as_mut().copy_from_slice(other);
+                    Ok(seed)
+                }
+            }
+
+            impl zeroize::Zeroize for $name {
+                fn zeroize(&mut self) {
+                    self.0.zeroize()
+                }
+            }
+
+            impl Drop for $name {
+                fn drop(&mut self) {
+                    zeroize::Zeroize::zeroize(self)
+                }
+            }
+
+            impl zeroize::ZeroizeOnDrop for $name {}
+        };
     }
 
-    impl zeroize::Zeroize for Seed {
-        fn zeroize(&mut self) {
-            self.0.zeroize()
+    seed_type!(
+        /// Seed for [`rand_core::SeedableRng::from_seed`]: 64 bytes, which are
+        /// absorbed together with padding using a single permutation call.
+        Seed,
+        8,
+        64
+    );
+
+    seed_type!(
+        /// Seed for [`RngState::from_full_rate_seed`]: 72 bytes, a full block
+        /// of the rate, which is absorbed without padding using a single
+        /// permutation call.
+        FullRateSeed,
+        9,
+        72
+    );
+
+    impl RngState {
+        /// Create an instance of this PRNG seeded with `seed`, which covers the
+        /// full rate of the sponge. This absorbs the maximum amount of
+        /// entropy possible with a single call to keccak-f.
+        ///
+        /// Compared to [`rand_core::SeedableRng::from_seed`], this takes 72
+        /// instead of 64 bytes of seed.
+        #[cfg_attr(docsrs, doc(cfg(feature = "rand-core")))]
+        pub fn from_full_rate_seed(seed: FullRateSeed) -> Self {
+            let mut rng = Self::new_unseeded();
+            rng.absorb_block(seed.as_ref().try_into().unwrap());
+            rng
         }
-    }
 
-    impl Drop for Seed {
-        fn drop(&mut self) {
-            zeroize::Zeroize::zeroize(self)
+        /// (Re)seed this existing instance with 64 bytes of output from
+        /// `seeder_rng`, e.g. an HSM adapter or a jitter collector
+        /// implementing [`rand_core::RngCore`]. Like
+        /// [`rand_core::SeedableRng::from_rng`], but without rebuilding the
+        /// instance, so previously absorbed entropy is kept.
+        ///
+        /// # Examples
+        /// ```
+        /// use fast_erasure_shake_rng::RngState;
+        ///
+        /// let mut rng = RngState::new_from_getrandom().unwrap();
+        /// let mut other = RngState::new_from_getrandom().unwrap();
+        /// rng.seed_from_rng(&mut other).unwrap();
+        /// ```
+        #[cfg_attr(docsrs, doc(cfg(feature = "rand-core")))]
+        pub fn seed_from_rng<R: rand_core::RngCore + ?Sized>(
+            &mut self,
+            seeder_rng: &mut R,
+        ) -> Result<(), rand_core::Error> {
+            // Don't leave a copy of the seeding material.
+            self.seed_with_64(|buf| seeder_rng.try_fill_bytes(buf))
         }
     }
 
-    impl zeroize::ZeroizeOnDrop for Seed {}
-
-    #[cfg_attr(doc, doc(cfg(feature = "rand_core")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rand-core")))]
     impl rand_core::SeedableRng for RngState {
         type Seed = Seed;
 
@@ -565,7 +1434,9 @@ mod rand_core {
             rng
         }
 
-        /// An `u64` doesn't give enough entropy. Don't use!
+        /// An `u64` doesn't give enough entropy. Don't use! Use
+        /// `StrongSeededRng` (feature `deny-weak-seeding`) to rule this out at
+        /// compile time.
         fn seed_from_u64(state: u64) -> Self {
             // A PCG32 is not going to help here. Keccak is secure; the problem is that this
             // seed is way to small (can't contain enough entropy).
@@ -598,6 +1469,36 @@ mod rand_core {
     }
 }
 
+#[cfg(feature = "std")]
+mod io {
+    use super::{Absorber, RngState};
+
+    /// Reading fills the buffer with random bytes, as
+    /// [`RngState::fill_random_bytes`], so every `read` call is forward
+    /// secure. Never fails and always fills the whole buffer.
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    impl std::io::Read for RngState {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.fill_random_bytes(buf);
+            Ok(buf.len())
+        }
+    }
+
+    /// Writing absorbs raw bytes, as [`Absorber::write_bytes`], e.g. to stream
+    /// a serialization into the state from [`AbsorbInto`](crate::AbsorbInto).
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    impl std::io::Write for Absorber<'_> {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.write_bytes(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::RngState;
@@ -608,6 +1509,14 @@ mod tests {
         assert_eq!(result, 4);
     }
 
+    #[test]
+    fn ct_eq_compares_contents_and_length() {
+        assert!(super::ct_eq(b"abc", b"abc"));
+        assert!(!super::ct_eq(b"abc", b"abd"));
+        assert!(!super::ct_eq(b"abc", b"ab"));
+        assert!(super::ct_eq(b"", b""));
+    }
+
     #[test]
     fn create_and_seed() {
         let mut rng = RngState::new_unseeded();
@@ -619,6 +1528,14 @@ mod tests {
         assert_ne!(out1, out2);
     }
 
+    #[test]
+    fn fork_is_independent() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"HELLO WORLD");
+        let mut child = rng.fork();
+        assert_ne!(rng.get_random_bytes::<32>(), child.get_random_bytes::<32>());
+    }
+
     #[cfg(feature = "getrandom")]
     #[test]
     fn create_from_getrandom() {
@@ -630,13 +1547,222 @@ mod tests {
         assert_ne!(out1, out2);
     }
 
-    #[cfg(feature = "rand_core")]
+    #[cfg(feature = "getrandom")]
+    #[test]
+    fn with_temp_returns_output() {
+        let a: [u8; 32] = RngState::with_temp(|rng| rng.get_random_bytes());
+        let b: [u8; 32] = RngState::with_temp(|rng| rng.get_random_bytes());
+        assert_ne!(a, b);
+    }
+
+    #[cfg(all(feature = "getrandom", feature = "std"))]
+    #[test]
+    fn create_from_getrandom_blocking() {
+        let mut rng = RngState::new_from_getrandom_blocking().expect("error in getrandom");
+        let mut builder_rng = crate::RngBuilder::new()
+            .block_until_os_seeded()
+            .build()
+            .unwrap();
+        assert_ne!(
+            rng.get_random_bytes::<32>(),
+            builder_rng.get_random_bytes::<32>()
+        );
+    }
+
+    #[cfg(feature = "rand-core")]
     #[test]
     fn rand_core_from_seed() {
+        use rand_core::{RngCore, SeedableRng};
+
         let seed = [37u64; 8].into();
         let mut rng = RngState::from_seed(seed);
         let mut buf = [0; 15];
         rng.try_fill_bytes(&mut buf).expect("unreachable");
         assert_ne!(buf, [0; 15]);
     }
+
+    #[cfg(feature = "rand-core")]
+    #[test]
+    fn rand_core_seed_conversions() {
+        extern crate std;
+
+        use crate::{InvalidSeedLength, Seed};
+
+        let seed = Seed::from([5u8; 64]);
+        assert_eq!(Seed::try_from([5u8; 64].as_ref()), Ok(seed.clone()));
+        assert_ne!(Seed::from([6u8; 64]), seed);
+        assert_eq!(Seed::try_from([5u8; 63].as_ref()), Err(InvalidSeedLength));
+        assert_eq!(std::format!("{:?}", seed), "Seed([REDACTED])");
+    }
+
+    #[cfg(feature = "rand-core")]
+    #[test]
+    fn rand_core_seed_from_rng_matches_from_rng() {
+        use rand_core::SeedableRng;
+
+        let mut seeder = RngState::new_unseeded();
+        seeder.seed(b"seed_from_rng tests");
+        let mut seeder_copy = RngState::new_unseeded();
+        seeder_copy.seed(b"seed_from_rng tests");
+
+        let mut rng = RngState::new_unseeded();
+        rng.seed_from_rng(&mut seeder).unwrap();
+        let mut reference = RngState::from_rng(&mut seeder_copy).unwrap();
+        assert_eq!(
+            rng.get_random_bytes::<32>(),
+            reference.get_random_bytes::<32>()
+        );
+    }
+
+    #[cfg(feature = "rand-core")]
+    #[test]
+    fn rand_core_from_full_rate_seed() {
+        let mut rng1 = RngState::from_full_rate_seed([37u64; 9].into());
+        let mut rng2 = RngState::from_full_rate_seed([38u64; 9].into());
+        assert_ne!(rng1.get_random_bytes::<32>(), rng2.get_random_bytes::<32>());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn seed_from_reader_equals_seed() {
+        // a reader returning short reads
+        struct Trickle<'a>(&'a [u8]);
+        impl std::io::Read for Trickle<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let n = buf.len().min(self.0.len()).min(5);
+                buf[..n].copy_from_slice(&self.0[..n]);
+                self.0 = &self.0[n..];
+                Ok(n)
+            }
+        }
+
+        let data: [u8; 200] = core::array::from_fn(|i| u8::try_from(i).unwrap());
+        let mut rng = RngState::new_unseeded();
+        assert_eq!(rng.seed_from_reader(Trickle(&data)).unwrap(), 200);
+        let mut reference = RngState::new_unseeded();
+        reference.seed(&data);
+        assert_eq!(
+            rng.get_random_bytes::<32>(),
+            reference.get_random_bytes::<32>()
+        );
+    }
+
+    #[test]
+    fn ratchet_equals_empty_request() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"ratchet tests");
+        let mut reference = RngState::new_unseeded();
+        reference.seed(b"ratchet tests");
+        rng.ratchet();
+        reference.fill_random_bytes(&mut []);
+        assert_eq!(
+            rng.get_random_bytes::<32>(),
+            reference.get_random_bytes::<32>()
+        );
+    }
+
+    #[test]
+    fn skip_empty_leaves_state_untouched() {
+        let (mut rng, mut reference) = (RngState::new_unseeded(), RngState::new_unseeded());
+        rng.seed(b"skip empty tests");
+        reference.seed(b"skip empty tests");
+        rng.fill_random_bytes_skip_empty(&mut []);
+        assert_eq!(
+            rng.get_random_bytes::<32>(),
+            reference.get_random_bytes::<32>()
+        );
+        let mut out = [0; 8];
+        rng.fill_random_bytes_skip_empty(&mut out);
+        assert_eq!(out, reference.get_random_bytes::<8>());
+    }
+
+    #[test]
+    fn empty_seed_is_domain_separated() {
+        let outputs = [&b""[..], &[0x80], &[0x80, 0x80]].map(|seed| {
+            let mut rng = RngState::new_unseeded();
+            rng.seed(seed);
+            rng.get_random_bytes::<32>()
+        });
+        assert_ne!(outputs[0], outputs[1]);
+        assert_ne!(outputs[0], outputs[2]);
+        assert_ne!(
+            outputs[0],
+            RngState::new_unseeded().get_random_bytes::<32>()
+        );
+    }
+
+    #[test]
+    fn padded_absorb_accepts_any_length() {
+        use super::RATE_BYTES;
+
+        for len in [RATE_BYTES - 1, RATE_BYTES, RATE_BYTES + 1, 2 * RATE_BYTES] {
+            let data = [0x17; 2 * RATE_BYTES];
+            let (mut rng, mut reference) = (RngState::new_unseeded(), RngState::new_unseeded());
+            rng.absorb_partial_block_padded(&data[..len]);
+            // full blocks first, then the (possibly empty) padded remainder
+            let full = len / RATE_BYTES * RATE_BYTES;
+            for block in data[..full].chunks_exact(RATE_BYTES) {
+                reference.absorb_block(block.try_into().unwrap());
+            }
+            reference.absorb_partial_block_padded(&data[full..len]);
+            assert_eq!(
+                rng.get_random_bytes::<32>(),
+                reference.get_random_bytes::<32>()
+            );
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn io_adapters() {
+        use std::io::{Read, Write};
+
+        let (mut rng, mut reference) = (RngState::new_unseeded(), RngState::new_unseeded());
+        let mut buf = [0; 100];
+        rng.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, reference.get_random_bytes::<100>());
+
+        struct Written;
+        impl crate::AbsorbInto for Written {
+            fn absorb_into(&self, absorber: &mut crate::Absorber<'_>) {
+                write!(absorber, "{}-{}", 4, 2).unwrap();
+            }
+        }
+        rng.absorb(&Written);
+        reference.seed(b"4-2");
+        assert_eq!(
+            rng.get_random_bytes::<32>(),
+            reference.get_random_bytes::<32>()
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn random_vec_matches_fill() {
+        let (mut rng, mut reference) = (RngState::new_unseeded(), RngState::new_unseeded());
+        let out = rng.get_random_vec(300);
+        assert_eq!(out.len(), 300);
+        assert_eq!(out[..], reference.get_random_bytes::<300>()[..]);
+        assert!(rng.get_random_vec(0).is_empty());
+    }
+
+    #[test]
+    fn xor_random_bytes_matches_fill() {
+        for len in [0, 1, 72, 73, 208, 209, 500] {
+            let (mut rng, mut reference) = (RngState::new_unseeded(), RngState::new_unseeded());
+            rng.seed(b"xor tests");
+            reference.seed(b"xor tests");
+            let mut buf = [0x5a; 500];
+            rng.xor_random_bytes(&mut buf[..len]);
+            let mut expected = [0; 500];
+            reference.fill_random_bytes(&mut expected[..len]);
+            for (b, e) in buf.iter().zip(expected) {
+                assert_eq!(*b, e ^ 0x5a);
+            }
+            assert_eq!(
+                rng.get_random_bytes::<32>(),
+                reference.get_random_bytes::<32>()
+            );
+        }
+    }
 }
@@ -0,0 +1,174 @@
+//! Sizes of the sponge's block structure, for wrappers which want to align
+//! their buffer sizes to it.
+//!
+//! A request for output first outputs up to [`FIRST_BLOCK_BYTES`] bytes from
+//! the rate area, then up to [`BLOCK_BYTES`] bytes per further block from the
+//! rate and zeroized capacity areas, applying the permutation once per block.
+//! Requesting a multiple of the block structure, as computed by
+//! [`optimal_request_size`], wastes no permutation output.
+
+/// Size of the rate area of the state in bytes: the number of bytes absorbed
+/// per application of the permutation when seeding.
+pub const RATE_BYTES: usize = crate::RATE_BYTES;
+
+/// Size of the (zeroized) capacity area of the state in bytes.
+pub const CAPACITY_BYTES: usize = crate::CAPACITY_BYTES;
+
+/// Size of the full Keccak-f\[1600\] state in bytes.
+pub const STATE_BYTES: usize = crate::BITS / 8;
+
+/// Number of output bytes of the first block of a request.
+pub const FIRST_BLOCK_BYTES: usize = RATE_BYTES;
+
+/// Number of output bytes of every block of a request after the first.
+pub const BLOCK_BYTES: usize = RATE_BYTES + CAPACITY_BYTES;
+
+/// Number of output blocks (applications of the permutation, excluding the
+/// one making the state forward secure) needed for a request of `len` bytes.
+/// Even an empty request takes one block.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::limits::blocks_needed;
+///
+/// assert_eq!(blocks_needed(32), 1);
+/// assert_eq!(blocks_needed(72), 1);
+/// assert_eq!(blocks_needed(73), 2);
+/// assert_eq!(blocks_needed(208), 2);
+/// ```
+pub const fn blocks_needed(len: usize) -> usize {
+    if len <= FIRST_BLOCK_BYTES {
+        1
+    } else {
+        1 + (len - FIRST_BLOCK_BYTES).div_ceil(BLOCK_BYTES)
+    }
+}
+
+/// The largest request size taking the same number of blocks as a request of
+/// `min_len` bytes, i.e. the smallest size `>= min_len` which fills all its
+/// blocks completely. Saturates at `usize::MAX`.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::limits::optimal_request_size;
+///
+/// assert_eq!(optimal_request_size(0), 72);
+/// assert_eq!(optimal_request_size(100), 208);
+/// assert_eq!(optimal_request_size(4096), 4152);
+/// ```
+pub const fn optimal_request_size(min_len: usize) -> usize {
+    let extra_blocks = blocks_needed(min_len) - 1;
+    FIRST_BLOCK_BYTES.saturating_add(extra_blocks.saturating_mul(BLOCK_BYTES))
+}
+
+/// Iterator over the sizes of the requests to split a total output length
+/// into, using as few permutations as possible when no request may exceed a
+/// maximum size, e.g. the buffer size of a streaming wrapper.
+///
+/// All chunks except the last have the same size: the largest size not
+/// exceeding the maximum which fills all its blocks completely.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "getrandom")] {
+/// use fast_erasure_shake_rng::limits::OptimalChunks;
+/// use fast_erasure_shake_rng::RngState;
+///
+/// let mut rng = RngState::new_from_getrandom().unwrap();
+/// let mut buffer = [0; 4096];
+/// for chunk in OptimalChunks::new(10_000, buffer.len()) {
+///     rng.fill_random_bytes(&mut buffer[..chunk]);
+///     // write out `buffer[..chunk]`
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct OptimalChunks {
+    remaining: usize,
+    chunk: usize,
+}
+
+impl OptimalChunks {
+    /// Plan requests for `total_len` bytes of output, each of at most
+    /// `max_chunk_len` bytes.
+    ///
+    /// # Panics
+    /// Panics if `max_chunk_len` is zero while `total_len` isn't.
+    pub fn new(total_len: usize, max_chunk_len: usize) -> Self {
+        assert!(
+            max_chunk_len > 0 || total_len == 0,
+            "maximum chunk length must be positive"
+        );
+        let chunk = if max_chunk_len < FIRST_BLOCK_BYTES {
+            max_chunk_len
+        } else {
+            let extra_blocks = (max_chunk_len - FIRST_BLOCK_BYTES) / BLOCK_BYTES;
+            FIRST_BLOCK_BYTES + extra_blocks * BLOCK_BYTES
+        };
+        Self {
+            remaining: total_len,
+            chunk,
+        }
+    }
+}
+
+impl Iterator for OptimalChunks {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let len = core::cmp::min(self.remaining, self.chunk);
+        self.remaining -= len;
+        Some(len)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = match self.remaining {
+            0 => 0,
+            remaining => remaining.div_ceil(self.chunk),
+        };
+        (count, Some(count))
+    }
+}
+
+impl ExactSizeIterator for OptimalChunks {}
+
+impl core::iter::FusedIterator for OptimalChunks {}
+
+#[cfg(test)]
+mod tests {
+    use super::{blocks_needed, optimal_request_size, OptimalChunks, BLOCK_BYTES, STATE_BYTES};
+
+    #[test]
+    fn block_structure() {
+        assert_eq!(STATE_BYTES, 200);
+        assert_eq!(BLOCK_BYTES, 136);
+        assert_eq!(blocks_needed(0), 1);
+        assert_eq!(blocks_needed(72 + 136), 2);
+        assert_eq!(blocks_needed(72 + 136 + 1), 3);
+        assert_eq!(optimal_request_size(usize::MAX), usize::MAX);
+    }
+
+    #[test]
+    fn optimal_size_fills_blocks() {
+        for len in 0..1000 {
+            let optimal = optimal_request_size(len);
+            assert!(optimal >= len);
+            assert_eq!(blocks_needed(optimal), blocks_needed(len));
+            assert_eq!(blocks_needed(optimal + 1), blocks_needed(len) + 1);
+        }
+    }
+
+    #[test]
+    fn optimal_chunks() {
+        let chunks: [usize; 3] = [1024, 1024, 962];
+        assert!(OptimalChunks::new(3010, 1100).eq(chunks));
+        assert!(OptimalChunks::new(100, 50).eq([50, 50]));
+        assert_eq!(OptimalChunks::new(0, 0).count(), 0);
+        assert_eq!(OptimalChunks::new(5000, 4096).len(), 2);
+        let total: usize = OptimalChunks::new(12345, 999).sum();
+        assert_eq!(total, 12345);
+    }
+}
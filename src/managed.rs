@@ -0,0 +1,867 @@
+#[cfg(feature = "audit")]
+use crate::audit::{EventRecorder, SeedTranscript};
+use crate::hardening::Hardening;
+use crate::health::{HealthTestConfig, HealthTests};
+#[cfg(feature = "replay")]
+use crate::replay::{ReplayLog, ReplayMode};
+use crate::status::RngStats;
+use crate::{
+    DegradedOutput, EntropySource, FailurePolicy, OutputPolicy, RngBuilder, RngError, RngEvent,
+    RngObserver, RngState, RATE_BYTES,
+};
+
+/// Process-wide counter absorbed before every output request when
+/// [`RngBuilder::output_counter`] is enabled.
+#[cfg(target_has_atomic = "64")]
+static OUTPUT_COUNTER: core::sync::atomic::AtomicU64 = core::sync::atomic::AtomicU64::new(0);
+
+/// Domain separation label of the output counter.
+#[cfg(target_has_atomic = "64")]
+const OUTPUT_COUNTER_LABEL: &[u8] = b"fast-erasure-shake-rng output counter";
+
+/// Maximum number of differently labeled sources whose credit is tracked for
+/// [`RngBuilder::require_independent_sources`]; further labels are ignored.
+const MAX_TRACKED_SOURCES: usize = crate::MAX_ENTROPY_SOURCES + 2;
+
+/// Fingerprint of a source label (64-bit FNV-1a). Collisions only merge the
+/// credit of two sources, making the two-source rule stricter.
+fn label_fingerprint(label: &str) -> u64 {
+    label.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Known-answer test of the Keccak-f\[1600\] permutation: the first lane of
+/// the permutation of the all-zero state, from the Keccak team's reference
+/// test vectors.
+pub(crate) fn permutation_known_answer_test() -> bool {
+    let mut state = [0u64; crate::LANES];
+    keccak::f1600(&mut state);
+    state[0] == 0xf125_8f79_40e1_dde7
+}
+
+impl RngState {
+    /// Fill `dest` with random bytes, only outputting the rate area and making
+    /// the state forward secure after every block. See
+    /// [`OutputPolicy::Conservative`].
+    pub(crate) fn fill_random_bytes_conservative(&mut self, dest: &mut [u8]) {
+        for block in dest.chunks_mut(RATE_BYTES) {
+            self.basic_initial_output(block);
+            self.basic_make_forward_secure();
+        }
+        if dest.is_empty() {
+            self.fill_random_bytes(dest);
+        }
+    }
+}
+
+/// An [`RngState`] bundled with the policies it was configured with by an
+/// [`RngBuilder`].
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "getrandom")] {
+/// use fast_erasure_shake_rng::ManagedRng;
+///
+/// let mut rng = ManagedRng::builder().build().unwrap();
+/// let mut key = [0; 32];
+/// rng.fill_random_bytes(&mut key);
+/// # }
+/// ```
+pub struct ManagedRng {
+    rng: RngState,
+    pub(crate) output_policy: OutputPolicy,
+    pub(crate) health_tests: Option<HealthTestConfig>,
+    /// How to treat output while degraded, `None` if degraded mode is off.
+    pub(crate) degraded_output: Option<DegradedOutput>,
+    /// Whether no full entropy source has been absorbed yet (only tracked in
+    /// degraded mode).
+    degraded: bool,
+    /// Whether to try upgrading from the OS RNG before output while degraded.
+    #[cfg(feature = "getrandom")]
+    pub(crate) upgrade_from_getrandom: bool,
+    /// Total entropy credited to absorbed inputs, in bits.
+    credited_bits: u64,
+    /// Entropy in bits which must be credited before output is produced.
+    pub(crate) required_entropy: u64,
+    /// Number of differently labeled sources which must each be credited with
+    /// at least the given number of bits before output is produced.
+    pub(crate) required_sources: Option<(usize, u64)>,
+    /// Entropy credited per source, by fingerprint of the source label.
+    source_credits: [Option<(u64, u64)>; MAX_TRACKED_SOURCES],
+    /// Maximum number of bytes per output request.
+    pub(crate) max_request_size: Option<usize>,
+    /// Whether to refuse output from a configuration which is not approved.
+    pub(crate) approved_mode: bool,
+    /// What to do when a self-test fails.
+    pub(crate) failure_policy: FailurePolicy,
+    /// Whether a failed self-test poisoned the RNG.
+    poisoned: bool,
+    /// Service indicator: whether the last output was produced by an
+    /// approved configuration.
+    last_output_approved: bool,
+    /// Whether the RNG may no longer make syscalls.
+    sandboxed: bool,
+    pub(crate) observer: Option<RngObserver>,
+    /// Interval of [`Self::reseed_if_due`].
+    pub(crate) reseed_interval: Option<core::time::Duration>,
+    /// Clock ticks at the last reseed by [`Self::reseed_if_due`].
+    pub(crate) last_reseed_ticks: Option<u64>,
+    /// Whether to absorb the process-wide output counter before every output.
+    #[cfg(target_has_atomic = "64")]
+    pub(crate) output_counter: bool,
+    /// Power analysis countermeasures, if enabled.
+    hardening: Option<Hardening>,
+    /// Counters reported by [`Self::status`].
+    pub(crate) stats: RngStats,
+    #[cfg(feature = "audit")]
+    transcript: Option<SeedTranscript>,
+    #[cfg(feature = "replay")]
+    replay: Option<ReplayMode>,
+}
+
+impl ManagedRng {
+    /// Create an unseeded instance with the default policies. It MUST be
+    /// seeded using [`Self::absorb_source`] before use.
+    pub(crate) fn new_unseeded() -> Self {
+        Self {
+            rng: RngState::new_unseeded(),
+            output_policy: OutputPolicy::default(),
+            health_tests: None,
+            degraded_output: None,
+            degraded: false,
+            #[cfg(feature = "getrandom")]
+            upgrade_from_getrandom: false,
+            credited_bits: 0,
+            required_entropy: 0,
+            required_sources: None,
+            source_credits: [None; MAX_TRACKED_SOURCES],
+            max_request_size: None,
+            approved_mode: false,
+            failure_policy: FailurePolicy::Error,
+            poisoned: false,
+            last_output_approved: false,
+            sandboxed: false,
+            observer: None,
+            reseed_interval: None,
+            last_reseed_ticks: None,
+            #[cfg(target_has_atomic = "64")]
+            output_counter: false,
+            hardening: None,
+            stats: RngStats::default(),
+            #[cfg(feature = "audit")]
+            transcript: None,
+            #[cfg(feature = "replay")]
+            replay: None,
+        }
+    }
+
+    /// Start recording seeding events in a transcript.
+    #[cfg(feature = "audit")]
+    pub(crate) fn enable_transcript(&mut self) {
+        self.transcript = Some(SeedTranscript::default());
+    }
+
+    /// Capture the entropy obtained from sources into a replay log, or play
+    /// back a log instead of using the sources.
+    #[cfg(feature = "replay")]
+    pub(crate) fn set_replay(&mut self, mode: ReplayMode) {
+        self.replay = Some(mode);
+    }
+
+    /// Absorb `data`, preceded by the domain separation label `domain` unless
+    /// it is empty. Records a seeding event labeled `label`.
+    pub(crate) fn absorb_input(&mut self, label: &str, domain: &[u8], data: &[u8]) {
+        if !domain.is_empty() {
+            self.seed_state(domain);
+        }
+        self.seed_state(data);
+        self.stats.seeded = true;
+        #[cfg(feature = "audit")]
+        if let Some(transcript) = &mut self.transcript {
+            let mut recorder = EventRecorder::new();
+            recorder.update(data);
+            transcript.push(recorder.finish(label, !self.sandboxed));
+        }
+        #[cfg(not(feature = "audit"))]
+        let _ = label;
+    }
+
+    /// Enable the power analysis countermeasures, see
+    /// [`RngBuilder::power_analysis_hardening`].
+    pub(crate) fn enable_hardening(&mut self) {
+        self.hardening = Some(Hardening::new(&mut self.rng));
+    }
+
+    /// Absorb `data` into the state, hardened if enabled.
+    fn seed_state(&mut self, data: &[u8]) {
+        match &mut self.hardening {
+            Some(hardening) => self.rng.seed_hardened(data, hardening),
+            None => self.rng.seed(data),
+        }
+    }
+
+    /// Fill `dest` like [`RngState::fill_random_bytes`], hardened if enabled.
+    fn fill_state(&mut self, dest: &mut [u8]) {
+        match &mut self.hardening {
+            Some(hardening) => self.rng.fill_random_bytes_hardened(dest, hardening),
+            None => self.rng.fill_random_bytes(dest),
+        }
+    }
+
+    /// Report `event` to the observer, if any.
+    pub(crate) fn notify(&self, event: RngEvent<'_>) {
+        if let Some(observer) = self.observer {
+            observer(&event);
+        }
+    }
+
+    /// Absorb entropy from `source` and report the outcome to the observer.
+    /// Without health tests 64 bytes are absorbed, with health tests the
+    /// startup number of samples is drawn and tested. Records a seeding event
+    /// labeled with the source label.
+    pub(crate) fn absorb_source<S: EntropySource + ?Sized>(
+        &mut self,
+        source: &mut S,
+    ) -> Result<(), RngError> {
+        let res = self.absorb_source_unobserved(source);
+        match res {
+            Ok(()) => {
+                if self.health_tests.is_some() {
+                    self.stats.record_health_test(true);
+                }
+                self.stats.record_reseed(self.sandboxed);
+            },
+            Err(RngError::HealthTest(_)) => self.stats.record_health_test(false),
+            Err(_) => {},
+        }
+        self.notify(match res {
+            Ok(()) => RngEvent::Reseeded {
+                source: source.label(),
+            },
+            Err(error) => RngEvent::SourceFailed {
+                source: source.label(),
+                error,
+            },
+        });
+        match res {
+            Err(error @ RngError::HealthTest(_)) => Err(self.fail(error)),
+            res => res,
+        }
+    }
+
+    /// Apply the failure policy to the failed self-test `error`, returning the
+    /// error to report.
+    ///
+    /// # Panics
+    /// Panics with [`FailurePolicy::Panic`].
+    pub(crate) fn fail(&mut self, error: RngError) -> RngError {
+        match self.failure_policy {
+            FailurePolicy::Panic => panic!("RNG self-test failed: {}", error),
+            FailurePolicy::Error => {},
+            FailurePolicy::Poison => self.poison(),
+        }
+        error
+    }
+
+    fn absorb_source_unobserved<S: EntropySource + ?Sized>(
+        &mut self,
+        source: &mut S,
+    ) -> Result<(), RngError> {
+        let mut tests = self.health_tests.map(HealthTests::new);
+        let chunks = match &tests {
+            Some(tests) => tests.startup_samples().div_ceil(64),
+            None => 1,
+        };
+        let credit = source.entropy_credit(chunks * 64);
+        #[cfg(feature = "audit")]
+        let mut recorder = self.transcript.as_ref().map(|_| EventRecorder::new());
+        #[cfg(feature = "replay")]
+        let replay = &mut self.replay;
+        for _ in 0..chunks {
+            self.rng
+                .seed_with_64_hardened(self.hardening.as_mut(), |buf| {
+                    #[cfg(feature = "replay")]
+                    match replay {
+                        Some(ReplayMode::Playback(log)) => log.play(buf)?,
+                        Some(ReplayMode::Capture(log)) => {
+                            source.fill_entropy(buf)?;
+                            log.record(buf);
+                        },
+                        None => source.fill_entropy(buf)?,
+                    }
+                    #[cfg(not(feature = "replay"))]
+                    source.fill_entropy(buf)?;
+                    if let Some(tests) = &mut tests {
+                        tests.feed_all(buf)?;
+                    }
+                    #[cfg(feature = "audit")]
+                    if let Some(recorder) = &mut recorder {
+                        recorder.update(buf);
+                    }
+                    Ok::<_, RngError>(())
+                })?;
+        }
+        #[cfg(feature = "audit")]
+        if let (Some(transcript), Some(recorder)) = (&mut self.transcript, recorder) {
+            transcript.push(recorder.finish(source.label(), !self.sandboxed));
+        }
+        if source.is_full_entropy() {
+            self.degraded = false;
+        }
+        self.credit(source.label(), credit);
+        Ok(())
+    }
+
+    /// Mark the RNG as degraded if degraded mode is enabled.
+    pub(crate) fn mark_degraded(&mut self) {
+        self.degraded = self.degraded_output.is_some();
+    }
+
+    /// Whether the static configuration is approved, see
+    /// [`RngBuilder::approved_mode`].
+    pub(crate) fn approved_configuration(&self) -> bool {
+        #[cfg(feature = "replay")]
+        if self.replay.is_some() {
+            return false;
+        }
+        self.health_tests.is_some()
+            && self.required_entropy >= crate::APPROVED_MIN_ENTROPY
+            && self
+                .max_request_size
+                .is_some_and(|max| max <= crate::NIST_MAX_REQUEST_BYTES)
+            && self.degraded_output != Some(DegradedOutput::Label)
+    }
+
+    /// Check whether `len` bytes of output may be produced, first trying to
+    /// upgrade a degraded RNG.
+    fn prepare_output(&mut self, len: usize) -> Result<(), RngError> {
+        self.last_output_approved = false;
+        if self.poisoned {
+            return Err(RngError::Poisoned);
+        }
+        if let Some(max) = self.max_request_size {
+            if len > max {
+                return Err(RngError::RequestTooLarge {
+                    requested: len,
+                    max,
+                });
+            }
+        }
+        if self.degraded {
+            #[cfg(feature = "getrandom")]
+            if self.upgrade_from_getrandom && !self.sandboxed {
+                // failure just means we stay degraded
+                let _ = self.absorb_source(&mut crate::OsEntropy);
+            }
+            if self.degraded && self.degraded_output == Some(DegradedOutput::Refuse) {
+                return Err(RngError::Degraded);
+            }
+        }
+        if self.credited_bits < self.required_entropy {
+            return Err(RngError::InsufficientEntropy {
+                credited: self.credited_bits,
+                required: self.required_entropy,
+            });
+        }
+        if let Some((required, min_bits)) = self.required_sources {
+            let qualified = self.qualified_sources(min_bits);
+            if qualified < required {
+                return Err(RngError::InsufficientSources {
+                    qualified,
+                    required,
+                });
+            }
+        }
+        let approved = self.approved_configuration() && !self.degraded;
+        if self.approved_mode && !approved {
+            return Err(RngError::NotApproved);
+        }
+        self.last_output_approved = approved;
+        Ok(())
+    }
+
+    /// Absorb the next value of the process-wide output counter, see
+    /// [`RngBuilder::output_counter`].
+    #[cfg(target_has_atomic = "64")]
+    fn absorb_output_counter(&mut self) {
+        let counter = OUTPUT_COUNTER.fetch_add(1, core::sync::atomic::Ordering::Relaxed);
+        let mut block = [0; OUTPUT_COUNTER_LABEL.len() + 8];
+        block[..OUTPUT_COUNTER_LABEL.len()].copy_from_slice(OUTPUT_COUNTER_LABEL);
+        block[OUTPUT_COUNTER_LABEL.len()..].copy_from_slice(&counter.to_le_bytes());
+        self.seed_state(&block);
+    }
+
+    /// Create a builder to configure a new instance, equivalent to
+    /// [`RngBuilder::new`].
+    pub fn builder<'a>() -> RngBuilder<'a> {
+        RngBuilder::new()
+    }
+
+    /// Fill `dest` with random bytes according to the output policy.
+    ///
+    /// # Panics
+    /// Panics if the configured policies refuse output, see
+    /// [`Self::try_fill_random_bytes`].
+    #[cfg_attr(feature = "profiling", track_caller)]
+    pub fn fill_random_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(e) = self.try_fill_random_bytes(dest) {
+            panic!("RNG refused output: {}", e);
+        }
+    }
+
+    /// Fill `dest` with random bytes according to the output policy, or
+    /// return an error if the configured policies refuse output.
+    ///
+    /// # Errors
+    /// Returns [`RngError::Degraded`] if the RNG is degraded and configured to
+    /// refuse output in that case, see [`RngBuilder::degraded_mode`]. Returns
+    /// [`RngError::InsufficientEntropy`] if less entropy has been credited than
+    /// required by [`RngBuilder::require_entropy`]. Returns
+    /// [`RngError::InsufficientSources`] if fewer independent sources have
+    /// been credited than required by
+    /// [`RngBuilder::require_independent_sources`]. Returns
+    /// [`RngError::NotApproved`] in approved mode if the state isn't approved,
+    /// see [`RngBuilder::approved_mode`]. Returns
+    /// [`RngError::RequestTooLarge`] if `dest` exceeds the maximum request
+    /// size, see [`RngBuilder::max_request_size`].
+    #[cfg_attr(feature = "profiling", track_caller)]
+    pub fn try_fill_random_bytes(&mut self, dest: &mut [u8]) -> Result<(), RngError> {
+        #[cfg(feature = "profiling")]
+        let _span = crate::profiling::Span::start(dest.len());
+        if let Err(e) = self.prepare_output(dest.len()) {
+            self.notify(RngEvent::OutputRefused(e));
+            return Err(e);
+        }
+        #[cfg(target_has_atomic = "64")]
+        if self.output_counter {
+            self.absorb_output_counter();
+        }
+        match self.output_policy {
+            OutputPolicy::Fast => self.fill_state(dest),
+            OutputPolicy::Conservative => match &mut self.hardening {
+                Some(hardening) => self
+                    .rng
+                    .fill_random_bytes_conservative_hardened(dest, hardening),
+                None => self.rng.fill_random_bytes_conservative(dest),
+            },
+            OutputPolicy::RatchetInterval(interval) => {
+                if dest.is_empty() {
+                    self.fill_state(dest);
+                }
+                for chunk in dest.chunks_mut(interval.get()) {
+                    self.fill_state(chunk);
+                }
+            },
+        }
+        self.stats.record_output(dest.len());
+        Ok(())
+    }
+
+    /// Output an array `[u8; N]` filled with random bytes according to the
+    /// output policy.
+    ///
+    /// # Panics
+    /// Panics if the configured policies refuse output, see
+    /// [`Self::try_fill_random_bytes`].
+    #[cfg_attr(feature = "profiling", track_caller)]
+    pub fn get_random_bytes<const N: usize>(&mut self) -> [u8; N] {
+        let mut out = [0; N];
+        self.fill_random_bytes(&mut out);
+        out
+    }
+
+    /// Output an array `[u8; N]` filled with random bytes according to the
+    /// output policy, see [`Self::try_fill_random_bytes`].
+    #[cfg_attr(feature = "profiling", track_caller)]
+    pub fn try_get_random_bytes<const N: usize>(&mut self) -> Result<[u8; N], RngError> {
+        let mut out = [0; N];
+        self.try_fill_random_bytes(&mut out)?;
+        Ok(out)
+    }
+
+    /// Force an erasure point without producing output, see
+    /// [`RngState::ratchet`].
+    pub fn ratchet(&mut self) {
+        match &mut self.hardening {
+            // an empty request is a ratchet
+            Some(hardening) => self.rng.fill_random_bytes_hardened(&mut [], hardening),
+            None => self.rng.ratchet(),
+        }
+    }
+
+    /// Hash additional data `seed` into the state, see [`RngState::seed`].
+    pub fn seed(&mut self, seed: &[u8]) {
+        self.absorb_input("seed", &[], seed);
+    }
+
+    /// Hash additional data `seed` into the state like [`Self::seed`],
+    /// crediting it with `credited_bits` bits of entropy (the caller's
+    /// estimate of its min-entropy) towards the threshold set by
+    /// [`RngBuilder::require_entropy`].
+    ///
+    /// The credit is capped at 8 bits per byte of `seed`, so in particular an
+    /// empty `seed` is never credited.
+    pub fn seed_credited(&mut self, seed: &[u8], credited_bits: u64) {
+        self.absorb_input("seed", &[], seed);
+        let credited_bits = core::cmp::min(credited_bits, 8 * seed.len() as u64);
+        self.credit("seed", credited_bits);
+    }
+
+    /// Credit `bits` of entropy absorbed from the source labeled `label`.
+    fn credit(&mut self, label: &str, bits: u64) {
+        self.credited_bits = self.credited_bits.saturating_add(bits);
+        let fingerprint = label_fingerprint(label);
+        let slot = self
+            .source_credits
+            .iter_mut()
+            .find(|slot| !matches!(slot, Some((f, _)) if *f != fingerprint));
+        if let Some(slot) = slot {
+            let (_, credited) = slot.get_or_insert((fingerprint, 0));
+            *credited = credited.saturating_add(bits);
+        }
+    }
+
+    /// Number of differently labeled sources credited with at least
+    /// `min_bits` bits each.
+    fn qualified_sources(&self, min_bits: u64) -> usize {
+        self.source_credits
+            .iter()
+            .flatten()
+            .filter(|(_, credited)| *credited >= min_bits)
+            .count()
+    }
+
+    /// Reseed the RNG from `source`, running the configured health tests on
+    /// its output. A degraded RNG is upgraded if `source` is
+    /// [full entropy](EntropySource::is_full_entropy).
+    pub fn reseed_from<S: EntropySource + ?Sized>(
+        &mut self,
+        source: &mut S,
+    ) -> Result<(), RngError> {
+        self.absorb_source(source)
+    }
+
+    /// Reseed the RNG from the OS RNG.
+    ///
+    /// # Errors
+    /// Returns [`RngError::Sandboxed`] if the RNG is sandboxed, see
+    /// [`Self::enter_sandbox`].
+    #[cfg(feature = "getrandom")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "getrandom")))]
+    pub fn reseed_with_getrandom(&mut self) -> Result<(), RngError> {
+        if self.sandboxed {
+            return Err(RngError::Sandboxed);
+        }
+        self.reseed_from(&mut crate::OsEntropy)
+    }
+
+    /// Enter sandbox mode, before applying a sandbox like seccomp or pledge
+    /// which forbids syscalls. First all OS entropy gathering is done: the RNG
+    /// is reseeded from the OS RNG (if enabled). Afterwards the RNG never
+    /// attempts a syscall of its own: requests to reseed from the OS RNG fail
+    /// with [`RngError::Sandboxed`], a degraded RNG no longer tries to upgrade
+    /// from the OS RNG and seeding events are no longer timestamped.
+    ///
+    /// Entropy sources passed to [`Self::reseed_from`] are still used; making
+    /// sure they don't need syscalls is up to the caller.
+    ///
+    /// # Errors
+    /// Returns an error if reseeding from the OS RNG fails; the RNG is not
+    /// sandboxed then.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::{ManagedRng, RngError};
+    ///
+    /// let mut rng = ManagedRng::builder().build().unwrap();
+    /// rng.enter_sandbox().unwrap();
+    /// // apply the sandbox here
+    /// let key = rng.get_random_bytes::<32>();
+    /// assert_eq!(rng.reseed_with_getrandom(), Err(RngError::Sandboxed));
+    /// # }
+    /// ```
+    pub fn enter_sandbox(&mut self) -> Result<(), RngError> {
+        #[cfg(feature = "getrandom")]
+        if self.upgrade_from_getrandom && !self.sandboxed {
+            self.absorb_source(&mut crate::OsEntropy)?;
+        }
+        self.sandboxed = true;
+        Ok(())
+    }
+
+    /// Whether the RNG is sandboxed, see [`Self::enter_sandbox`].
+    pub fn is_sandboxed(&self) -> bool {
+        self.sandboxed
+    }
+
+    /// The transcript of all seeding events, if enabled using
+    /// [`RngBuilder::audit_transcript`].
+    #[cfg(feature = "audit")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "audit")))]
+    pub fn transcript(&self) -> Option<&SeedTranscript> {
+        self.transcript.as_ref()
+    }
+
+    /// The log of all entropy obtained from sources so far, if capturing was
+    /// enabled using [`RngBuilder::capture_replay`]. NOT FOR PRODUCTION USE,
+    /// see [`ReplayLog`].
+    #[cfg(feature = "replay")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "replay")))]
+    pub fn replay_log(&self) -> Option<&ReplayLog> {
+        match &self.replay {
+            Some(ReplayMode::Capture(log)) => Some(log),
+            _ => None,
+        }
+    }
+
+    /// Block until the entropy threshold set by [`RngBuilder::require_entropy`]
+    /// is reached, by repeatedly reseeding from `source` and sleeping for
+    /// `retry_interval` whenever the source fails or delivers no credited
+    /// entropy.
+    ///
+    /// Never returns if `source` never delivers credited entropy.
+    ///
+    /// # Errors
+    /// Returns an error if the output of `source` fails a health test.
+    /// Returns [`RngError::Sandboxed`] instead of sleeping if the RNG is
+    /// sandboxed, see [`Self::enter_sandbox`].
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn reseed_until_credited<S: EntropySource + ?Sized>(
+        &mut self,
+        source: &mut S,
+        retry_interval: core::time::Duration,
+    ) -> Result<(), RngError> {
+        while self.credited_bits < self.required_entropy {
+            let before = self.credited_bits;
+            match self.reseed_from(source) {
+                Ok(()) | Err(RngError::EntropySource(_)) => {},
+                Err(e) => return Err(e),
+            }
+            if self.credited_bits == before {
+                if self.sandboxed {
+                    return Err(RngError::Sandboxed);
+                }
+                std::thread::sleep(retry_interval);
+            }
+        }
+        Ok(())
+    }
+
+    /// Total entropy in bits credited to the inputs absorbed so far, see
+    /// [`EntropySource::entropy_credit`] and [`Self::seed_credited`].
+    pub fn credited_entropy(&self) -> u64 {
+        self.credited_bits
+    }
+
+    /// Whether the RNG is degraded: it was built in degraded mode and has not
+    /// yet absorbed entropy from a full entropy source. See
+    /// [`RngBuilder::degraded_mode`].
+    pub fn is_degraded(&self) -> bool {
+        self.degraded
+    }
+
+    /// Whether the RNG is poisoned, see [`Self::poison`].
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned
+    }
+
+    /// Poison the RNG: all output requests fail with [`RngError::Poisoned`]
+    /// until it is recovered using [`Self::recover_from`], so a service can't
+    /// keep minting keys from a generator known to be unhealthy. Use this
+    /// e.g. when an integrity check of the caller fails. A failed self-test
+    /// poisons the RNG with [`FailurePolicy::Poison`].
+    ///
+    /// Reseeding and absorbing data are still possible while poisoned, but
+    /// don't recover the RNG.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::{ManagedRng, OsEntropy, RngError};
+    ///
+    /// let mut rng = ManagedRng::builder().build().unwrap();
+    /// rng.poison();
+    /// assert_eq!(rng.try_get_random_bytes::<32>(), Err(RngError::Poisoned));
+    /// rng.recover_from(&mut OsEntropy).unwrap();
+    /// let key = rng.get_random_bytes::<32>();
+    /// # }
+    /// ```
+    pub fn poison(&mut self) {
+        self.poisoned = true;
+        self.notify(RngEvent::Poisoned);
+    }
+
+    /// Recover a poisoned RNG: reseed it from `source`, running the
+    /// configured health tests, and on success acknowledge the failure which
+    /// poisoned it, so it produces output again. Equivalent to
+    /// [`Self::reseed_from`] if the RNG isn't poisoned.
+    ///
+    /// # Errors
+    /// Returns an error if reseeding fails; the RNG stays poisoned then.
+    pub fn recover_from<S: EntropySource + ?Sized>(
+        &mut self,
+        source: &mut S,
+    ) -> Result<(), RngError> {
+        self.reseed_from(source)?;
+        if self.poisoned {
+            self.poisoned = false;
+            self.notify(RngEvent::Recovered);
+        }
+        Ok(())
+    }
+
+    /// Whether this RNG runs in approved mode, see
+    /// [`RngBuilder::approved_mode`].
+    pub fn is_approved_mode(&self) -> bool {
+        self.approved_mode
+    }
+
+    /// Service indicator for the last output operation: whether it produced
+    /// output from an approved configuration, see
+    /// [`RngBuilder::approved_mode`]. `false` if the last operation failed or
+    /// there was none yet.
+    pub fn last_output_approved(&self) -> bool {
+        self.last_output_approved
+    }
+
+    /// The output policy this RNG was configured with.
+    pub fn output_policy(&self) -> OutputPolicy {
+        self.output_policy
+    }
+
+    /// Unwrap the underlying [`RngState`], dropping the policies.
+    pub fn into_inner(mut self) -> RngState {
+        core::mem::replace(&mut self.rng, RngState::new_unseeded())
+    }
+}
+
+/// The state is zeroized on drop, so a dropped RNG leaves no trace of its
+/// future output in memory.
+impl Drop for ManagedRng {
+    fn drop(&mut self) {
+        self.rng.state.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::NonZeroUsize;
+
+    use super::ManagedRng;
+    use crate::OutputPolicy;
+
+    fn managed(policy: OutputPolicy) -> ManagedRng {
+        let mut rng = ManagedRng::new_unseeded();
+        rng.output_policy = policy;
+        rng.seed(b"managed tests");
+        rng
+    }
+
+    #[test]
+    fn policies_fill_all_bytes() {
+        for policy in [
+            OutputPolicy::Fast,
+            OutputPolicy::Conservative,
+            OutputPolicy::RatchetInterval(NonZeroUsize::new(100).unwrap()),
+        ] {
+            let mut rng = managed(policy);
+            let out = rng.get_random_bytes::<1000>();
+            assert!(out.chunks(8).all(|c| c != [0; 8]));
+        }
+    }
+
+    #[test]
+    fn ratchet_interval_matches_chunked_fast() {
+        let interval = NonZeroUsize::new(100).unwrap();
+        let out = managed(OutputPolicy::RatchetInterval(interval)).get_random_bytes::<250>();
+        let mut fast = managed(OutputPolicy::Fast);
+        let mut expected = [0; 250];
+        for chunk in expected.chunks_mut(100) {
+            fast.fill_random_bytes(chunk);
+        }
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn conservative_differs_from_fast() {
+        let conservative = managed(OutputPolicy::Conservative).get_random_bytes::<200>();
+        let fast = managed(OutputPolicy::Fast).get_random_bytes::<200>();
+        assert_eq!(conservative[..72], fast[..72]);
+        assert_ne!(conservative[72..], fast[72..]);
+    }
+
+    #[test]
+    fn sandbox_refuses_os_reseeds() {
+        let mut rng = managed(OutputPolicy::Fast);
+        rng.enter_sandbox().unwrap();
+        assert!(rng.is_sandboxed());
+        #[cfg(feature = "getrandom")]
+        assert_eq!(rng.reseed_with_getrandom(), Err(crate::RngError::Sandboxed));
+        assert!(rng.try_get_random_bytes::<8>().is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "audit")]
+    fn transcript_records_seed_events() {
+        use crate::{EntropySource, EntropySourceError, RngBuilder};
+
+        struct Fixed;
+        impl EntropySource for Fixed {
+            fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+                dest.fill(1);
+                Ok(())
+            }
+
+            fn label(&self) -> &str {
+                "fixed"
+            }
+        }
+
+        let mut source = Fixed;
+        let builder = RngBuilder::new();
+        #[cfg(feature = "getrandom")]
+        let builder = builder.getrandom(false);
+        let mut rng = builder
+            .audit_transcript(true)
+            .personalization(b"audit tests")
+            .entropy_source(&mut source)
+            .build()
+            .unwrap();
+        rng.seed(b"extra");
+
+        let events = rng.transcript().unwrap().events();
+        let summary: [(&str, u64); 3] = [
+            (events[0].source(), events[0].input_len()),
+            (events[1].source(), events[1].input_len()),
+            (events[2].source(), events[2].input_len()),
+        ];
+        assert_eq!(
+            summary,
+            [("personalization", 11), ("fixed", 64), ("seed", 5)]
+        );
+        assert_eq!(events.len(), 3);
+    }
+
+    #[test]
+    fn credit_capped_by_seed_length() {
+        let mut rng = managed(OutputPolicy::Fast);
+        rng.seed_credited(&[], 256);
+        assert_eq!(rng.credited_entropy(), 0);
+        rng.seed_credited(&[0x42; 4], 256);
+        assert_eq!(rng.credited_entropy(), 32);
+    }
+
+    #[cfg(all(target_has_atomic = "64", feature = "hazmat"))]
+    #[test]
+    fn output_counter_diverges_copies() {
+        let mut rng = managed(OutputPolicy::Fast);
+        rng.output_counter = true;
+        let mut copy = managed(OutputPolicy::Fast);
+        copy.rng = crate::hazmat::duplicate_state(&rng.rng);
+        copy.output_counter = true;
+        assert_ne!(rng.get_random_bytes::<32>(), copy.get_random_bytes::<32>());
+    }
+}
@@ -0,0 +1,75 @@
+use crate::SecureRng;
+
+/// A fake [`SecureRng`] producing scripted output, for deterministic unit
+/// tests of code written against [`SecureRng`]. NEVER use this outside of
+/// tests: its output is not random at all.
+///
+/// The output repeats the script over and over. Reseeding doesn't affect the
+/// output but is counted, so tests can check that code under test reseeds.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::{MockRng, SecureRng};
+///
+/// let mut rng = MockRng::new(&[1, 2, 3]);
+/// let mut out = [0; 5];
+/// rng.fill(&mut out);
+/// assert_eq!(out, [1, 2, 3, 1, 2]);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+#[derive(Clone, Debug)]
+pub struct MockRng<'a> {
+    script: &'a [u8],
+    position: usize,
+    reseeds: usize,
+}
+
+impl<'a> MockRng<'a> {
+    /// Create a mock RNG which outputs `script` repeatedly.
+    ///
+    /// # Panics
+    /// Panics if `script` is empty.
+    pub fn new(script: &'a [u8]) -> Self {
+        assert!(!script.is_empty(), "script must not be empty");
+        Self {
+            script,
+            position: 0,
+            reseeds: 0,
+        }
+    }
+
+    /// Number of times [`SecureRng::reseed`] has been called.
+    pub fn reseed_count(&self) -> usize {
+        self.reseeds
+    }
+}
+
+impl SecureRng for MockRng<'_> {
+    fn fill(&mut self, dest: &mut [u8]) {
+        for b in dest {
+            *b = self.script[self.position];
+            self.position = (self.position + 1) % self.script.len();
+        }
+    }
+
+    fn reseed(&mut self, _seed: &[u8]) {
+        self.reseeds += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MockRng;
+    use crate::SecureRng;
+
+    #[test]
+    fn output_continues_script() {
+        let mut rng = MockRng::new(&[1, 2, 3]);
+        let (mut a, mut b) = ([0; 2], [0; 2]);
+        rng.fill(&mut a);
+        rng.reseed(b"ignored");
+        rng.fill(&mut b);
+        assert_eq!((a, b), ([1, 2], [3, 1]));
+        assert_eq!(rng.reseed_count(), 1);
+    }
+}
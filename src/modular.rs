@@ -0,0 +1,174 @@
+use crate::{u64_slice_as_ne_bytes_mut, RngState};
+
+/// Constant-time comparison `a < b` of two equally long little-endian limb
+/// slices (least significant limb first).
+///
+/// Computes the final borrow of the subtraction `a - b` without branching on
+/// the limb values. The borrow is passed through an optimization barrier, so
+/// the compiler can't turn the loop into an early exit.
+pub(crate) fn ct_lt_limbs(a: &[u64], b: &[u64]) -> bool {
+    debug_assert_eq!(a.len(), b.len());
+    let mut borrow = 0u64;
+    for (x, y) in a.iter().zip(b.iter()) {
+        let (diff, borrow1) = x.overflowing_sub(*y);
+        let (_, borrow2) = diff.overflowing_sub(borrow);
+        borrow = core::hint::black_box(u64::from(borrow1 | borrow2));
+    }
+    borrow == 1
+}
+
+impl RngState {
+    /// Fill `out` with a uniformly random integer below `bound`, as
+    /// little-endian `u64` limbs (least significant limb first). The RNG MUST
+    /// be seeded prior to using this method.
+    ///
+    /// Random candidates with the bit length of `bound` are generated until one
+    /// is smaller than `bound`; this rejection sampling makes the result free
+    /// of modulo bias. The comparison with the bound runs in constant time, so
+    /// the only information leaked through timing is the number of rejected
+    /// candidates, which is independent of the returned value.
+    ///
+    /// # Panics
+    /// Panics if `bound` is zero or if `out` and `bound` differ in length.
+    pub fn fill_random_uint_below(&mut self, out: &mut [u64], bound: &[u64]) {
+        assert_eq!(out.len(), bound.len(), "output and bound differ in length");
+        // the bound is public, so its bit length may be computed in variable time
+        let top = bound
+            .iter()
+            .rposition(|&limb| limb != 0)
+            .expect("bound must be non-zero");
+        let top_mask = u64::MAX >> bound[top].leading_zeros();
+
+        out[top + 1..].fill(0);
+        loop {
+            self.fill_random_bytes(u64_slice_as_ne_bytes_mut(&mut out[..=top]));
+            out[top] &= top_mask;
+            if ct_lt_limbs(out, bound) {
+                return;
+            }
+        }
+    }
+
+    /// Fill `out` with a uniformly random integer of at most `bits` bits, i.e.
+    /// below `2^bits`, as little-endian `u64` limbs (least significant limb
+    /// first). The RNG MUST be seeded prior to using this method.
+    ///
+    /// # Panics
+    /// Panics if `out` is too short to hold `bits` bits.
+    pub fn fill_random_uint_bits(&mut self, out: &mut [u64], bits: usize) {
+        assert!(bits <= 64 * out.len(), "output too short for {} bits", bits);
+        let limbs = bits.div_ceil(64);
+        out[limbs..].fill(0);
+        if limbs == 0 {
+            return;
+        }
+        self.fill_random_bytes(u64_slice_as_ne_bytes_mut(&mut out[..limbs]));
+        out[limbs - 1] &= u64::MAX >> (64 * limbs - bits);
+    }
+
+    /// Fill `out` with a uniformly random integer of exactly `bits` bits, i.e.
+    /// with the top bit set, as little-endian `u64` limbs (least significant
+    /// limb first). If `force_odd` is set the lowest bit is set as well. The
+    /// RNG MUST be seeded prior to using this method.
+    ///
+    /// The remaining bits are uniformly random, so the result is uniform over
+    /// all (odd) integers of `bits` bits without bias, as needed for RSA prime
+    /// candidates or fixed-size blinding values. See
+    /// [`Self::random_bits_exact`] for a fixed number of limbs.
+    ///
+    /// # Panics
+    /// Panics if `bits` is zero or if `out` is too short to hold `bits` bits.
+    pub fn fill_random_uint_exact_bits(&mut self, out: &mut [u64], bits: usize, force_odd: bool) {
+        assert!(bits > 0, "an integer of exactly 0 bits doesn't exist");
+        self.fill_random_uint_bits(out, bits);
+        out[(bits - 1) / 64] |= 1 << ((bits - 1) % 64);
+        if force_odd {
+            out[0] |= 1;
+        }
+    }
+
+    /// Output a uniformly random integer of exactly `bits` bits, i.e. with the
+    /// top bit set, as an array of little-endian `u64` limbs (least
+    /// significant limb first). If `force_odd` is set the lowest bit is set as
+    /// well. See [`Self::fill_random_uint_exact_bits`].
+    ///
+    /// # Panics
+    /// Panics if `bits` is zero or exceeds the `64 * LIMBS` bits of the
+    /// output.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// // an odd 1024-bit RSA prime candidate
+    /// let candidate: [u64; 16] = rng.random_bits_exact(1024, true);
+    /// assert_eq!(candidate[15] >> 63, 1);
+    /// assert_eq!(candidate[0] & 1, 1);
+    /// # }
+    /// ```
+    pub fn random_bits_exact<const LIMBS: usize>(
+        &mut self,
+        bits: usize,
+        force_odd: bool,
+    ) -> [u64; LIMBS] {
+        let mut out = [0u64; LIMBS];
+        self.fill_random_uint_exact_bits(&mut out, bits, force_odd);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ct_lt_limbs;
+    use crate::RngState;
+
+    #[test]
+    fn ct_lt() {
+        assert!(ct_lt_limbs(&[1, 0], &[2, 0]));
+        assert!(ct_lt_limbs(&[u64::MAX, 0], &[0, 1]));
+        assert!(!ct_lt_limbs(&[0, 1], &[u64::MAX, 0]));
+        assert!(!ct_lt_limbs(&[3, 3], &[3, 3]));
+    }
+
+    #[test]
+    fn fill_random_uint_below_bound() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"modular tests");
+        let bound = [5, 0, 0];
+        let mut value = [u64::MAX; 3];
+        for _ in 0..32 {
+            rng.fill_random_uint_below(&mut value, &bound);
+            assert!(value[0] < 5 && value[1..] == [0, 0]);
+        }
+    }
+
+    #[test]
+    fn fill_random_uint_bits_masks() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"modular tests");
+        let mut out = [u64::MAX; 3];
+        rng.fill_random_uint_bits(&mut out, 67);
+        assert!(out[1] < 8 && out[2] == 0);
+        rng.fill_random_uint_bits(&mut out, 0);
+        assert_eq!(out, [0; 3]);
+    }
+
+    #[test]
+    fn random_bits_exact_has_top_bit() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"modular tests");
+        for bits in [1, 2, 63, 64, 65, 128] {
+            for force_odd in [false, true] {
+                let value: [u64; 2] = rng.random_bits_exact(bits, force_odd);
+                let value = u128::from(value[0]) | u128::from(value[1]) << 64;
+                assert_eq!(128 - value.leading_zeros() as usize, bits);
+                assert!(!force_odd || value & 1 == 1);
+            }
+        }
+        // the low bits are not stuck
+        let values: [[u64; 1]; 8] = core::array::from_fn(|_| rng.random_bits_exact(64, false));
+        assert!(values.iter().any(|value| value[0] & 1 == 0));
+    }
+}
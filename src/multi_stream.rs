@@ -0,0 +1,175 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{EntropySource, ManagedRng, RngError, RngState};
+
+/// Domain separation label absorbed into every stream with its name.
+const STREAM_LABEL: &[u8] = b"fast-erasure-shake-rng named stream";
+
+/// A [`ManagedRng`] parent maintaining named output streams, e.g.
+/// `stream("tls")` and `stream("tokens")`, one per subsystem.
+///
+/// Every stream is an independent [`RngState`] seeded with output of the
+/// parent, domain separated by its name. Reseeding the parent refreshes all
+/// streams from the new parent state. Since a stream only ever sees one-way
+/// output of the parent, compromise of one stream's state (or its consumer)
+/// reveals nothing about the parent or the other streams.
+///
+/// Streams persist until removed; their output is not affected by drawing
+/// from other streams.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::{ManagedRng, MultiStreamRng};
+///
+/// let mut rng = MultiStreamRng::new(ManagedRng::builder().build().unwrap());
+/// let session_key = rng.stream("tls").unwrap().get_random_bytes::<32>();
+/// let token = rng.stream("tokens").unwrap().get_random_bytes::<16>();
+/// rng.reseed_with_getrandom().unwrap();
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub struct MultiStreamRng {
+    parent: ManagedRng,
+    streams: Vec<(String, RngState)>,
+}
+
+impl MultiStreamRng {
+    /// Create a multi-stream RNG without streams, deriving them from `parent`.
+    pub fn new(parent: ManagedRng) -> Self {
+        Self {
+            parent,
+            streams: Vec::new(),
+        }
+    }
+
+    /// Seed `stream` named `name` from the parent.
+    fn seed_stream(
+        parent: &mut ManagedRng,
+        name: &str,
+        stream: &mut RngState,
+    ) -> Result<(), RngError> {
+        stream.seed(STREAM_LABEL);
+        stream.seed(name.as_bytes());
+        stream.seed_with_64(|buf| parent.try_fill_random_bytes(buf))
+    }
+
+    /// The stream named `name`, created from the parent on first use.
+    ///
+    /// # Errors
+    /// Returns an error if the stream doesn't exist yet and the parent refuses
+    /// output, see [`ManagedRng::try_fill_random_bytes`].
+    pub fn stream(&mut self, name: &str) -> Result<&mut RngState, RngError> {
+        let index = match self.streams.iter().position(|(n, _)| n == name) {
+            Some(index) => index,
+            None => {
+                let mut stream = RngState::new_unseeded();
+                Self::seed_stream(&mut self.parent, name, &mut stream)?;
+                self.streams.push((String::from(name), stream));
+                self.streams.len() - 1
+            },
+        };
+        Ok(&mut self.streams[index].1)
+    }
+
+    /// Remove the stream named `name`, returning whether it existed. A stream
+    /// of the same name created later is independent of the removed one.
+    pub fn remove_stream(&mut self, name: &str) -> bool {
+        let len = self.streams.len();
+        self.streams.retain(|(n, _)| n != name);
+        self.streams.len() != len
+    }
+
+    /// Names of the existing streams, in order of creation.
+    pub fn stream_names(&self) -> impl Iterator<Item = &str> {
+        self.streams.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Refresh all streams from the parent: absorb fresh parent output into
+    /// every stream.
+    fn refresh(&mut self) -> Result<(), RngError> {
+        for (name, stream) in &mut self.streams {
+            Self::seed_stream(&mut self.parent, name, stream)?;
+        }
+        Ok(())
+    }
+
+    /// Reseed the parent from `source`, see [`ManagedRng::reseed_from`], then
+    /// refresh all streams from it.
+    pub fn reseed_from<S: EntropySource + ?Sized>(
+        &mut self,
+        source: &mut S,
+    ) -> Result<(), RngError> {
+        self.parent.reseed_from(source)?;
+        self.refresh()
+    }
+
+    /// Reseed the parent from the OS RNG, see
+    /// [`ManagedRng::reseed_with_getrandom`], then refresh all streams from
+    /// it.
+    #[cfg(feature = "getrandom")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "getrandom")))]
+    pub fn reseed_with_getrandom(&mut self) -> Result<(), RngError> {
+        self.parent.reseed_with_getrandom()?;
+        self.refresh()
+    }
+
+    /// The parent RNG.
+    pub fn parent(&self) -> &ManagedRng {
+        &self.parent
+    }
+
+    /// Unwrap the parent RNG, dropping all streams.
+    pub fn into_parent(self) -> ManagedRng {
+        self.parent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MultiStreamRng;
+    use crate::{EntropySource, EntropySourceError, ManagedRng};
+
+    struct Fixed;
+
+    impl EntropySource for Fixed {
+        fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+            dest.fill(0x5c);
+            Ok(())
+        }
+    }
+
+    fn multi_stream() -> MultiStreamRng {
+        let mut parent = ManagedRng::new_unseeded();
+        parent.seed(b"multi stream tests");
+        MultiStreamRng::new(parent)
+    }
+
+    #[test]
+    fn streams_are_independent_and_persistent() {
+        let (mut rng, mut reference) = (multi_stream(), multi_stream());
+        let tls = rng.stream("tls").unwrap().get_random_bytes::<32>();
+        assert_ne!(tls, rng.stream("tokens").unwrap().get_random_bytes());
+        // drawing from "tokens" doesn't affect "tls"
+        reference.stream("tls").unwrap().get_random_bytes::<32>();
+        reference.stream("tokens").unwrap();
+        assert_eq!(
+            rng.stream("tls").unwrap().get_random_bytes::<32>(),
+            reference.stream("tls").unwrap().get_random_bytes::<32>()
+        );
+        assert!(rng.stream_names().eq(["tls", "tokens"]));
+        assert!(rng.remove_stream("tls"));
+        assert!(!rng.remove_stream("tls"));
+    }
+
+    #[test]
+    fn reseed_refreshes_streams() {
+        let (mut rng, mut reference) = (multi_stream(), multi_stream());
+        rng.stream("tls").unwrap();
+        reference.stream("tls").unwrap();
+        rng.reseed_from(&mut Fixed).unwrap();
+        assert_ne!(
+            rng.stream("tls").unwrap().get_random_bytes::<32>(),
+            reference.stream("tls").unwrap().get_random_bytes::<32>()
+        );
+    }
+}
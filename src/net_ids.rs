@@ -0,0 +1,112 @@
+use core::net::Ipv6Addr;
+
+use crate::RngState;
+
+/// Bit of the first octet of a MAC address marking it as multicast (I/G bit).
+const MAC_MULTICAST_BIT: u8 = 0b0000_0001;
+/// Bit of the first octet of a MAC address marking it as locally administered
+/// (U/L bit). Also the "universal" bit of a modified EUI-64 interface ID.
+const MAC_LOCAL_BIT: u8 = 0b0000_0010;
+
+/// Whether `iid` is a reserved IPv6 interface identifier (RFC 5453): the
+/// subnet-router anycast ID, the reserved subnet anycast IDs or the proxy
+/// mobile IPv6 IDs.
+fn is_reserved_interface_id(iid: [u8; 8]) -> bool {
+    let iid = u64::from_be_bytes(iid);
+    iid == 0
+        || (0xfdff_ffff_ffff_ff80..=0xfdff_ffff_ffff_ffff).contains(&iid)
+        || (0x0200_5eff_fe00_0000..=0x0200_5eff_fe00_ffff).contains(&iid)
+}
+
+impl RngState {
+    /// Output a random unicast, locally administered MAC address, e.g. for MAC
+    /// address randomization. The RNG MUST be seeded prior to using this
+    /// method.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let mac = rng.random_mac_address();
+    /// assert_eq!(mac[0] & 0b11, 0b10);
+    /// # }
+    /// ```
+    pub fn random_mac_address(&mut self) -> [u8; 6] {
+        let mut mac: [u8; 6] = self.get_random_bytes();
+        mac[0] = (mac[0] & !MAC_MULTICAST_BIT) | MAC_LOCAL_BIT;
+        mac
+    }
+
+    /// Output a random IPv6 interface identifier for a temporary address
+    /// (RFC 4941, section 3.3.1), with the universal/local bit cleared to mark
+    /// it as not globally unique. Reserved interface identifiers (RFC 5453) are
+    /// never returned. The RNG MUST be seeded prior to using this method.
+    pub fn random_ipv6_interface_id(&mut self) -> [u8; 8] {
+        loop {
+            let mut iid: [u8; 8] = self.get_random_bytes();
+            iid[0] &= !MAC_LOCAL_BIT;
+            if !is_reserved_interface_id(iid) {
+                return iid;
+            }
+        }
+    }
+
+    /// Output a temporary IPv6 address in the /64 network of `prefix` (RFC
+    /// 4941): the first 64 bits of `prefix` followed by a random interface
+    /// identifier from [`Self::random_ipv6_interface_id`]. The RNG MUST be
+    /// seeded prior to using this method.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use std::net::Ipv6Addr;
+    ///
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let prefix: Ipv6Addr = "2001:db8:1:2::".parse().unwrap();
+    /// let address = rng.random_temporary_ipv6_address(prefix);
+    /// assert_eq!(address.segments()[..4], prefix.segments()[..4]);
+    /// # }
+    /// ```
+    pub fn random_temporary_ipv6_address(&mut self, prefix: Ipv6Addr) -> Ipv6Addr {
+        let mut octets = prefix.octets();
+        octets[8..].copy_from_slice(&self.random_ipv6_interface_id());
+        Ipv6Addr::from(octets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_reserved_interface_id, MAC_LOCAL_BIT, MAC_MULTICAST_BIT};
+    use crate::RngState;
+
+    #[test]
+    fn reserved_bits() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"net ids tests");
+        for _ in 0..100 {
+            let mac = rng.random_mac_address();
+            assert_eq!(mac[0] & (MAC_LOCAL_BIT | MAC_MULTICAST_BIT), MAC_LOCAL_BIT);
+            let iid = rng.random_ipv6_interface_id();
+            assert_eq!(iid[0] & MAC_LOCAL_BIT, 0);
+            assert!(!is_reserved_interface_id(iid));
+        }
+    }
+
+    #[test]
+    fn reserved_interface_ids() {
+        assert!(is_reserved_interface_id([0; 8]));
+        assert!(is_reserved_interface_id([
+            0xfd, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x80
+        ]));
+        assert!(is_reserved_interface_id([
+            0x02, 0x00, 0x5e, 0xff, 0xfe, 0x00, 0x52, 0x13
+        ]));
+        assert!(!is_reserved_interface_id([
+            0xfd, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f
+        ]));
+    }
+}
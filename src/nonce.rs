@@ -0,0 +1,117 @@
+use crate::xof::Shake256;
+use crate::{RngState, SecretBytes};
+
+/// Domain separation label of deterministic nonces.
+const DETERMINISTIC_LABEL: &[u8] = b"fast-erasure-shake-rng deterministic nonce";
+/// Domain separation label of hedged nonces.
+const HEDGED_LABEL: &[u8] = b"fast-erasure-shake-rng hedged nonce";
+
+/// Length in bytes of the fresh randomness absorbed into a hedged nonce.
+const HEDGE_LEN: usize = 32;
+
+/// Derive a nonce from the secret signing `key`, the `message` (or its hash)
+/// and the domain separation `label`, in the spirit of RFC 6979.
+/// `label` separates schemes and uses, e.g. `b"ecdsa-p256"`.
+///
+/// The nonce is SHAKE256 of the length-prefixed label, key and message, so it
+/// is portable and reproducible, needs no RNG and is unpredictable without
+/// the key. Signing the same message twice reuses the nonce, which is safe for
+/// schemes like (EC)DSA and Schnorr. Deterministic nonces are vulnerable to
+/// fault attacks, see [`RngState::hedged_nonce`] for the alternative.
+///
+/// The nonce is uniformly random bytes; reduce it as the signature scheme
+/// requires, e.g. choose `N` at least 8 bytes longer than the group order so
+/// reduction modulo the order has negligible bias.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::deterministic_nonce;
+///
+/// let key = [0x42; 32];
+/// let k1 = deterministic_nonce::<40>(&key, b"message", b"example-scheme");
+/// let k2 = deterministic_nonce::<40>(&key, b"message", b"example-scheme");
+/// assert_eq!(k1, k2);
+/// ```
+pub fn deterministic_nonce<const N: usize>(
+    key: &[u8],
+    message: &[u8],
+    label: &[u8],
+) -> SecretBytes<N> {
+    let mut xof = Shake256::new();
+    xof.absorb_prefixed(DETERMINISTIC_LABEL);
+    xof.absorb_prefixed(label);
+    xof.absorb_prefixed(key);
+    xof.absorb_prefixed(message);
+    let mut nonce = SecretBytes::new([0; N]);
+    xof.squeeze(nonce.expose_secret_mut());
+    nonce
+}
+
+impl RngState {
+    /// Derive a hedged nonce: like [`deterministic_nonce`], but additionally
+    /// absorbing 32 fresh random bytes from this RNG (RFC 6979 section 3.6).
+    ///
+    /// The nonce stays secure if either the RNG or the key is good: a broken
+    /// RNG degrades it to a deterministic nonce, and unlike deterministic
+    /// nonces it differs for every signature, which defeats fault attacks
+    /// comparing two signatures of the same message. For purely random nonces
+    /// use [`Self::get_secret_bytes`].
+    ///
+    /// The RNG MUST be seeded prior to using this method.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let key = [0x42; 32];
+    /// let k1 = rng.hedged_nonce::<40>(&key, b"message", b"example-scheme");
+    /// let k2 = rng.hedged_nonce::<40>(&key, b"message", b"example-scheme");
+    /// assert_ne!(k1, k2);
+    /// # }
+    /// ```
+    pub fn hedged_nonce<const N: usize>(
+        &mut self,
+        key: &[u8],
+        message: &[u8],
+        label: &[u8],
+    ) -> SecretBytes<N> {
+        let hedge: SecretBytes<HEDGE_LEN> = self.get_secret_bytes();
+        let mut xof = Shake256::new();
+        xof.absorb_prefixed(HEDGED_LABEL);
+        xof.absorb_prefixed(label);
+        xof.absorb_prefixed(key);
+        xof.absorb_prefixed(message);
+        xof.absorb_prefixed(hedge.expose_secret());
+        let mut nonce = SecretBytes::new([0; N]);
+        xof.squeeze(nonce.expose_secret_mut());
+        nonce
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::deterministic_nonce;
+    use crate::RngState;
+
+    #[test]
+    fn deterministic_nonce_depends_on_all_inputs() {
+        let nonce = deterministic_nonce::<32>(b"key", b"message", b"label");
+        assert_eq!(nonce, deterministic_nonce(b"key", b"message", b"label"));
+        assert_ne!(nonce, deterministic_nonce(b"kez", b"message", b"label"));
+        assert_ne!(nonce, deterministic_nonce(b"key", b"messagf", b"label"));
+        assert_ne!(nonce, deterministic_nonce(b"key", b"message", b"labem"));
+        // length prefixes keep the boundaries between inputs unambiguous
+        assert_ne!(nonce, deterministic_nonce(b"keym", b"essage", b"label"));
+    }
+
+    #[test]
+    fn hedged_nonce_differs_from_deterministic() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"nonce tests");
+        let hedged = rng.hedged_nonce::<32>(b"key", b"message", b"label");
+        assert_ne!(hedged, rng.hedged_nonce(b"key", b"message", b"label"));
+        assert_ne!(hedged, deterministic_nonce(b"key", b"message", b"label"));
+    }
+}
@@ -0,0 +1,103 @@
+use core::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+};
+
+use crate::RngState;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Non-zero integer types which can be sampled using
+/// [`RngState::random_nonzero`], i.e. the `NonZero*` types from [`core::num`].
+///
+/// This trait is sealed and can not be implemented outside of this crate.
+pub trait NonZeroInteger: sealed::Sealed + Sized {
+    /// Output a uniformly random non-zero value.
+    #[doc(hidden)]
+    fn random_nonzero(rng: &mut RngState) -> Self;
+}
+
+macro_rules! impl_nonzero_integer {
+    ($($nonzero:ty => $int:ty),* $(,)?) => {
+        $(
+            impl sealed::Sealed for $nonzero {}
+
+            impl NonZeroInteger for $nonzero {
+                fn random_nonzero(rng: &mut RngState) -> Self {
+                    // rejection sampling: a zero is simply discarded, so all non-zero values
+                    // remain equally likely
+                    loop {
+                        let value = <$int>::from_ne_bytes(rng.get_random_bytes());
+                        if let Some(nonzero) = <$nonzero>::new(value) {
+                            return nonzero;
+                        }
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_nonzero_integer! {
+    NonZeroU8 => u8,
+    NonZeroU16 => u16,
+    NonZeroU32 => u32,
+    NonZeroU64 => u64,
+    NonZeroU128 => u128,
+    NonZeroUsize => usize,
+    NonZeroI8 => i8,
+    NonZeroI16 => i16,
+    NonZeroI32 => i32,
+    NonZeroI64 => i64,
+    NonZeroI128 => i128,
+    NonZeroIsize => isize,
+}
+
+impl RngState {
+    /// Output a uniformly random non-zero integer of type `T`, e.g.
+    /// [`NonZeroU32`]. Uses rejection sampling, so every non-zero value is
+    /// equally likely. The RNG MUST be seeded prior to using this method.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use core::num::NonZeroU32;
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let blinding: NonZeroU32 = rng.random_nonzero();
+    /// # }
+    /// ```
+    pub fn random_nonzero<T: NonZeroInteger>(&mut self) -> T {
+        T::random_nonzero(self)
+    }
+
+    /// Output a uniformly random non-zero `u64`. The RNG MUST be seeded prior
+    /// to using this method.
+    ///
+    /// Shorthand for `self.random_nonzero::<NonZeroU64>()`, see
+    /// [`Self::random_nonzero`].
+    pub fn random_nonzero_u64(&mut self) -> NonZeroU64 {
+        self.random_nonzero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::NonZeroU8;
+
+    use crate::RngState;
+
+    #[test]
+    fn random_nonzero_u8() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"nonzero tests");
+        // with 1024 samples from a byte, a zero would be sampled with
+        // overwhelming probability if it wasn't rejected
+        for _ in 0..1024 {
+            let _: NonZeroU8 = rng.random_nonzero();
+        }
+    }
+}
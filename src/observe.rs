@@ -0,0 +1,52 @@
+use crate::RngError;
+
+/// An event in the life of a [`ManagedRng`](crate::ManagedRng), reported to
+/// the observer set with [`RngBuilder::observer`](crate::RngBuilder::observer).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RngEvent<'a> {
+    /// The RNG was built and seeded.
+    Instantiated,
+    /// The RNG was (re)seeded from the entropy source labeled `source`.
+    Reseeded {
+        /// Label of the source, see
+        /// [`EntropySource::label`](crate::EntropySource::label).
+        source: &'a str,
+    },
+    /// Seeding from the entropy source labeled `source` failed, e.g. because
+    /// its output failed a health test.
+    SourceFailed {
+        /// Label of the source, see
+        /// [`EntropySource::label`](crate::EntropySource::label).
+        source: &'a str,
+        /// Why seeding failed.
+        error: RngError,
+    },
+    /// An output request was refused.
+    OutputRefused(RngError),
+    /// The RNG was poisoned, see
+    /// [`ManagedRng::poison`](crate::ManagedRng::poison).
+    Poisoned,
+    /// The RNG was recovered from being poisoned, see
+    /// [`ManagedRng::recover_from`](crate::ManagedRng::recover_from).
+    Recovered,
+}
+
+/// Observer of [`RngEvent`]s, e.g. forwarding them to a logging or tracing
+/// framework.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "getrandom")] {
+/// use fast_erasure_shake_rng::{RngBuilder, RngEvent};
+///
+/// fn log_event(event: &RngEvent<'_>) {
+///     if let RngEvent::SourceFailed { source, error } = event {
+///         eprintln!("entropy source {} failed: {}", source, error);
+///     }
+/// }
+///
+/// let rng = RngBuilder::new().observer(log_event).build().unwrap();
+/// # }
+/// ```
+pub type RngObserver = fn(&RngEvent<'_>);
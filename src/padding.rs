@@ -0,0 +1,109 @@
+use crate::RngState;
+
+/// Padding scheme for [`RngState::random_padded_length`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Padding {
+    /// Pad to the Padmé length, see [`padme_length`]. Deterministic; doesn't
+    /// consume randomness.
+    Padme,
+    /// Pad to a uniformly random length between the message length and its
+    /// Padmé length (both inclusive). Leaks at most as much as [`Self::Padme`]
+    /// while also hiding the exact position inside the Padmé bucket.
+    RandomPadme,
+    /// Pad to a uniformly random length between the message length and the
+    /// next multiple of the contained bucket size (both inclusive).
+    UniformInBucket(u64),
+}
+
+/// Compute the Padmé padded length of a message of `len` bytes, from
+/// "Reducing Metadata Leakage from Encrypted Files and Communication with
+/// PURBs" (Nikitin et al., 2019).
+///
+/// The padded length is the smallest number `>= len` whose binary
+/// representation has its `floor(log2(len)) - floor(log2(floor(log2(len)))) -
+/// 1` lowest bits equal to zero. This leaks only `O(log log len)` bits of
+/// information about the length, at an overhead of at most 12%.
+///
+/// # Panics
+/// Panics if the padded length doesn't fit in an `u64`.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::padme_length;
+///
+/// assert_eq!(padme_length(1000), 1024);
+/// assert_eq!(padme_length(9), 10);
+/// ```
+pub fn padme_length(len: u64) -> u64 {
+    if len < 2 {
+        return len;
+    }
+    let e = 63 - len.leading_zeros();
+    let s = 32 - e.leading_zeros();
+    let mask = (1u64 << (e - s)) - 1;
+    len.checked_add(mask).expect("padded length overflows") & !mask
+}
+
+impl RngState {
+    /// Sample the padded length of a message of `len` bytes according to the
+    /// padding scheme `padding`, to mitigate traffic analysis based on message
+    /// lengths. The RNG MUST be seeded prior to using this method.
+    ///
+    /// # Panics
+    /// Panics if the padded length doesn't fit in an `u64` or if `padding` is
+    /// [`Padding::UniformInBucket`] with a bucket size of zero.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::{Padding, RngState};
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let padded = rng.random_padded_length(1000, Padding::RandomPadme);
+    /// assert!((1000..=1024).contains(&padded));
+    /// # }
+    /// ```
+    pub fn random_padded_length(&mut self, len: u64, padding: Padding) -> u64 {
+        let max = match padding {
+            Padding::Padme => return padme_length(len),
+            Padding::RandomPadme => padme_length(len),
+            Padding::UniformInBucket(bucket) => {
+                assert!(bucket != 0, "bucket size must be non-zero");
+                len.checked_next_multiple_of(bucket)
+                    .expect("padded length overflows")
+            },
+        };
+        match (max - len).checked_add(1) {
+            Some(bound) => len + self.random_u64_below(bound),
+            // `len == 0` and `max == u64::MAX`, so every length is possible
+            None => self.random_u64(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{padme_length, Padding};
+    use crate::RngState;
+
+    #[test]
+    fn padme() {
+        for (len, padded) in [(0, 0), (1, 1), (2, 2), (9, 10), (1000, 1024), (1025, 1088)] {
+            assert_eq!(padme_length(len), padded);
+        }
+    }
+
+    #[test]
+    fn uniform_in_bucket() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"padding tests");
+        for _ in 0..16 {
+            let padded = rng.random_padded_length(130, Padding::UniformInBucket(64));
+            assert!((130..=192).contains(&padded));
+        }
+        assert_eq!(
+            rng.random_padded_length(128, Padding::UniformInBucket(64)),
+            128
+        );
+    }
+}
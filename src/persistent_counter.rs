@@ -0,0 +1,44 @@
+use crate::EntropySourceError;
+
+/// A counter persisted across boots, e.g. in a backup register, EEPROM or
+/// flash, or a real-time clock which never repeats a value.
+///
+/// When passed to
+/// [`RngBuilder::persistent_counter`](crate::RngBuilder::persistent_counter),
+/// the counter is read and incremented once when the RNG is built and its
+/// value is absorbed. This gives devices without a TRNG at least per-boot
+/// uniqueness of their output even when all other inputs repeat, e.g. a fixed
+/// seed from flash. The value adds no entropy.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::{EntropySourceError, PersistentCounter};
+///
+/// /// Boot counter in a battery backed register.
+/// struct BackupRegister(u32);
+///
+/// impl PersistentCounter for BackupRegister {
+///     fn read_and_increment(&mut self) -> Result<u64, EntropySourceError> {
+///         let value = self.0;
+///         self.0 = value
+///             .checked_add(1)
+///             .ok_or(EntropySourceError::new("boot counter exhausted"))?;
+///         Ok(u64::from(value))
+///     }
+/// }
+/// ```
+pub trait PersistentCounter {
+    /// Return the current value of the counter and increment the persisted
+    /// value.
+    ///
+    /// The increment MUST be persisted before returning, otherwise a reset
+    /// before it is written reuses the value. The counter MUST NOT wrap
+    /// around; return an error instead.
+    fn read_and_increment(&mut self) -> Result<u64, EntropySourceError>;
+}
+
+impl<C: PersistentCounter + ?Sized> PersistentCounter for &mut C {
+    fn read_and_increment(&mut self) -> Result<u64, EntropySourceError> {
+        (**self).read_and_increment()
+    }
+}
@@ -0,0 +1,219 @@
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::{RngState, SecretBytes};
+
+/// Single producer, single consumer ring of random blocks.
+struct Ring<const B: usize> {
+    slots: Box<[UnsafeCell<[u8; B]>]>,
+    /// Number of blocks popped so far; only written by the consumer.
+    head: AtomicUsize,
+    /// Number of blocks pushed so far; only written by the producer.
+    tail: AtomicUsize,
+}
+
+// SAFETY: a slot is only written by the producer while it is empty (between
+// `head` and `head + capacity`, not below `tail`) and only read by the
+// consumer while it is full (between `head` and `tail`); the release/acquire
+// pairs on `head` and `tail` order these accesses
+unsafe impl<const B: usize> Sync for Ring<B> {}
+
+impl<const B: usize> Ring<B> {
+    fn slot(&self, index: usize) -> *mut [u8; B] {
+        self.slots[index % self.slots.len()].get()
+    }
+}
+
+impl<const B: usize> Drop for Ring<B> {
+    fn drop(&mut self) {
+        for slot in self.slots.iter_mut() {
+            zeroize::Zeroize::zeroize(slot.get_mut());
+        }
+    }
+}
+
+/// Create a lock-free block pipeline of `capacity` blocks of `B` random bytes,
+/// generated by `rng`, for latency-critical consumers which cannot afford a
+/// Keccak permutation inline (packet pacing, trading systems).
+///
+/// The [`BlockProducer`] keeps the ring topped up, typically on a dedicated
+/// thread (see [`BlockProducer::spawn`]), while the [`BlockConsumer`] pops
+/// blocks wait-free. Popped slots are zeroized immediately, and the ring is
+/// zeroized when both ends are dropped.
+///
+/// Note that blocks waiting in the ring are not forward secure: a compromise
+/// of the process memory reveals up to `capacity` blocks which are yet to be
+/// consumed. Keep `capacity` as small as the latency requirements allow.
+///
+/// # Panics
+/// Panics if `capacity` is zero.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use fast_erasure_shake_rng::{block_pipeline, RngState};
+///
+/// let (producer, mut consumer) =
+///     block_pipeline::<32>(RngState::new_from_getrandom().unwrap(), 64);
+/// let handle = producer.spawn(Duration::from_micros(50));
+/// let _key = loop {
+///     if let Some(block) = consumer.pop() {
+///         break block;
+///     }
+/// };
+/// drop(consumer);
+/// handle.join().unwrap();
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn block_pipeline<const B: usize>(
+    rng: RngState,
+    capacity: usize,
+) -> (BlockProducer<B>, BlockConsumer<B>) {
+    assert!(capacity > 0, "a block pipeline needs a nonzero capacity");
+    let ring = Arc::new(Ring {
+        slots: (0..capacity).map(|_| UnsafeCell::new([0; B])).collect(),
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0),
+    });
+    (
+        BlockProducer {
+            ring: Arc::clone(&ring),
+            rng,
+        },
+        BlockConsumer { ring },
+    )
+}
+
+/// Producing end of a [`block_pipeline`], owning the generating RNG.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub struct BlockProducer<const B: usize> {
+    ring: Arc<Ring<B>>,
+    rng: RngState,
+}
+
+impl<const B: usize> BlockProducer<B> {
+    /// Fill all empty slots of the ring, returning the number of blocks
+    /// generated.
+    pub fn top_up(&mut self) -> usize {
+        let head = self.ring.head.load(Ordering::Acquire);
+        let tail = self.ring.tail.load(Ordering::Relaxed);
+        let free = self.ring.slots.len() - tail.wrapping_sub(head);
+        for index in tail..tail.wrapping_add(free) {
+            // SAFETY: the slot is empty, so the consumer doesn't access it
+            let slot = unsafe { &mut *self.ring.slot(index) };
+            self.rng.fill_random_bytes(slot);
+            self.ring
+                .tail
+                .store(index.wrapping_add(1), Ordering::Release);
+        }
+        free
+    }
+
+    /// Whether the consumer has been dropped.
+    pub fn is_disconnected(&self) -> bool {
+        Arc::strong_count(&self.ring) == 1
+    }
+
+    /// The generating RNG, e.g. for reseeding. Blocks already in the ring are
+    /// not affected.
+    pub fn rng(&mut self) -> &mut RngState {
+        &mut self.rng
+    }
+
+    /// Run the producer on a new thread: top up the ring, sleeping `idle`
+    /// whenever it is full, until the consumer is dropped.
+    pub fn spawn(mut self, idle: Duration) -> JoinHandle<()> {
+        std::thread::spawn(move || {
+            while !self.is_disconnected() {
+                if self.top_up() == 0 {
+                    std::thread::sleep(idle);
+                }
+            }
+        })
+    }
+}
+
+/// Consuming end of a [`block_pipeline`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub struct BlockConsumer<const B: usize> {
+    ring: Arc<Ring<B>>,
+}
+
+impl<const B: usize> BlockConsumer<B> {
+    /// Pop a block from the ring, zeroizing its slot. Wait-free; returns
+    /// `None` if the producer hasn't kept up.
+    pub fn pop(&mut self) -> Option<SecretBytes<B>> {
+        let head = self.ring.head.load(Ordering::Relaxed);
+        if head == self.ring.tail.load(Ordering::Acquire) {
+            return None;
+        }
+        // SAFETY: the slot is full, so the producer doesn't access it
+        let slot = unsafe { &mut *self.ring.slot(head) };
+        let block = SecretBytes::from(*slot);
+        zeroize::Zeroize::zeroize(slot);
+        self.ring
+            .head
+            .store(head.wrapping_add(1), Ordering::Release);
+        Some(block)
+    }
+
+    /// Number of blocks ready to be popped.
+    pub fn available(&self) -> usize {
+        let head = self.ring.head.load(Ordering::Relaxed);
+        self.ring.tail.load(Ordering::Acquire).wrapping_sub(head)
+    }
+
+    /// Whether the producer has been dropped; the remaining blocks can still
+    /// be popped.
+    pub fn is_disconnected(&self) -> bool {
+        Arc::strong_count(&self.ring) == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::block_pipeline;
+    use crate::RngState;
+
+    fn rng() -> RngState {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"pipeline tests");
+        rng
+    }
+
+    #[test]
+    fn blocks_match_rng_output() {
+        let (mut producer, mut consumer) = block_pipeline::<40>(rng(), 3);
+        assert!(consumer.pop().is_none());
+        assert_eq!(producer.top_up(), 3);
+        assert_eq!(producer.top_up(), 0);
+        assert_eq!(consumer.available(), 3);
+        let mut reference = rng();
+        for _ in 0..5 {
+            let block = consumer.pop().unwrap();
+            assert_eq!(*block.expose_secret(), reference.get_random_bytes::<40>());
+            producer.top_up();
+        }
+    }
+
+    #[test]
+    fn spawned_producer_stops_on_disconnect() {
+        let (producer, mut consumer) = block_pipeline::<32>(rng(), 8);
+        let handle = producer.spawn(Duration::from_micros(10));
+        let mut popped = 0;
+        while popped < 100 {
+            if consumer.pop().is_some() {
+                popped += 1;
+            }
+        }
+        drop(consumer);
+        handle.join().unwrap();
+    }
+}
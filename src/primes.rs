@@ -0,0 +1,405 @@
+use crate::modular::ct_lt_limbs;
+use crate::RngState;
+
+/// Number of Miller–Rabin rounds performed on a candidate that passed trial
+/// division. Each round lets a composite pass with probability at most 1/4,
+/// even for adversarially chosen candidates, so this gives an error
+/// probability of at most 2^-128.
+const MILLER_RABIN_ROUNDS: usize = 64;
+
+/// Compute the first `N` primes at compile time.
+const fn small_primes<const N: usize>() -> [u64; N] {
+    let mut primes = [0; N];
+    let mut count = 0;
+    let mut candidate = 2;
+    while count < N {
+        let mut i = 0;
+        let mut is_prime = true;
+        while i < count {
+            if candidate % primes[i] == 0 {
+                is_prime = false;
+                break;
+            }
+            i += 1;
+        }
+        if is_prime {
+            primes[count] = candidate;
+            count += 1;
+        }
+        candidate += 1;
+    }
+    primes
+}
+
+/// Small primes used for trial division of candidates.
+const SMALL_PRIMES: [u64; 128] = small_primes();
+/// Candidates below this bound that pass trial division are prime.
+const TRIAL_DIVISION_BOUND: u64 = SMALL_PRIMES[127] * SMALL_PRIMES[127];
+
+/// Compute `a + b * c + carry`, returning the low and high limb of the result.
+/// Can not overflow.
+#[allow(clippy::cast_possible_truncation)] // splitting a `u128` into its limbs
+fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let wide = u128::from(a) + u128::from(b) * u128::from(c) + u128::from(carry);
+    (wide as u64, (wide >> 64) as u64)
+}
+
+/// Subtract `b` from `a` in place, returning the final borrow.
+fn sub_assign(a: &mut [u64], b: &[u64]) -> bool {
+    let mut borrow = false;
+    for (x, y) in a.iter_mut().zip(b.iter()) {
+        let (diff, borrow1) = x.overflowing_sub(*y);
+        let (diff, borrow2) = diff.overflowing_sub(u64::from(borrow));
+        *x = diff;
+        borrow = borrow1 | borrow2;
+    }
+    borrow
+}
+
+/// Add the single limb `b` to `a` in place, returning the final carry.
+fn add_limb_assign(a: &mut [u64], mut b: u64) -> bool {
+    for x in a.iter_mut() {
+        let (sum, carry) = x.overflowing_add(b);
+        *x = sum;
+        if !carry {
+            return false;
+        }
+        b = 1;
+    }
+    b != 0
+}
+
+/// Shift `a` right by `shift < 64 * a.len()` bits in place.
+fn shr_assign(a: &mut [u64], shift: usize) {
+    let (limbs, bits) = (shift / 64, shift % 64);
+    for i in 0..a.len() {
+        let lo = a.get(i + limbs).copied().unwrap_or(0);
+        let hi = a.get(i + limbs + 1).copied().unwrap_or(0);
+        a[i] = if bits == 0 {
+            lo
+        } else {
+            (lo >> bits) | (hi << (64 - bits))
+        };
+    }
+}
+
+/// Remainder of `a` modulo the small number `p`.
+#[allow(clippy::cast_possible_truncation)] // the remainder is smaller than `p`
+fn rem_small(a: &[u64], p: u64) -> u64 {
+    a.iter().rev().fold(0, |rem, &limb| {
+        (((u128::from(rem) << 64) | u128::from(limb)) % u128::from(p)) as u64
+    })
+}
+
+/// Arithmetic modulo an odd modulus `n > 1` using Montgomery multiplication.
+/// Values are represented as little-endian limbs in Montgomery form, i.e.
+/// `x` is represented by `x * R mod n` with `R = 2^(64 * LIMBS)`.
+struct Montgomery<const LIMBS: usize> {
+    n: [u64; LIMBS],
+    /// `-n^-1 mod 2^64`.
+    n_inv_neg: u64,
+    /// `R mod n`, i.e. 1 in Montgomery form.
+    one: [u64; LIMBS],
+    /// `R^2 mod n`, used to convert into Montgomery form.
+    r2: [u64; LIMBS],
+}
+
+impl<const LIMBS: usize> Montgomery<LIMBS> {
+    fn new(n: &[u64; LIMBS]) -> Self {
+        debug_assert!(n[0] & 1 == 1);
+        // Newton iteration, doubling the number of correct low bits every step;
+        // `n[0]` is its own inverse modulo 8
+        let mut inv = n[0];
+        for _ in 0..5 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(n[0].wrapping_mul(inv)));
+        }
+
+        // compute `R mod n` and `R^2 mod n` by repeated doubling of 1
+        let mut x = [0u64; LIMBS];
+        x[0] = 1;
+        let mut one = [0u64; LIMBS];
+        for i in 0..2 * 64 * LIMBS {
+            if i == 64 * LIMBS {
+                one = x;
+            }
+            let carry = x[LIMBS - 1] >> 63 == 1;
+            shl1_assign(&mut x);
+            if carry || !ct_lt_limbs(&x, n) {
+                sub_assign(&mut x, n);
+            }
+        }
+
+        Self {
+            n: *n,
+            n_inv_neg: inv.wrapping_neg(),
+            one,
+            r2: x,
+        }
+    }
+
+    /// Montgomery product `a * b / R mod n` (CIOS method).
+    fn mul(&self, a: &[u64; LIMBS], b: &[u64; LIMBS]) -> [u64; LIMBS] {
+        let mut t = [0u64; LIMBS];
+        let mut t_hi = 0u64;
+        for &b_i in b.iter() {
+            // t += a * b_i
+            let mut carry = 0;
+            for (t_j, &a_j) in t.iter_mut().zip(a.iter()) {
+                (*t_j, carry) = mac(*t_j, a_j, b_i, carry);
+            }
+            let (sum, overflow) = t_hi.overflowing_add(carry);
+            t_hi = sum;
+
+            // t = (t + m * n) / 2^64, where m is chosen to make the division exact
+            let m = t[0].wrapping_mul(self.n_inv_neg);
+            let (_, mut carry) = mac(t[0], m, self.n[0], 0);
+            for j in 1..LIMBS {
+                (t[j - 1], carry) = mac(t[j], m, self.n[j], carry);
+            }
+            let (sum, overflow2) = t_hi.overflowing_add(carry);
+            t[LIMBS - 1] = sum;
+            t_hi = u64::from(overflow) + u64::from(overflow2);
+        }
+        if t_hi != 0 || !ct_lt_limbs(&t, &self.n) {
+            sub_assign(&mut t, &self.n);
+        }
+        t
+    }
+
+    /// Convert `a < n` into Montgomery form.
+    fn to_montgomery(&self, a: &[u64; LIMBS]) -> [u64; LIMBS] {
+        self.mul(a, &self.r2)
+    }
+
+    /// Compute `base^exp` for `base` in Montgomery form. Variable time.
+    fn pow(&self, base: &[u64; LIMBS], exp: &[u64; LIMBS]) -> [u64; LIMBS] {
+        let mut x = self.one;
+        for &limb in exp.iter().rev() {
+            for bit in (0..64).rev() {
+                x = self.mul(&x, &x);
+                if (limb >> bit) & 1 == 1 {
+                    x = self.mul(&x, base);
+                }
+            }
+        }
+        x
+    }
+}
+
+/// Shift `a` left by one bit in place, discarding the top bit.
+fn shl1_assign(a: &mut [u64]) {
+    let mut carry = 0;
+    for x in a.iter_mut() {
+        let next_carry = *x >> 63;
+        *x = (*x << 1) | carry;
+        carry = next_carry;
+    }
+}
+
+/// Whether `n` fits in a single limb with value below `bound`.
+fn is_small(n: &[u64], bound: u64) -> bool {
+    n[1..].iter().all(|&limb| limb == 0) && n[0] < bound
+}
+
+/// Result of trial division of a candidate.
+enum TrialDivision {
+    /// The candidate is divisible by a small prime (and not equal to it).
+    Composite,
+    /// The candidate is small and has no small prime factor, hence is prime.
+    Prime,
+    /// The candidate has no small prime factor.
+    Unknown,
+}
+
+fn trial_division(n: &[u64]) -> TrialDivision {
+    if is_small(n, 2) {
+        return TrialDivision::Composite;
+    }
+    for &p in SMALL_PRIMES.iter() {
+        if rem_small(n, p) == 0 {
+            return if is_small(n, p + 1) {
+                TrialDivision::Prime
+            } else {
+                TrialDivision::Composite
+            };
+        }
+    }
+    if is_small(n, TRIAL_DIVISION_BOUND) {
+        TrialDivision::Prime
+    } else {
+        TrialDivision::Unknown
+    }
+}
+
+impl RngState {
+    /// Perform the Miller–Rabin test on the odd number `n >= 5` with
+    /// [`MILLER_RABIN_ROUNDS`] random witnesses.
+    fn miller_rabin<const LIMBS: usize>(&mut self, n: &[u64; LIMBS]) -> bool {
+        let mont = Montgomery::new(n);
+        let mut minus_one = *n;
+        sub_assign(&mut minus_one, &mont.one);
+
+        // n - 1 = d * 2^s with d odd
+        let mut d = *n;
+        d[0] -= 1;
+        let s = d
+            .iter()
+            .position(|&limb| limb != 0)
+            .map(|i| 64 * i + d[i].trailing_zeros() as usize)
+            .expect("n > 1");
+        shr_assign(&mut d, s);
+
+        // witnesses are sampled uniformly from 2..=n-2
+        let mut three = [0u64; LIMBS];
+        three[0] = 3;
+        let mut witness_bound = *n;
+        sub_assign(&mut witness_bound, &three);
+        let mut witness = [0u64; LIMBS];
+        'witness: for _ in 0..MILLER_RABIN_ROUNDS {
+            self.fill_random_uint_below(&mut witness, &witness_bound);
+            add_limb_assign(&mut witness, 2);
+            let mut x = mont.pow(&mont.to_montgomery(&witness), &d);
+            if x == mont.one || x == minus_one {
+                continue;
+            }
+            for _ in 1..s {
+                x = mont.mul(&x, &x);
+                if x == minus_one {
+                    continue 'witness;
+                }
+            }
+            return false;
+        }
+        true
+    }
+
+    /// Test whether `n` (as little-endian `u64` limbs) is prime, using trial
+    /// division followed by the Miller–Rabin test with witnesses drawn from
+    /// this RNG. The RNG MUST be seeded prior to using this method.
+    ///
+    /// A prime is always reported as prime. A composite is reported as prime
+    /// with probability at most 2^-128, also for adversarially chosen `n`.
+    ///
+    /// Runs in variable time.
+    #[cfg_attr(docsrs, doc(cfg(feature = "primes")))]
+    pub fn is_probable_prime<const LIMBS: usize>(&mut self, n: &[u64; LIMBS]) -> bool {
+        match trial_division(n) {
+            TrialDivision::Composite => false,
+            TrialDivision::Prime => true,
+            TrialDivision::Unknown => self.miller_rabin(n),
+        }
+    }
+
+    /// Output a uniformly random prime of exactly `bits` bits, as little-endian
+    /// `u64` limbs. The RNG MUST be seeded prior to using this method.
+    ///
+    /// Random odd candidates with the top bit set are generated until one
+    /// passes trial division and the Miller–Rabin test (see
+    /// [`Self::is_probable_prime`]).
+    ///
+    /// Runs in variable time; the running time leaks information about the
+    /// rejected candidates only.
+    ///
+    /// # Panics
+    /// Panics if `bits < 2` or if `bits` exceeds the `64 * LIMBS` bits of the
+    /// output.
+    ///
+    /// # Examples
+    /// ```
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let p: [u64; 4] = rng.random_prime(256);
+    /// assert_eq!(p[3] >> 63, 1);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "primes")))]
+    pub fn random_prime<const LIMBS: usize>(&mut self, bits: usize) -> [u64; LIMBS] {
+        assert!(bits >= 2, "there are no primes of less than 2 bits");
+        let mut candidate = [0u64; LIMBS];
+        loop {
+            self.random_prime_candidate(&mut candidate, bits);
+            if self.is_probable_prime(&candidate) {
+                return candidate;
+            }
+        }
+    }
+
+    /// Output a uniformly random safe prime `p` of exactly `bits` bits, i.e. a
+    /// prime `p` such that `(p - 1) / 2` is prime as well, as little-endian
+    /// `u64` limbs. The RNG MUST be seeded prior to using this method.
+    ///
+    /// Only candidates `p = 3 mod 4` are considered, which excludes only the
+    /// safe prime 5.
+    ///
+    /// Safe primes are rare, so this is much slower than
+    /// [`Self::random_prime`]. Runs in variable time.
+    ///
+    /// # Panics
+    /// Panics if `bits < 3` or if `bits` exceeds the `64 * LIMBS` bits of the
+    /// output.
+    #[cfg_attr(docsrs, doc(cfg(feature = "primes")))]
+    pub fn random_safe_prime<const LIMBS: usize>(&mut self, bits: usize) -> [u64; LIMBS] {
+        assert!(bits >= 3, "there are no safe primes of less than 3 bits");
+        let mut candidate = [0u64; LIMBS];
+        loop {
+            self.random_prime_candidate(&mut candidate, bits);
+            // p = 3 mod 4 so that (p - 1) / 2 is odd
+            candidate[0] |= 0b10;
+            let mut half = candidate;
+            shr_assign(&mut half, 1);
+            // cheap trial division on both before running Miller–Rabin on either
+            if matches!(trial_division(&half), TrialDivision::Composite)
+                || matches!(trial_division(&candidate), TrialDivision::Composite)
+            {
+                continue;
+            }
+            if self.is_probable_prime(&half) && self.is_probable_prime(&candidate) {
+                return candidate;
+            }
+        }
+    }
+
+    /// Fill `out` with a random odd integer of exactly `bits` bits.
+    fn random_prime_candidate(&mut self, out: &mut [u64], bits: usize) {
+        self.fill_random_uint_exact_bits(out, bits, true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RngState;
+
+    fn test_rng() -> RngState {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"primes tests");
+        rng
+    }
+
+    #[test]
+    fn known_primes_and_composites() {
+        let mut rng = test_rng();
+        // small primes and composites, including Carmichael numbers and strong
+        // pseudoprimes to the bases 2, 3, 5 and 7
+        for p in [2, 3, 5, 719, 727, (1 << 61) - 1, u64::MAX - 58] {
+            assert!(rng.is_probable_prime(&[p, 0]), "{} is prime", p);
+        }
+        for c in [0, 1, 4, 561, 41041, 3215031751, u64::MAX] {
+            assert!(!rng.is_probable_prime(&[c, 0]), "{} is composite", c);
+        }
+        // 2^127 - 1 is prime, 2^128 - 1 is not
+        assert!(rng.is_probable_prime(&[u64::MAX, u64::MAX >> 1]));
+        assert!(!rng.is_probable_prime(&[u64::MAX, u64::MAX]));
+    }
+
+    #[test]
+    fn random_primes() {
+        let mut rng = test_rng();
+        let p: [u64; 2] = rng.random_prime(100);
+        assert_eq!(p[1] >> 35, 1);
+        let q: [u64; 1] = rng.random_safe_prime(40);
+        assert_eq!(q[0] >> 39, 1);
+        assert!(rng.is_probable_prime(&[q[0] >> 1]));
+        let small: [u64; 1] = rng.random_safe_prime(3);
+        assert_eq!(small, [7]);
+    }
+}
@@ -0,0 +1,155 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::panic::Location;
+use std::sync::Mutex;
+
+/// Usage per call site, recorded while profiling.
+static USAGE: Mutex<BTreeMap<&'static Location<'static>, CallSiteUsage>> =
+    Mutex::new(BTreeMap::new());
+
+/// RNG usage attributed to one call site, see [`profiling_report`].
+#[cfg_attr(docsrs, doc(cfg(feature = "profiling")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CallSiteUsage {
+    /// Source location of the call site.
+    pub location: &'static Location<'static>,
+    /// Number of output requests.
+    pub calls: u64,
+    /// Number of output bytes requested.
+    pub bytes: u64,
+    /// Number of applications of the permutation consumed by the requests.
+    pub permutations: u64,
+}
+
+std::thread_local! {
+    /// Number of applications of the permutation on this thread.
+    static PERMUTATIONS: Cell<u64> = const { Cell::new(0) };
+    /// Whether a [`Span`] is active on this thread.
+    static IN_SPAN: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Count an application of the permutation to an RNG state.
+pub(crate) fn count_permutation() {
+    PERMUTATIONS.with(|count| count.set(count.get() + 1));
+}
+
+/// An output request being profiled, recorded on drop. Nested spans, e.g. of
+/// a [`ManagedRng`](crate::ManagedRng) request calling into its
+/// [`RngState`](crate::RngState), are attributed to the outermost span.
+pub(crate) struct Span {
+    /// `None` for nested spans.
+    start: Option<(&'static Location<'static>, u64)>,
+    bytes: usize,
+}
+
+impl Span {
+    /// Start profiling an output request of `bytes` bytes, attributed to the
+    /// calling location.
+    #[track_caller]
+    pub(crate) fn start(bytes: usize) -> Self {
+        let outermost = !IN_SPAN.with(|in_span| in_span.replace(true));
+        // `Location::caller` must not be called in a closure
+        let location = Location::caller();
+        Self {
+            start: outermost.then(|| (location, PERMUTATIONS.with(Cell::get))),
+            bytes,
+        }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let Some((location, start)) = self.start else {
+            return;
+        };
+        IN_SPAN.with(|in_span| in_span.set(false));
+        let permutations = PERMUTATIONS.with(Cell::get) - start;
+        let mut usage = USAGE.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = usage.entry(location).or_insert(CallSiteUsage {
+            location,
+            calls: 0,
+            bytes: 0,
+            permutations: 0,
+        });
+        entry.calls += 1;
+        entry.bytes += self.bytes as u64;
+        entry.permutations += permutations;
+    }
+}
+
+/// Report the RNG usage per call site recorded so far, sorted by number of
+/// permutations consumed, most expensive first.
+///
+/// Output requests of [`RngState`](crate::RngState) and
+/// [`ManagedRng`](crate::ManagedRng) are attributed to their caller using
+/// `#[track_caller]`. Requests made by helpers of this crate (e.g. uniform
+/// integers or shuffling) are attributed to the location inside the helper.
+///
+/// Profiling takes a global lock on every output request; don't enable the
+/// `profiling` feature in production builds.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::{profiling_report, RngState};
+///
+/// let mut rng = RngState::new_from_getrandom().unwrap();
+/// let key = rng.get_random_bytes::<32>();
+/// for usage in profiling_report() {
+///     println!(
+///         "{}: {} calls, {} bytes, {} permutations",
+///         usage.location, usage.calls, usage.bytes, usage.permutations
+///     );
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "profiling")))]
+pub fn profiling_report() -> Vec<CallSiteUsage> {
+    let usage = USAGE.lock().unwrap_or_else(|e| e.into_inner());
+    let mut report: Vec<_> = usage.values().copied().collect();
+    report.sort_by_key(|usage| core::cmp::Reverse(usage.permutations));
+    report
+}
+
+/// Discard all usage recorded so far.
+#[cfg_attr(docsrs, doc(cfg(feature = "profiling")))]
+pub fn reset_profiling() {
+    USAGE.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{profiling_report, CallSiteUsage};
+    use crate::{ManagedRng, OutputPolicy, RngState};
+
+    /// Usage recorded for `line` of this file; other tests record
+    /// concurrently, so only look at specific call sites.
+    fn usage_at(line: u32) -> CallSiteUsage {
+        profiling_report()
+            .into_iter()
+            .find(|usage| usage.location.file() == file!() && usage.location.line() == line)
+            .unwrap()
+    }
+
+    #[test]
+    fn attributes_to_call_site() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"profiling tests");
+        let line = line!() + 1;
+        let _ = rng.get_random_bytes::<300>();
+        let usage = usage_at(line);
+        // first block, then two more blocks of 136 bytes
+        assert_eq!((usage.calls, usage.bytes, usage.permutations), (1, 300, 3));
+    }
+
+    #[test]
+    fn managed_requests_are_counted_once() {
+        let mut rng = ManagedRng::new_unseeded();
+        rng.output_policy = OutputPolicy::Conservative;
+        rng.seed(b"profiling tests");
+        let line = line!() + 1;
+        let _ = rng.get_random_bytes::<100>();
+        let usage = usage_at(line);
+        // two blocks of the rate
+        assert_eq!((usage.calls, usage.bytes, usage.permutations), (1, 100, 2));
+    }
+}
@@ -0,0 +1,217 @@
+#![allow(deprecated)] // `SipHasher` is deprecated only in favour of std's unnameable hasher
+
+use core::fmt;
+use core::hash::{Hasher, SipHasher};
+
+use zeroize::Zeroize;
+
+use crate::RngState;
+
+/// Number of Feistel rounds.
+const ROUNDS: usize = 8;
+
+/// A keyed pseudorandom permutation of `0..n`, for any `n` up to `u64::MAX`.
+///
+/// The permutation is a balanced Feistel network with SipHash-2-4 round
+/// functions over the smallest even number of bits covering `n`, restricted to
+/// `0..n` by cycle walking. It takes constant space, so it can enumerate a
+/// random non-repeating sequence of indices lazily, e.g. to hand out unique
+/// non-sequential IDs or to visit the items of a huge collection in random
+/// order without materializing a shuffle. The keys are zeroized on drop.
+///
+/// Unlike [`RngState::random_permutation`], the permutation is not uniformly
+/// random among all permutations of `0..n`; it is only indistinguishable from
+/// one without knowledge of the keys.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "getrandom")] {
+/// use fast_erasure_shake_rng::{IndexPermutation, RngState};
+///
+/// let mut rng = RngState::new_from_getrandom().unwrap();
+/// let permutation = IndexPermutation::from_rng(&mut rng, 1_000_000_000);
+/// let ids: Vec<u64> = permutation.iter().take(3).collect();
+/// assert_eq!(permutation.inverse(ids[1]), 1);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct IndexPermutation {
+    n: u64,
+    /// Number of bits of each Feistel half.
+    half_bits: u32,
+    keys: [(u64, u64); ROUNDS],
+}
+
+impl IndexPermutation {
+    /// Create a permutation of `0..n` with random keys from `rng`.
+    pub fn from_rng(rng: &mut RngState, n: u64) -> Self {
+        let bits = 64 - n.saturating_sub(1).leading_zeros();
+        Self {
+            n,
+            half_bits: core::cmp::max(bits.div_ceil(2), 1),
+            keys: core::array::from_fn(|_| rng.hasher_keys()),
+        }
+    }
+
+    /// Size `n` of the domain `0..n`.
+    pub fn len(&self) -> u64 {
+        self.n
+    }
+
+    /// Whether the domain is empty.
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Mask of a Feistel half.
+    fn half_mask(&self) -> u64 {
+        u64::MAX >> (64 - self.half_bits)
+    }
+
+    /// Round function of round `round`.
+    fn round(&self, round: usize, half: u64) -> u64 {
+        let (k0, k1) = self.keys[round];
+        let mut hasher = SipHasher::new_with_keys(k0, k1);
+        hasher.write_u64(half);
+        hasher.finish() & self.half_mask()
+    }
+
+    /// Feistel network over `2 * half_bits` bits.
+    fn encrypt(&self, x: u64) -> u64 {
+        let (mut left, mut right) = (x >> self.half_bits, x & self.half_mask());
+        for round in 0..ROUNDS {
+            (left, right) = (right, left ^ self.round(round, right));
+        }
+        (left << self.half_bits) | right
+    }
+
+    /// Inverse of [`Self::encrypt`].
+    fn decrypt(&self, y: u64) -> u64 {
+        let (mut left, mut right) = (y >> self.half_bits, y & self.half_mask());
+        for round in (0..ROUNDS).rev() {
+            (left, right) = (right ^ self.round(round, left), left);
+        }
+        (left << self.half_bits) | right
+    }
+
+    /// Image of `index` under the permutation.
+    ///
+    /// # Panics
+    /// Panics if `index >= n`.
+    pub fn permute(&self, index: u64) -> u64 {
+        assert!(index < self.n, "index out of the permuted domain");
+        // the Feistel domain is less than 4 times larger than `0..n`, so this
+        // takes less than 4 iterations on average
+        let mut y = self.encrypt(index);
+        while y >= self.n {
+            y = self.encrypt(y);
+        }
+        y
+    }
+
+    /// Preimage of `value` under the permutation, i.e. the index `i` with
+    /// `permute(i) == value`.
+    ///
+    /// # Panics
+    /// Panics if `value >= n`.
+    pub fn inverse(&self, value: u64) -> u64 {
+        assert!(value < self.n, "value out of the permuted domain");
+        let mut x = self.decrypt(value);
+        while x >= self.n {
+            x = self.decrypt(x);
+        }
+        x
+    }
+
+    /// Iterate over the images of `0..n`, i.e. over all of `0..n` in
+    /// pseudorandom order without repetitions.
+    pub fn iter(&self) -> PermutedIndices<'_> {
+        PermutedIndices {
+            permutation: self,
+            next: 0,
+        }
+    }
+}
+
+impl Drop for IndexPermutation {
+    fn drop(&mut self) {
+        for (k0, k1) in self.keys.iter_mut() {
+            k0.zeroize();
+            k1.zeroize();
+        }
+    }
+}
+
+impl fmt::Debug for IndexPermutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndexPermutation")
+            .field("n", &self.n)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Iterator over the images of an [`IndexPermutation`], see
+/// [`IndexPermutation::iter`].
+#[derive(Clone, Debug)]
+pub struct PermutedIndices<'a> {
+    permutation: &'a IndexPermutation,
+    next: u64,
+}
+
+impl Iterator for PermutedIndices<'_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.next == self.permutation.n {
+            return None;
+        }
+        let value = self.permutation.permute(self.next);
+        self.next += 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.permutation.n - self.next;
+        match usize::try_from(remaining) {
+            Ok(remaining) => (remaining, Some(remaining)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+}
+
+impl core::iter::FusedIterator for PermutedIndices<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::IndexPermutation;
+    use crate::RngState;
+
+    #[test]
+    fn is_permutation() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"prp tests");
+        for n in [1, 2, 3, 17, 1000] {
+            let permutation = IndexPermutation::from_rng(&mut rng, n);
+            let mut seen = [false; 1000];
+            for (i, value) in (0..).zip(permutation.iter()) {
+                let slot = &mut seen[usize::try_from(value).unwrap()];
+                assert!(!*slot);
+                *slot = true;
+                assert_eq!(permutation.inverse(value), i);
+            }
+            assert!(seen[..usize::try_from(n).unwrap()].iter().all(|&s| s));
+        }
+        assert_eq!(IndexPermutation::from_rng(&mut rng, 0).iter().next(), None);
+    }
+
+    #[test]
+    fn huge_domain() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"prp tests");
+        let permutation = IndexPermutation::from_rng(&mut rng, u64::MAX);
+        let value = permutation.permute(u64::MAX - 1);
+        assert!(value < u64::MAX);
+        assert_eq!(permutation.inverse(value), u64::MAX - 1);
+        assert_ne!(permutation.permute(0), 0);
+    }
+}
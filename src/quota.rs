@@ -0,0 +1,175 @@
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::limits::optimal_request_size;
+use crate::{RawSpinLock, SyncRngState};
+
+/// Maximum number of bytes output while holding the lock of the shared RNG,
+/// so large requests can't monopolize it.
+const MAX_LOCKED_CHUNK: usize = optimal_request_size(4096);
+
+/// Error returned by [`QuotaRng::fill`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum QuotaError {
+    /// The handle was revoked by the host.
+    Revoked,
+    /// The request exceeds the remaining budget.
+    Exhausted {
+        /// Number of bytes requested.
+        requested: usize,
+        /// Remaining budget in bytes.
+        remaining: usize,
+    },
+}
+
+impl fmt::Display for QuotaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Revoked => f.write_str("RNG handle was revoked"),
+            Self::Exhausted {
+                requested,
+                remaining,
+            } => write!(
+                f,
+                "request for {} bytes exceeds the remaining RNG budget of {} bytes",
+                requested, remaining
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for QuotaError {}
+
+/// A handle to a shared RNG with a total output budget, which can be revoked,
+/// for handing randomness to untrusted plugins or scripts.
+///
+/// Requests are all-or-nothing: a request exceeding the remaining budget fails
+/// without output. Large requests are served in chunks, releasing the lock of
+/// the shared RNG in between, so a plugin can't monopolize it. All methods
+/// take `&self`, so the host can share the handle with the plugin and keep
+/// a reference to revoke it.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "getrandom")] {
+/// use fast_erasure_shake_rng::{AtomicSpinLock, QuotaError, QuotaRng, RngState, SyncRngState};
+///
+/// let shared = SyncRngState::<AtomicSpinLock>::from(RngState::new_from_getrandom().unwrap());
+/// let plugin_rng = QuotaRng::new(&shared, 1024);
+/// let mut nonce = [0; 24];
+/// plugin_rng.fill(&mut nonce).unwrap();
+/// assert_eq!(plugin_rng.remaining(), 1000);
+///
+/// plugin_rng.revoke();
+/// assert_eq!(plugin_rng.fill(&mut nonce), Err(QuotaError::Revoked));
+/// # }
+/// ```
+pub struct QuotaRng<'a, L: RawSpinLock> {
+    rng: &'a SyncRngState<L>,
+    remaining: AtomicUsize,
+    revoked: AtomicBool,
+}
+
+impl<'a, L: RawSpinLock> QuotaRng<'a, L> {
+    /// Create a handle to `rng` which may output at most `budget` bytes in
+    /// total. `rng` MUST contain a seeded RNG.
+    pub fn new(rng: &'a SyncRngState<L>, budget: usize) -> Self {
+        Self {
+            rng,
+            remaining: AtomicUsize::new(budget),
+            revoked: AtomicBool::new(false),
+        }
+    }
+
+    /// Fill `dest` with random bytes from the shared RNG, charging
+    /// `dest.len()` bytes to the budget.
+    ///
+    /// # Errors
+    /// Fails without output if the handle was revoked or if the request
+    /// exceeds the remaining budget.
+    ///
+    /// # Panics
+    /// Panics if the shared [`SyncRngState`] is empty.
+    pub fn fill(&self, dest: &mut [u8]) -> Result<(), QuotaError> {
+        if self.is_revoked() {
+            return Err(QuotaError::Revoked);
+        }
+        self.remaining
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |remaining| {
+                remaining.checked_sub(dest.len())
+            })
+            .map_err(|remaining| QuotaError::Exhausted {
+                requested: dest.len(),
+                remaining,
+            })?;
+        for chunk in dest.chunks_mut(MAX_LOCKED_CHUNK) {
+            self.rng.with(|rng| rng.fill_random_bytes(chunk));
+        }
+        Ok(())
+    }
+
+    /// Remaining budget in bytes.
+    pub fn remaining(&self) -> usize {
+        self.remaining.load(Ordering::Acquire)
+    }
+
+    /// Revoke the handle: all later requests fail with
+    /// [`QuotaError::Revoked`]. Requests already in progress complete.
+    pub fn revoke(&self) {
+        self.revoked.store(true, Ordering::Release);
+    }
+
+    /// Whether the handle was revoked.
+    pub fn is_revoked(&self) -> bool {
+        self.revoked.load(Ordering::Acquire)
+    }
+}
+
+impl<L: RawSpinLock> fmt::Debug for QuotaRng<'_, L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuotaRng")
+            .field("remaining", &self.remaining())
+            .field("revoked", &self.is_revoked())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(all(test, target_has_atomic = "8"))]
+mod tests {
+    use super::{QuotaError, QuotaRng};
+    use crate::{AtomicSpinLock, RngState, SyncRngState};
+
+    fn shared() -> SyncRngState<AtomicSpinLock> {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"quota tests");
+        SyncRngState::from(rng)
+    }
+
+    #[test]
+    fn budget_is_enforced() {
+        let shared = shared();
+        let quota = QuotaRng::new(&shared, 10_000);
+        let mut buf = [0; 9_000];
+        quota.fill(&mut buf).unwrap();
+        assert_ne!(buf[8_900..], [0; 100]);
+        assert_eq!(
+            quota.fill(&mut buf[..1_001]),
+            Err(QuotaError::Exhausted {
+                requested: 1_001,
+                remaining: 1_000
+            })
+        );
+        quota.fill(&mut buf[..1_000]).unwrap();
+        assert_eq!(quota.remaining(), 0);
+    }
+
+    #[test]
+    fn revocation() {
+        let shared = shared();
+        let quota = QuotaRng::new(&shared, 100);
+        quota.revoke();
+        assert_eq!(quota.fill(&mut [0; 1]), Err(QuotaError::Revoked));
+        assert_eq!(quota.remaining(), 100);
+    }
+}
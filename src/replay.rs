@@ -0,0 +1,103 @@
+use alloc::vec::Vec;
+
+use zeroize::Zeroizing;
+
+use crate::EntropySourceError;
+
+/// Log of all entropy a [`ManagedRng`](crate::ManagedRng) obtained from its
+/// entropy sources, in order, for reconstructing an identical RNG while
+/// debugging. NOT FOR PRODUCTION USE.
+///
+/// Capture a log using
+/// [`RngBuilder::capture_replay`](crate::RngBuilder::capture_replay),
+/// and clone it. Feeding the log to
+/// [`RngBuilder::replay`](crate::RngBuilder::replay) with the same
+/// configuration makes the new RNG take its entropy from the log instead of
+/// its sources, so it produces exactly the same output as the captured RNG
+/// given the same sequence of calls.
+///
+/// The log only lives in memory: it can't be exported, as the contained
+/// entropy must not be written out unencrypted.
+///
+/// # Security
+/// The log contains all seeding material: anyone who can read it can
+/// reconstruct every output of the captured RNG. A replayed RNG produces
+/// output which has been produced before. Only use this to reproduce bugs in
+/// test environments.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::RngBuilder;
+///
+/// let mut rng = RngBuilder::new().capture_replay().build().unwrap();
+/// let output = rng.get_random_bytes::<32>();
+/// let log = rng.replay_log().unwrap().clone();
+///
+/// let mut replayed = RngBuilder::new().replay(log).build().unwrap();
+/// assert_eq!(replayed.get_random_bytes::<32>(), output);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "replay")))]
+#[derive(Clone)]
+pub struct ReplayLog {
+    entropy: Zeroizing<Vec<u8>>,
+    /// Position of the next entropy to play back.
+    position: usize,
+}
+
+impl ReplayLog {
+    pub(crate) fn new() -> Self {
+        Self {
+            entropy: Zeroizing::new(Vec::new()),
+            position: 0,
+        }
+    }
+
+    /// Number of entropy bytes in the log.
+    pub fn len(&self) -> usize {
+        self.entropy.len()
+    }
+
+    /// Whether the log contains no entropy.
+    pub fn is_empty(&self) -> bool {
+        self.entropy.is_empty()
+    }
+
+    /// Append entropy obtained from a source.
+    pub(crate) fn record(&mut self, entropy: &[u8]) {
+        self.entropy.extend_from_slice(entropy);
+    }
+
+    /// Fill `dest` with the next entropy from the log.
+    pub(crate) fn play(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+        let next = self
+            .entropy
+            .get(self.position..self.position + dest.len())
+            .ok_or(EntropySourceError::new("replay log exhausted"))?;
+        dest.copy_from_slice(next);
+        self.position += dest.len();
+        Ok(())
+    }
+}
+
+/// Whether a managed RNG captures its entropy into a log or plays it back.
+pub(crate) enum ReplayMode {
+    Capture(ReplayLog),
+    Playback(ReplayLog),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReplayLog;
+
+    #[test]
+    fn play_back_recorded_entropy() {
+        let mut log = ReplayLog::new();
+        log.record(&[1; 60]);
+        log.record(&[2; 40]);
+        let mut buf = [0; 100];
+        log.play(&mut buf).unwrap();
+        assert_eq!(buf[..60], [1; 60]);
+        assert_eq!(buf[60..], [2; 40]);
+        assert!(log.play(&mut buf[..1]).is_err());
+    }
+}
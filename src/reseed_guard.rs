@@ -0,0 +1,133 @@
+use core::ops::{Deref, DerefMut};
+
+use crate::{EntropySource, EntropySourceError, RngState};
+
+/// Scope guard returned by [`RngState::reseed_on_drop`], which reseeds the RNG
+/// from an entropy source when it goes out of scope.
+///
+/// The guard dereferences to the guarded [`RngState`], so the RNG can be used
+/// through it while the guard is alive.
+#[must_use = "the RNG is reseeded as soon as the guard is dropped"]
+pub struct ReseedGuard<'r, S: EntropySource> {
+    rng: &'r mut RngState,
+    /// `None` once the reseed has been performed by [`Self::finish`].
+    source: Option<S>,
+}
+
+impl<'r, S: EntropySource> ReseedGuard<'r, S> {
+    /// Reseed the RNG now and dispose of the guard, reporting whether the
+    /// entropy source succeeded.
+    pub fn finish(mut self) -> Result<(), EntropySourceError> {
+        self.reseed()
+    }
+
+    fn reseed(&mut self) -> Result<(), EntropySourceError> {
+        match self.source.take() {
+            Some(mut source) => self.rng.seed_from_entropy_source(&mut source),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<S: EntropySource> Deref for ReseedGuard<'_, S> {
+    type Target = RngState;
+
+    fn deref(&self) -> &RngState {
+        self.rng
+    }
+}
+
+impl<S: EntropySource> DerefMut for ReseedGuard<'_, S> {
+    fn deref_mut(&mut self) -> &mut RngState {
+        self.rng
+    }
+}
+
+impl<S: EntropySource> Drop for ReseedGuard<'_, S> {
+    fn drop(&mut self) {
+        // errors can't be reported from `drop`; `finish` exists for callers who
+        // need to observe them
+        let _ = self.reseed();
+    }
+}
+
+impl RngState {
+    /// Return a guard which reseeds the RNG from `source` when it goes out of
+    /// scope, including during unwinding. Code handling especially sensitive
+    /// material can use this to ensure an attacker who later learns the RNG
+    /// state can't reconstruct the output produced inside the scope, nor
+    /// predict the output after it.
+    ///
+    /// If the entropy source fails when the guard is dropped the failure is
+    /// silently ignored; use [`ReseedGuard::finish`] at the end of the scope
+    /// to observe it.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::{OsEntropy, RngState};
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// {
+    ///     let mut guard = rng.reseed_on_drop(OsEntropy);
+    ///     let session_key = guard.get_random_bytes::<32>();
+    ///     // ... use the session key ...
+    /// } // reseeded here
+    /// # }
+    /// ```
+    pub fn reseed_on_drop<S: EntropySource>(&mut self, source: S) -> ReseedGuard<'_, S> {
+        ReseedGuard {
+            rng: self,
+            source: Some(source),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EntropySource, EntropySourceError, RngState};
+
+    struct CountingSource<'a>(&'a mut usize);
+
+    impl EntropySource for CountingSource<'_> {
+        fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+            *self.0 += 1;
+            dest.fill(0x5a);
+            Ok(())
+        }
+    }
+
+    fn test_rng() -> RngState {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"reseed guard tests");
+        rng
+    }
+
+    #[test]
+    fn reseeds_on_drop() {
+        let mut calls = 0;
+        let (mut rng, mut reference) = (test_rng(), test_rng());
+        {
+            let mut guard = rng.reseed_on_drop(CountingSource(&mut calls));
+            assert_eq!(
+                guard.get_random_bytes::<16>(),
+                reference.get_random_bytes::<16>()
+            );
+        }
+        assert_eq!(calls, 1);
+        assert_ne!(
+            rng.get_random_bytes::<16>(),
+            reference.get_random_bytes::<16>()
+        );
+    }
+
+    #[test]
+    fn finish_reseeds_once() {
+        let mut calls = 0;
+        let mut rng = test_rng();
+        rng.reseed_on_drop(CountingSource(&mut calls))
+            .finish()
+            .unwrap();
+        assert_eq!(calls, 1);
+    }
+}
@@ -0,0 +1,216 @@
+//! C-callable glue exposing a system-wide instance of this RNG to RTOS based
+//! firmware, e.g. as Zephyr entropy driver or as FreeRTOS random number
+//! provider.
+//!
+//! The firmware initializes the system RNG once with hardware entropy using
+//! [`fesr_init`], after which tasks obtain random bytes with
+//! [`fesr_get_entropy`] and interrupt handlers with [`fesr_get_entropy_isr`].
+//! All functions return 0 (or the number of bytes written) on success and a
+//! negative `errno` value on failure, following the Zephyr driver
+//! conventions. The C declarations are:
+//!
+//! ```c
+//! int fesr_init(const uint8_t *seed, size_t len);
+//! int fesr_add_entropy(const uint8_t *data, size_t len);
+//! int fesr_get_entropy(uint8_t *buf, size_t len);
+//! int fesr_get_entropy_isr(uint8_t *buf, size_t len, uint32_t flags);
+//! ```
+//!
+//! A Zephyr entropy driver then consists of:
+//!
+//! ```c
+//! static int fesr_driver_get_entropy(const struct device *dev, uint8_t *buf, uint16_t len)
+//! {
+//!     return fesr_get_entropy(buf, len);
+//! }
+//!
+//! static int fesr_driver_get_entropy_isr(const struct device *dev, uint8_t *buf, uint16_t len,
+//!                                        uint32_t flags)
+//! {
+//!     return fesr_get_entropy_isr(buf, len, flags);
+//! }
+//!
+//! static const struct entropy_driver_api fesr_driver_api = {
+//!     .get_entropy = fesr_driver_get_entropy,
+//!     .get_entropy_isr = fesr_driver_get_entropy_isr,
+//! };
+//! ```
+//!
+//! On FreeRTOS, e.g. the FreeRTOS+TCP `xApplicationGetRandomNumber` hook can
+//! call [`fesr_get_entropy`].
+
+use core::ffi::c_int;
+
+use crate::{AtomicSpinLock, RngState, SyncRngState};
+
+/// The system RNG instance.
+static SYSTEM_RNG: SyncRngState<AtomicSpinLock> = SyncRngState::new();
+
+/// Minimum length of the seed passed to [`fesr_init`].
+pub const MIN_SEED_LEN: usize = 32;
+
+/// `errno` value for invalid arguments.
+const EINVAL: c_int = 22;
+/// `errno` value for a busy resource.
+const EBUSY: c_int = 16;
+/// `errno` value for a missing device, i.e. an uninitialized RNG.
+const ENODEV: c_int = 19;
+
+/// View the C buffer `ptr` of `len` bytes as slice, or `None` if `ptr` is null
+/// while `len` is non-zero.
+///
+/// # Safety
+/// `ptr` must be null or valid for reads of `len` bytes.
+unsafe fn slice_from_c<'a>(ptr: *const u8, len: usize) -> Option<&'a [u8]> {
+    if len == 0 {
+        Some(&[])
+    } else if ptr.is_null() {
+        None
+    } else {
+        // SAFETY: `ptr` is non-null, so valid for reads of `len` bytes
+        Some(unsafe { core::slice::from_raw_parts(ptr, len) })
+    }
+}
+
+/// Mutable counterpart of [`slice_from_c`].
+///
+/// # Safety
+/// `ptr` must be null or valid for writes of `len` bytes.
+unsafe fn slice_from_c_mut<'a>(ptr: *mut u8, len: usize) -> Option<&'a mut [u8]> {
+    if len == 0 {
+        Some(&mut [])
+    } else if ptr.is_null() {
+        None
+    } else {
+        // SAFETY: `ptr` is non-null, so valid for writes of `len` bytes
+        Some(unsafe { core::slice::from_raw_parts_mut(ptr, len) })
+    }
+}
+
+/// (Re)initialize the system RNG with `len` bytes of hardware entropy at
+/// `seed`. Returns 0 on success, or `-EINVAL` if `seed` is null or shorter
+/// than [`MIN_SEED_LEN`] bytes.
+///
+/// # Safety
+/// `seed` must be valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn fesr_init(seed: *const u8, len: usize) -> c_int {
+    // SAFETY: guaranteed by the caller
+    match unsafe { slice_from_c(seed, len) } {
+        Some(seed) if seed.len() >= MIN_SEED_LEN => {
+            let mut rng = RngState::new_unseeded();
+            rng.seed(seed);
+            SYSTEM_RNG.set(rng);
+            0
+        },
+        _ => -EINVAL,
+    }
+}
+
+/// Hash the additional seeding material at `data` into the system RNG.
+/// Returns 0 on success, `-EINVAL` if `data` is null or `-ENODEV` if the
+/// system RNG is not initialized. Spins while the RNG is in use, so don't
+/// call this from interrupt handlers.
+///
+/// # Safety
+/// `data` must be valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn fesr_add_entropy(data: *const u8, len: usize) -> c_int {
+    // SAFETY: guaranteed by the caller
+    let Some(data) = (unsafe { slice_from_c(data, len) }) else {
+        return -EINVAL;
+    };
+    SYSTEM_RNG.with_slot(|slot| match slot {
+        Some(rng) => {
+            rng.seed(data);
+            0
+        },
+        None => -ENODEV,
+    })
+}
+
+/// Fill the `len` bytes at `buf` with random bytes from the system RNG.
+/// Returns 0 on success, `-EINVAL` if `buf` is null or `-ENODEV` if the system
+/// RNG is not initialized. Spins while the RNG is in use, so don't call this
+/// from interrupt handlers.
+///
+/// # Safety
+/// `buf` must be valid for writes of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn fesr_get_entropy(buf: *mut u8, len: usize) -> c_int {
+    // SAFETY: guaranteed by the caller
+    let Some(buf) = (unsafe { slice_from_c_mut(buf, len) }) else {
+        return -EINVAL;
+    };
+    SYSTEM_RNG.with_slot(|slot| match slot {
+        Some(rng) => {
+            rng.fill_random_bytes(buf);
+            0
+        },
+        None => -ENODEV,
+    })
+}
+
+/// Interrupt safe variant of [`fesr_get_entropy`]: fills the `len` bytes at
+/// `buf` and returns the number of bytes written, at most `INT_MAX`. Returns
+/// `-EBUSY` instead of spinning if the system RNG is in use, e.g. by the
+/// interrupted task, `-EINVAL` if `buf` is null and `-ENODEV` if the system RNG
+/// is not initialized.
+///
+/// `flags` is accepted for compatibility with Zephyr's `get_entropy_isr`, but
+/// `ENTROPY_BUSYWAIT` is ignored: waiting for a lock held by the interrupted
+/// task would never finish.
+///
+/// # Safety
+/// `buf` must be valid for writes of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn fesr_get_entropy_isr(buf: *mut u8, len: usize, flags: u32) -> c_int {
+    let _ = flags;
+    let len = core::cmp::min(len, c_int::MAX.unsigned_abs() as usize);
+    // SAFETY: guaranteed by the caller
+    let Some(buf) = (unsafe { slice_from_c_mut(buf, len) }) else {
+        return -EINVAL;
+    };
+    SYSTEM_RNG
+        .try_with_slot(|slot| match slot {
+            Some(rng) => {
+                rng.fill_random_bytes(buf);
+                c_int::try_from(buf.len()).unwrap_or(c_int::MAX)
+            },
+            None => -ENODEV,
+        })
+        .unwrap_or(-EBUSY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        fesr_add_entropy, fesr_get_entropy, fesr_get_entropy_isr, fesr_init, EBUSY, EINVAL, ENODEV,
+        SYSTEM_RNG,
+    };
+
+    // the system RNG is global state, so everything is tested in order in a
+    // single test
+    #[test]
+    fn system_rng_lifecycle() {
+        let mut buf = [0u8; 40];
+        unsafe {
+            assert_eq!(fesr_get_entropy(buf.as_mut_ptr(), buf.len()), -ENODEV);
+            assert_eq!(fesr_add_entropy(b"extra".as_ptr(), 5), -ENODEV);
+            assert_eq!(fesr_init(b"short".as_ptr(), 5), -EINVAL);
+            assert_eq!(fesr_init(core::ptr::null(), 32), -EINVAL);
+
+            assert_eq!(fesr_init([0x42; 32].as_ptr(), 32), 0);
+            assert_eq!(fesr_get_entropy(buf.as_mut_ptr(), buf.len()), 0);
+            assert_ne!(buf, [0; 40]);
+            assert_eq!(fesr_add_entropy(b"extra".as_ptr(), 5), 0);
+            assert_eq!(fesr_get_entropy_isr(buf.as_mut_ptr(), 16, 0), 16);
+            assert_eq!(fesr_get_entropy(core::ptr::null_mut(), 1), -EINVAL);
+
+            // interrupting a task which holds the lock
+            SYSTEM_RNG.with(|_| {
+                assert_eq!(fesr_get_entropy_isr(buf.as_mut_ptr(), 16, 1), -EBUSY);
+            });
+        }
+    }
+}
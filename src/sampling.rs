@@ -0,0 +1,188 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::RngState;
+
+impl RngState {
+    /// Shuffle `slice` in place, such that every permutation of its elements
+    /// is equally likely (Fisher–Yates shuffle). The RNG MUST be seeded prior
+    /// to using this method.
+    pub fn shuffle<T>(&mut self, slice: &mut [T]) {
+        for i in (1..slice.len()).rev() {
+            let j = self.random_usize_below(i + 1);
+            slice.swap(i, j);
+        }
+    }
+
+    /// Fill `out` with a uniformly random permutation of `0..out.len()`. The
+    /// RNG MUST be seeded prior to using this method.
+    pub fn random_permutation_into(&mut self, out: &mut [usize]) {
+        for (i, index) in out.iter_mut().enumerate() {
+            *index = i;
+        }
+        self.shuffle(out);
+    }
+
+    /// Output a uniformly random permutation of `0..n`. The RNG MUST be seeded
+    /// prior to using this method.
+    ///
+    /// # Examples
+    /// ```
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let order = rng.random_permutation(10);
+    /// let mut sorted = order.clone();
+    /// sorted.sort();
+    /// assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn random_permutation(&mut self, n: usize) -> Vec<usize> {
+        let mut permutation = alloc::vec![0; n];
+        self.random_permutation_into(&mut permutation);
+        permutation
+    }
+
+    /// Output a uniformly random `f64` in the half-open interval `(0, 1]`, with
+    /// 53 bits of precision.
+    #[cfg(feature = "std")]
+    fn random_f64_open_closed(&mut self) -> f64 {
+        const SCALE: f64 = 1.0 / (1u64 << 53) as f64;
+        ((self.random_u64() >> 11) + 1) as f64 * SCALE
+    }
+
+    /// Select a uniformly random sample of `k` items from `iter`, whose length
+    /// doesn't need to be known in advance. Every `k`-subset of the items is
+    /// equally likely to be returned; if `iter` yields fewer than `k` items,
+    /// all of them are returned. The order of the returned items is
+    /// unspecified. The RNG MUST be seeded prior to using this method.
+    ///
+    /// Implements Li's "Algorithm L", which consumes randomness only for the
+    /// `O(k * (1 + log(n / k)))` items that enter the reservoir, rather than
+    /// for every item of the stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let sample = rng.reservoir_sample((0..).step_by(3).take(1000), 10);
+    /// assert_eq!(sample.len(), 10);
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn reservoir_sample<I: IntoIterator>(&mut self, iter: I, k: usize) -> Vec<I::Item> {
+        let mut iter = iter.into_iter();
+        let mut reservoir: Vec<I::Item> = iter.by_ref().take(k).collect();
+        if reservoir.len() < k || k == 0 {
+            return reservoir;
+        }
+
+        let k_inv = 1.0 / k as f64;
+        let mut w = (self.random_f64_open_closed().ln() * k_inv).exp();
+        loop {
+            let skip = (self.random_f64_open_closed().ln() / (1.0 - w).ln()).floor();
+            // the float to int cast saturates, so a huge skip simply exhausts the iterator
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let skip = skip as usize;
+            match iter.nth(skip) {
+                Some(item) => {
+                    let index = self.random_usize_below(k);
+                    reservoir[index] = item;
+                    w *= (self.random_f64_open_closed().ln() * k_inv).exp();
+                },
+                None => return reservoir,
+            }
+        }
+    }
+
+    /// Fill `out` with a uniformly random bitmask of `nbits` bits with exactly
+    /// `weight` bits set, i.e. a uniformly random `weight`-subset of
+    /// `0..nbits`. The RNG MUST be seeded prior to using this method.
+    ///
+    /// Bit `i` is stored in `out[i / 8]` at position `i % 8` (least
+    /// significant bit first); bits beyond `nbits` are cleared. The set bit
+    /// positions are sampled without replacement using Floyd's algorithm,
+    /// which needs exactly `weight` uniform samples.
+    ///
+    /// # Panics
+    /// Panics if `out` is too short to hold `nbits` bits or if `weight >
+    /// nbits`.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let mut error_vector = [0u8; 32];
+    /// rng.random_mask(&mut error_vector, 256, 20);
+    /// let weight: u32 = error_vector.iter().map(|b| b.count_ones()).sum();
+    /// assert_eq!(weight, 20);
+    /// # }
+    /// ```
+    pub fn random_mask(&mut self, out: &mut [u8], nbits: usize, weight: usize) {
+        assert!(
+            nbits <= 8 * out.len(),
+            "output too short for {} bits",
+            nbits
+        );
+        assert!(weight <= nbits, "weight exceeds number of bits");
+        out.fill(0);
+        for j in nbits - weight..nbits {
+            let t = self.random_usize_below(j + 1);
+            // `j` has not been selected yet, since all earlier selections are below `j`
+            let bit = if out[t / 8] & (1 << (t % 8)) == 0 {
+                t
+            } else {
+                j
+            };
+            out[bit / 8] |= 1 << (bit % 8);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RngState;
+
+    #[test]
+    fn random_permutation_into_is_permutation() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"sampling tests");
+        let mut out = [0; 40];
+        rng.random_permutation_into(&mut out);
+        let mut seen = [false; 40];
+        for i in out {
+            assert!(!seen[i]);
+            seen[i] = true;
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn reservoir_sample_distinct() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"sampling tests");
+        let mut sample = rng.reservoir_sample(0..10_000, 16);
+        sample.sort_unstable();
+        sample.dedup();
+        assert_eq!(sample.len(), 16);
+        assert!(sample.iter().all(|&i| i < 10_000));
+        assert_eq!(rng.reservoir_sample(0..3, 16), [0, 1, 2]);
+    }
+
+    #[test]
+    fn random_mask_weight() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"sampling tests");
+        let mut out = [0xff; 3];
+        for (nbits, weight) in [(0, 0), (17, 0), (17, 5), (17, 17), (24, 12)] {
+            rng.random_mask(&mut out, nbits, weight);
+            let set: u32 = out.iter().map(|b| b.count_ones()).sum();
+            assert_eq!(set as usize, weight);
+            assert!((nbits..24).all(|i| out[i / 8] & (1 << (i % 8)) == 0));
+        }
+    }
+}
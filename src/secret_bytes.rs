@@ -0,0 +1,185 @@
+use core::fmt;
+
+use zeroize::Zeroize;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::{ManagedRng, RngError, RngState};
+
+/// A fixed size secret byte array, e.g. a generated key, which carries its
+/// hygiene with it: it is zeroized on drop, its `Debug` output is redacted and
+/// comparisons take constant time.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "getrandom")] {
+/// use fast_erasure_shake_rng::{RngState, SecretBytes};
+///
+/// let mut rng = RngState::new_from_getrandom().unwrap();
+/// let key: SecretBytes<32> = rng.get_secret_bytes();
+/// assert_eq!(format!("{:?}", key), "SecretBytes<32>([REDACTED])");
+/// let raw: &[u8; 32] = key.expose_secret();
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct SecretBytes<const N: usize>([u8; N]);
+
+impl<const N: usize> SecretBytes<N> {
+    /// Wrap the secret `bytes`.
+    pub const fn new(bytes: [u8; N]) -> Self {
+        Self(bytes)
+    }
+
+    /// Access the secret bytes.
+    pub fn expose_secret(&self) -> &[u8; N] {
+        &self.0
+    }
+
+    /// Mutably access the secret bytes.
+    pub fn expose_secret_mut(&mut self) -> &mut [u8; N] {
+        &mut self.0
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for SecretBytes<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl<const N: usize> Drop for SecretBytes<N> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<const N: usize> fmt::Debug for SecretBytes<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretBytes<{}>([REDACTED])", N)
+    }
+}
+
+impl<const N: usize> PartialEq for SecretBytes<N> {
+    /// Constant time comparison.
+    fn eq(&self, other: &Self) -> bool {
+        crate::ct_eq(&self.0, &other.0)
+    }
+}
+
+impl<const N: usize> Eq for SecretBytes<N> {}
+
+impl RngState {
+    /// Output a [`SecretBytes<N>`] filled with random bytes, e.g. a key. The
+    /// RNG MUST be seeded prior to using this method.
+    #[cfg_attr(feature = "profiling", track_caller)]
+    pub fn get_secret_bytes<const N: usize>(&mut self) -> SecretBytes<N> {
+        let mut out = SecretBytes([0; N]);
+        self.fill_random_bytes(&mut out.0);
+        out
+    }
+
+    /// Generate a key of `len` random bytes and pass it to `wrap`, e.g. to
+    /// encrypt it under a key encryption key or import it into an HSM,
+    /// returning the wrapped key. The RNG MUST be seeded prior to using this
+    /// method.
+    ///
+    /// The plaintext key only ever lives in a single zeroizing buffer, which
+    /// is erased before this method returns, also if `wrap` fails or panics.
+    /// `wrap` must not copy the plaintext key elsewhere.
+    ///
+    /// # Errors
+    /// Returns the error of `wrap`.
+    ///
+    /// # Examples
+    /// ```
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// # fn kek_wrap(key: &[u8]) -> Result<Vec<u8>, ()> {
+    /// #     Ok(key.iter().map(|b| b ^ 0x5c).collect())
+    /// # }
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let wrapped = rng.generate_wrapped_key(32, |key| kek_wrap(key)).unwrap();
+    /// // store `wrapped`; the plaintext key is gone
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn generate_wrapped_key<E, F: FnOnce(&[u8]) -> Result<Vec<u8>, E>>(
+        &mut self,
+        len: usize,
+        wrap: F,
+    ) -> Result<Vec<u8>, E> {
+        let mut key = zeroize::Zeroizing::new(alloc::vec![0; len]);
+        self.fill_random_bytes(&mut key);
+        wrap(&key)
+    }
+}
+
+impl ManagedRng {
+    /// Output a [`SecretBytes<N>`] filled with random bytes according to the
+    /// output policy.
+    ///
+    /// # Panics
+    /// Panics if the configured policies refuse output, see
+    /// [`Self::try_fill_random_bytes`].
+    #[cfg_attr(feature = "profiling", track_caller)]
+    pub fn get_secret_bytes<const N: usize>(&mut self) -> SecretBytes<N> {
+        let mut out = SecretBytes([0; N]);
+        self.fill_random_bytes(&mut out.0);
+        out
+    }
+
+    /// Output a [`SecretBytes<N>`] filled with random bytes according to the
+    /// output policy, see [`Self::try_fill_random_bytes`].
+    #[cfg_attr(feature = "profiling", track_caller)]
+    pub fn try_get_secret_bytes<const N: usize>(&mut self) -> Result<SecretBytes<N>, RngError> {
+        let mut out = SecretBytes([0; N]);
+        self.try_fill_random_bytes(&mut out.0)?;
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SecretBytes;
+    use crate::RngState;
+
+    #[test]
+    fn secret_bytes_match_plain_output() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"secret bytes tests");
+        let mut reference = RngState::new_unseeded();
+        reference.seed(b"secret bytes tests");
+        let secret: SecretBytes<32> = rng.get_secret_bytes();
+        assert_eq!(secret.expose_secret(), &reference.get_random_bytes::<32>());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn wrapped_key_is_passed_to_wrap() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"secret bytes tests");
+        let mut reference = RngState::new_unseeded();
+        reference.seed(b"secret bytes tests");
+        let wrapped = rng
+            .generate_wrapped_key(40, |key| {
+                Ok::<_, ()>(key.iter().map(|b| b ^ 0xff).collect())
+            })
+            .unwrap();
+        let expected = reference.get_random_bytes::<40>().map(|b| b ^ 0xff);
+        assert_eq!(wrapped, expected);
+        assert_eq!(
+            rng.generate_wrapped_key(16, |_| Err::<alloc::vec::Vec<u8>, _>(7)),
+            Err(7)
+        );
+    }
+
+    #[test]
+    fn eq_and_debug() {
+        let (a, b) = (SecretBytes::new([1; 4]), SecretBytes::from([1, 1, 1, 2]));
+        assert_eq!(a, a.clone());
+        assert_ne!(a, b);
+        extern crate std;
+        assert_eq!(std::format!("{:?}", a), "SecretBytes<4>([REDACTED])");
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{ManagedRng, RngState};
+
+/// Object safe interface of a cryptographically secure RNG, implemented by
+/// [`RngState`] and [`ManagedRng`].
+///
+/// Code generating randomness can be written against `&mut dyn SecureRng` (or
+/// a generic `R: SecureRng`), so it can be unit tested deterministically, e.g.
+/// using `MockRng` from the `test-util` feature.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "getrandom")] {
+/// use fast_erasure_shake_rng::{RngState, SecureRng};
+///
+/// fn new_session_id(rng: &mut dyn SecureRng) -> [u8; 16] {
+///     let mut id = [0; 16];
+///     rng.fill(&mut id);
+///     id
+/// }
+///
+/// let mut rng = RngState::new_from_getrandom().unwrap();
+/// let id = new_session_id(&mut rng);
+/// # }
+/// ```
+pub trait SecureRng {
+    /// Fill `dest` with random bytes.
+    fn fill(&mut self, dest: &mut [u8]);
+
+    /// Hash the additional seeding material `seed` into the RNG.
+    fn reseed(&mut self, seed: &[u8]);
+}
+
+impl SecureRng for RngState {
+    fn fill(&mut self, dest: &mut [u8]) {
+        self.fill_random_bytes(dest);
+    }
+
+    fn reseed(&mut self, seed: &[u8]) {
+        self.seed(seed);
+    }
+}
+
+impl SecureRng for ManagedRng {
+    fn fill(&mut self, dest: &mut [u8]) {
+        self.fill_random_bytes(dest);
+    }
+
+    fn reseed(&mut self, seed: &[u8]) {
+        self.seed(seed);
+    }
+}
+
+impl<R: SecureRng + ?Sized> SecureRng for &mut R {
+    fn fill(&mut self, dest: &mut [u8]) {
+        (**self).fill(dest);
+    }
+
+    fn reseed(&mut self, seed: &[u8]) {
+        (**self).reseed(seed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SecureRng;
+    use crate::RngState;
+
+    #[test]
+    fn rng_state_via_trait_object() {
+        let (mut rng, mut reference) = (RngState::new_unseeded(), RngState::new_unseeded());
+        let dyn_rng: &mut dyn SecureRng = &mut rng;
+        dyn_rng.reseed(b"secure rng tests");
+        reference.seed(b"secure rng tests");
+        let mut out = [0; 32];
+        dyn_rng.fill(&mut out);
+        assert_eq!(out, reference.get_random_bytes::<32>());
+    }
+}
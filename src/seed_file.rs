@@ -0,0 +1,183 @@
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use zeroize::Zeroizing;
+
+use crate::SecureRng;
+
+/// Number of bytes of fresh output written to a seed file.
+pub const SEED_FILE_LEN: usize = 64;
+
+/// Maximum number of bytes read from a seed file.
+const MAX_SEED_FILE_LEN: u64 = 4096;
+
+/// Domain separation label absorbed before the contents of a seed file.
+const SEED_FILE_LABEL: &[u8] = b"fast-erasure-shake-rng seed file";
+
+/// A persistent seed file carrying entropy across restarts, improving early
+/// boot seeding on systems with weak boot-time entropy.
+///
+/// This implements the classic OS pattern: at startup the seed file is
+/// absorbed alongside the OS RNG and immediately overwritten with fresh
+/// output, so the same seed is never used twice even after a crash; on
+/// shutdown it is updated once more. The seed file only supplements the OS
+/// RNG; it must not be the sole seed, and it must be readable only by the
+/// owner (new files are created with mode `0600` on Unix).
+///
+/// # Examples
+/// ```no_run
+/// use fast_erasure_shake_rng::{RngState, SeedFile};
+///
+/// let seed_file = SeedFile::new("/var/lib/myapp/random-seed");
+/// let mut rng = RngState::new_from_getrandom().unwrap();
+/// seed_file.load_into(&mut rng)?;
+/// // ... run the application ...
+/// seed_file.update(&mut rng)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "seed-file")))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SeedFile {
+    path: PathBuf,
+}
+
+impl SeedFile {
+    /// Use the seed file at `path`.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Path of the seed file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Absorb the seed file into `rng` if it exists, then immediately
+    /// overwrite it with fresh output of `rng`. Returns whether a seed file
+    /// was absorbed.
+    ///
+    /// `rng` should already be seeded from the OS RNG, since the new seed
+    /// file is derived from its state.
+    ///
+    /// # Errors
+    /// Returns an error if reading an existing seed file or writing the new
+    /// one fails.
+    pub fn load_into<R: SecureRng + ?Sized>(&self, rng: &mut R) -> io::Result<bool> {
+        let loaded = match fs::File::open(&self.path) {
+            Ok(file) => {
+                let mut contents = Zeroizing::new(std::vec::Vec::new());
+                file.take(MAX_SEED_FILE_LEN).read_to_end(&mut contents)?;
+                rng.reseed(SEED_FILE_LABEL);
+                rng.reseed(&contents);
+                true
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => false,
+            Err(e) => return Err(e),
+        };
+        self.update(rng)?;
+        Ok(loaded)
+    }
+
+    /// Overwrite the seed file with fresh output of `rng`, e.g. on shutdown.
+    /// The file is replaced atomically by writing a temporary file next to it
+    /// and renaming it. On Unix, the temporary file is created fresh with mode
+    /// `0o600` and the directory is synced after the rename, so a crash can't
+    /// bring back the previous, already used seed.
+    ///
+    /// # Errors
+    /// Returns an error if writing the seed file fails.
+    pub fn update<R: SecureRng + ?Sized>(&self, rng: &mut R) -> io::Result<()> {
+        let mut seed = Zeroizing::new([0; SEED_FILE_LEN]);
+        rng.fill(seed.as_mut());
+
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+        // a stale (or planted) temporary file would keep its permissions
+        match fs::remove_file(&tmp_path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {},
+        }
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&tmp_path)?;
+        file.write_all(seed.as_ref())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &self.path)?;
+        #[cfg(unix)]
+        {
+            let dir = match self.path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+            fs::File::open(dir)?.sync_all()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SeedFile, SEED_FILE_LEN};
+    use crate::RngState;
+
+    fn test_rng() -> RngState {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"seed file tests");
+        rng
+    }
+
+    fn temp_seed_file(name: &str) -> SeedFile {
+        let mut path = std::env::temp_dir();
+        path.push(std::format!("fesr-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        SeedFile::new(path)
+    }
+
+    #[test]
+    fn load_rewrites_seed_file() {
+        let seed_file = temp_seed_file("load");
+        let mut rng = test_rng();
+        assert!(!seed_file.load_into(&mut rng).unwrap());
+        let first = std::fs::read(seed_file.path()).unwrap();
+        assert_eq!(first.len(), SEED_FILE_LEN);
+
+        assert!(seed_file.load_into(&mut rng).unwrap());
+        assert_ne!(std::fs::read(seed_file.path()).unwrap(), first);
+        std::fs::remove_file(seed_file.path()).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stale_temporary_file_is_replaced() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let seed_file = temp_seed_file("stale");
+        let mut tmp_path = seed_file.path().to_path_buf().into_os_string();
+        tmp_path.push(".tmp");
+        std::fs::write(&tmp_path, b"stale").unwrap();
+        let world_readable = std::fs::Permissions::from_mode(0o644);
+        std::fs::set_permissions(&tmp_path, world_readable).unwrap();
+        seed_file.update(&mut test_rng()).unwrap();
+        let metadata = std::fs::metadata(seed_file.path()).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        assert_eq!(metadata.len(), SEED_FILE_LEN as u64);
+        std::fs::remove_file(seed_file.path()).unwrap();
+    }
+
+    #[test]
+    fn seed_file_contents_are_absorbed() {
+        let seed_file = temp_seed_file("absorb");
+        std::fs::write(seed_file.path(), b"stored entropy").unwrap();
+        let mut rng = test_rng();
+        seed_file.load_into(&mut rng).unwrap();
+        assert_ne!(
+            rng.get_random_bytes::<32>(),
+            test_rng().get_random_bytes::<32>()
+        );
+        std::fs::remove_file(seed_file.path()).unwrap();
+    }
+}
@@ -0,0 +1,57 @@
+use std::sync::{Mutex, OnceLock};
+
+use crate::{RngState, TestSeed};
+
+#[cfg(not(debug_assertions))]
+compile_error!(
+    "the `test-seed-override` feature must not be enabled in release builds (without \
+     debug assertions)"
+);
+
+/// Environment variable holding the master test seed overriding the OS RNG.
+pub const TEST_SEED_ENV: &str = "FAST_ERASURE_SHAKE_RNG_TEST_SEED";
+
+/// RNG derived from the master test seed, if the override is set.
+static OVERRIDE: OnceLock<Option<Mutex<RngState>>> = OnceLock::new();
+
+/// Fill `dest` from the master test seed instead of the OS RNG if
+/// [`TEST_SEED_ENV`] is set. Returns whether the override is active.
+///
+/// # Panics
+/// Panics if the variable is set but isn't a valid [`TestSeed`].
+pub(crate) fn fill(dest: &mut [u8]) -> bool {
+    let master = OVERRIDE.get_or_init(|| {
+        let seed = std::env::var(TEST_SEED_ENV).ok()?;
+        let seed: TestSeed = seed
+            .parse()
+            .unwrap_or_else(|e| panic!("invalid {}: {}", TEST_SEED_ENV, e));
+        std::eprintln!(
+            "fast-erasure-shake-rng: OS RNG overridden by test seed {}={}",
+            TEST_SEED_ENV,
+            seed
+        );
+        Some(Mutex::new(seed.rng()))
+    });
+    match master {
+        Some(master) => {
+            // a panic while holding the lock can't leave the RNG inconsistent
+            let mut master = master.lock().unwrap_or_else(|e| e.into_inner());
+            master.fill_random_bytes(dest);
+            true
+        },
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fill, TEST_SEED_ENV};
+
+    #[test]
+    fn inactive_without_env() {
+        // setting the variable here would affect all other tests
+        if std::env::var_os(TEST_SEED_ENV).is_none() {
+            assert!(!fill(&mut [0; 8]));
+        }
+    }
+}
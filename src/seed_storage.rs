@@ -0,0 +1,314 @@
+use zeroize::Zeroizing;
+
+use crate::xof::Shake256;
+use crate::SecureRng;
+
+/// Length in bytes of the seed stored in a [`SeedStorage`] slot.
+const STORED_SEED_LEN: usize = 64;
+
+/// Length in bytes of the integrity check of a stored seed record.
+const CHECK_LEN: usize = 16;
+
+/// Length in bytes of a seed record stored in a [`SeedStorage`] slot: a 64-bit
+/// little-endian sequence number, the seed and an integrity check.
+pub const SEED_RECORD_LEN: usize = 8 + STORED_SEED_LEN + CHECK_LEN;
+
+/// Domain separation label of the integrity check of seed records.
+const CHECK_LABEL: &[u8] = b"fast-erasure-shake-rng seed record check";
+
+/// Domain separation label absorbed before a stored seed.
+const STORED_SEED_LABEL: &[u8] = b"fast-erasure-shake-rng stored seed";
+
+/// Non-volatile storage for seed records, e.g. sectors of NOR flash or an
+/// EEPROM, letting devices without a TRNG carry entropy across reboots. Used
+/// through [`PersistentSeed`].
+///
+/// Storage has one or more slots of [`SEED_RECORD_LEN`] bytes each. Writes
+/// rotate through the slots, so with `n` slots every slot is written once
+/// every `n` updates. On flash, each slot should be its own erase sector and
+/// [`Self::write_slot`] erases it before programming. The storage must not be
+/// readable by untrusted parties.
+pub trait SeedStorage {
+    /// Error returned by the storage.
+    type Error;
+
+    /// Number of slots, at least 1.
+    fn slot_count(&self) -> usize {
+        1
+    }
+
+    /// Read the record in slot `slot` into `record`. Erased or never written
+    /// slots may return any content; they are recognized as invalid.
+    fn read_slot(
+        &mut self,
+        slot: usize,
+        record: &mut [u8; SEED_RECORD_LEN],
+    ) -> Result<(), Self::Error>;
+
+    /// Replace the record in slot `slot` by `record`.
+    fn write_slot(
+        &mut self,
+        slot: usize,
+        record: &[u8; SEED_RECORD_LEN],
+    ) -> Result<(), Self::Error>;
+}
+
+impl<S: SeedStorage + ?Sized> SeedStorage for &mut S {
+    type Error = S::Error;
+
+    fn slot_count(&self) -> usize {
+        (**self).slot_count()
+    }
+
+    fn read_slot(
+        &mut self,
+        slot: usize,
+        record: &mut [u8; SEED_RECORD_LEN],
+    ) -> Result<(), Self::Error> {
+        (**self).read_slot(slot, record)
+    }
+
+    fn write_slot(
+        &mut self,
+        slot: usize,
+        record: &[u8; SEED_RECORD_LEN],
+    ) -> Result<(), Self::Error> {
+        (**self).write_slot(slot, record)
+    }
+}
+
+/// Integrity check of a seed record, detecting erased slots and torn writes.
+fn record_check(sequence: &[u8], seed: &[u8]) -> [u8; CHECK_LEN] {
+    let mut xof = Shake256::new();
+    xof.absorb_prefixed(CHECK_LABEL);
+    xof.absorb_prefixed(sequence);
+    xof.absorb_prefixed(seed);
+    let mut check = [0; CHECK_LEN];
+    xof.squeeze(&mut check);
+    check
+}
+
+/// A persistent seed in a [`SeedStorage`], the `no_std` counterpart of a seed
+/// file.
+///
+/// At boot, [`Self::load_into`] absorbs the newest valid record and
+/// immediately writes a fresh one, so a seed is never used twice even if the
+/// device resets before a regular update. Like a seed file, a persistent seed
+/// supplements but doesn't replace other entropy sources: a device which is
+/// cloned or whose storage is read out loses its security.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::{PersistentSeed, RngState, SeedStorage, SEED_RECORD_LEN};
+///
+/// struct Eeprom([[u8; SEED_RECORD_LEN]; 2]);
+///
+/// impl SeedStorage for Eeprom {
+///     type Error = core::convert::Infallible;
+///
+///     fn slot_count(&self) -> usize {
+///         2
+///     }
+///
+///     fn read_slot(
+///         &mut self,
+///         slot: usize,
+///         record: &mut [u8; SEED_RECORD_LEN],
+///     ) -> Result<(), Self::Error> {
+///         *record = self.0[slot];
+///         Ok(())
+///     }
+///
+///     fn write_slot(
+///         &mut self,
+///         slot: usize,
+///         record: &[u8; SEED_RECORD_LEN],
+///     ) -> Result<(), Self::Error> {
+///         self.0[slot] = *record;
+///         Ok(())
+///     }
+/// }
+///
+/// let mut seed = PersistentSeed::new(Eeprom([[0xff; SEED_RECORD_LEN]; 2]));
+/// let mut rng = RngState::new_unseeded();
+/// rng.seed(b"device serial 1234");
+/// // false on first boot, true afterwards
+/// let loaded = seed.load_into(&mut rng).unwrap();
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "seed-storage")))]
+#[derive(Debug)]
+pub struct PersistentSeed<S> {
+    storage: S,
+    /// Slot to write the next record to.
+    next_slot: usize,
+    /// Sequence number of the next record.
+    next_sequence: u64,
+}
+
+impl<S: SeedStorage> PersistentSeed<S> {
+    /// Use the seed records in `storage`.
+    pub fn new(storage: S) -> Self {
+        Self {
+            storage,
+            next_slot: 0,
+            next_sequence: 0,
+        }
+    }
+
+    /// Absorb the newest valid seed record into `rng`, then immediately write
+    /// a fresh record derived from `rng`. Returns whether a valid record was
+    /// absorbed.
+    ///
+    /// # Errors
+    /// Returns an error if reading or writing the storage fails.
+    ///
+    /// # Panics
+    /// Panics if the storage has no slots.
+    pub fn load_into<R: SecureRng + ?Sized>(&mut self, rng: &mut R) -> Result<bool, S::Error> {
+        let slots = self.storage.slot_count();
+        assert!(slots > 0, "seed storage has no slots");
+        let mut newest: Option<(usize, u64)> = None;
+        let mut record = Zeroizing::new([0; SEED_RECORD_LEN]);
+        for slot in 0..slots {
+            self.storage.read_slot(slot, &mut record)?;
+            let (sequence, rest) = record.split_at(8);
+            let (seed, check) = rest.split_at(STORED_SEED_LEN);
+            if record_check(sequence, seed) != check {
+                continue;
+            }
+            let sequence = u64::from_le_bytes(sequence.try_into().unwrap());
+            if !matches!(newest, Some((_, newest)) if newest >= sequence) {
+                newest = Some((slot, sequence));
+            }
+        }
+
+        if let Some((slot, sequence)) = newest {
+            self.storage.read_slot(slot, &mut record)?;
+            rng.reseed(STORED_SEED_LABEL);
+            rng.reseed(&record[8..8 + STORED_SEED_LEN]);
+            self.next_slot = (slot + 1) % slots;
+            self.next_sequence = sequence.wrapping_add(1);
+        }
+        self.update(rng)?;
+        Ok(newest.is_some())
+    }
+
+    /// Write a fresh seed record derived from `rng` to the next slot, e.g.
+    /// periodically or before shutdown.
+    ///
+    /// # Errors
+    /// Returns an error if writing the storage fails.
+    pub fn update<R: SecureRng + ?Sized>(&mut self, rng: &mut R) -> Result<(), S::Error> {
+        let mut record = Zeroizing::new([0; SEED_RECORD_LEN]);
+        {
+            let (sequence, rest) = record.split_at_mut(8);
+            let (seed, check) = rest.split_at_mut(STORED_SEED_LEN);
+            sequence.copy_from_slice(&self.next_sequence.to_le_bytes());
+            rng.fill(seed);
+            check.copy_from_slice(&record_check(sequence, seed));
+        }
+        self.storage.write_slot(self.next_slot, &record)?;
+        self.next_slot = (self.next_slot + 1) % self.storage.slot_count();
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+        Ok(())
+    }
+
+    /// Unwrap the underlying storage.
+    pub fn into_inner(self) -> S {
+        self.storage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PersistentSeed, SeedStorage, SEED_RECORD_LEN};
+    use crate::RngState;
+
+    /// Storage in RAM counting the writes per slot.
+    struct Ram {
+        slots: [[u8; SEED_RECORD_LEN]; 3],
+        writes: [u32; 3],
+    }
+
+    impl Ram {
+        fn erased() -> Self {
+            Self {
+                slots: [[0xff; SEED_RECORD_LEN]; 3],
+                writes: [0; 3],
+            }
+        }
+    }
+
+    impl SeedStorage for Ram {
+        type Error = ();
+
+        fn slot_count(&self) -> usize {
+            3
+        }
+
+        fn read_slot(&mut self, slot: usize, record: &mut [u8; SEED_RECORD_LEN]) -> Result<(), ()> {
+            *record = self.slots[slot];
+            Ok(())
+        }
+
+        fn write_slot(&mut self, slot: usize, record: &[u8; SEED_RECORD_LEN]) -> Result<(), ()> {
+            self.slots[slot] = *record;
+            self.writes[slot] += 1;
+            Ok(())
+        }
+    }
+
+    fn test_rng() -> RngState {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"seed storage tests");
+        rng
+    }
+
+    #[test]
+    fn seed_survives_reboots() {
+        let mut storage = Ram::erased();
+        assert_eq!(
+            PersistentSeed::new(&mut storage).load_into(&mut test_rng()),
+            Ok(false)
+        );
+        let mut outputs = [[0; 32]; 2];
+        for output in &mut outputs {
+            let mut rng = test_rng();
+            assert_eq!(
+                PersistentSeed::new(&mut storage).load_into(&mut rng),
+                Ok(true)
+            );
+            *output = rng.get_random_bytes();
+        }
+        // every boot absorbs a fresh seed
+        assert_ne!(outputs[0], outputs[1]);
+        assert_ne!(outputs[0], test_rng().get_random_bytes::<32>());
+    }
+
+    #[test]
+    fn writes_rotate_through_slots() {
+        let mut storage = Ram::erased();
+        let mut rng = test_rng();
+        let mut seed = PersistentSeed::new(&mut storage);
+        seed.load_into(&mut rng).unwrap();
+        for _ in 0..5 {
+            seed.update(&mut rng).unwrap();
+        }
+        assert_eq!(storage.writes, [2, 2, 2]);
+    }
+
+    #[test]
+    fn torn_record_is_ignored() {
+        let mut storage = Ram::erased();
+        let mut rng = test_rng();
+        let mut seed = PersistentSeed::new(&mut storage);
+        seed.load_into(&mut rng).unwrap();
+        seed.update(&mut rng).unwrap();
+        // corrupt the newest record in slot 1; slot 0 is still valid
+        storage.slots[1][20] ^= 1;
+        let mut seed = PersistentSeed::new(&mut storage);
+        assert_eq!(seed.load_into(&mut rng), Ok(true));
+        // the fresh record goes after the valid one, replacing the torn one
+        assert_eq!(storage.writes, [1, 2, 0]);
+    }
+}
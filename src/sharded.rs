@@ -0,0 +1,147 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{RawSpinLock, RngState, SyncRngState};
+
+/// Domain separation label absorbed into every shard with its index.
+const SHARD_LABEL: &[u8] = b"fast-erasure-shake-rng shard";
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    /// Preferred shard index of the current thread, assigned round robin on
+    /// first use.
+    static THREAD_HINT: usize = {
+        static NEXT_THREAD: AtomicUsize = AtomicUsize::new(0);
+        NEXT_THREAD.fetch_add(1, Ordering::Relaxed)
+    };
+}
+
+/// A pool of `N` independent RNG shards, each protected by its own spin lock,
+/// as a scalable alternative to a single locked RNG for services drawing
+/// randomness from many threads or tasks at once. Can be shared in an `Arc`
+/// or a `static` initialized at startup.
+///
+/// Every shard is derived from a root RNG with domain separation. Callers are
+/// routed to a preferred shard (per thread with the `std` feature, round
+/// robin otherwise) and move on to the next free shard if it is busy, only
+/// spinning if all shards are busy. Pick `N` around the number of CPU cores.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "getrandom")] {
+/// use fast_erasure_shake_rng::{AtomicSpinLock, RngState, ShardedRng};
+///
+/// let mut root = RngState::new_from_getrandom().unwrap();
+/// let pool = ShardedRng::<AtomicSpinLock, 8>::from_root(&mut root);
+/// std::thread::scope(|s| {
+///     for _ in 0..4 {
+///         s.spawn(|| pool.with(|rng| rng.get_random_bytes::<32>()));
+///     }
+/// });
+/// # }
+/// ```
+pub struct ShardedRng<L: RawSpinLock, const N: usize> {
+    shards: [SyncRngState<L>; N],
+    /// Next shard for round robin routing without `std`.
+    #[cfg(not(feature = "std"))]
+    next: AtomicUsize,
+}
+
+impl<L: RawSpinLock, const N: usize> ShardedRng<L, N> {
+    /// Create a pool of `N` shards derived from `root`, which MUST be seeded.
+    ///
+    /// # Panics
+    /// Panics if `N` is zero.
+    pub fn from_root(root: &mut RngState) -> Self {
+        assert!(N > 0, "a sharded RNG needs at least one shard");
+        Self {
+            shards: core::array::from_fn(|i| {
+                let mut shard = root.fork();
+                shard.seed(SHARD_LABEL);
+                shard.seed(&(i as u64).to_le_bytes());
+                SyncRngState::from(shard)
+            }),
+            #[cfg(not(feature = "std"))]
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Create a pool of `N` shards derived from a root seeded from the OS RNG.
+    ///
+    /// # Panics
+    /// Panics if `N` is zero.
+    #[cfg(feature = "getrandom")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "getrandom")))]
+    pub fn from_getrandom() -> Result<Self, getrandom::Error> {
+        Ok(Self::from_root(&mut RngState::new_from_getrandom()?))
+    }
+
+    /// Index of the shard to try first.
+    fn preferred_shard(&self) -> usize {
+        #[cfg(feature = "std")]
+        return THREAD_HINT.with(|hint| *hint) % N;
+        #[cfg(not(feature = "std"))]
+        return self.next.fetch_add(1, Ordering::Relaxed) % N;
+    }
+
+    /// Call `f` with exclusive access to one of the shards: the preferred
+    /// shard if free, otherwise the next free one. Spins on the preferred
+    /// shard if all shards are busy.
+    pub fn with<R, F: FnOnce(&mut RngState) -> R>(&self, f: F) -> R {
+        let start = self.preferred_shard();
+        let mut f = Some(f);
+        for i in 0..N {
+            let shard = &self.shards[(start + i) % N];
+            // `f` is only taken if the closure runs, i.e. the shard was free
+            if let Some(res) = shard.try_with(|rng| (f.take().unwrap())(rng)) {
+                return res;
+            }
+        }
+        self.shards[start].with(f.take().unwrap())
+    }
+
+    /// Hash additional data `seed` into every shard, see [`RngState::seed`].
+    pub fn seed(&self, seed: &[u8]) {
+        for shard in &self.shards {
+            shard.with(|rng| rng.seed(seed));
+        }
+    }
+}
+
+#[cfg(all(test, target_has_atomic = "8"))]
+mod tests {
+    use super::ShardedRng;
+    use crate::{AtomicSpinLock, RngState};
+
+    fn pool() -> ShardedRng<AtomicSpinLock, 4> {
+        let mut root = RngState::new_unseeded();
+        root.seed(b"sharded tests");
+        ShardedRng::from_root(&mut root)
+    }
+
+    #[test]
+    fn shards_are_independent() {
+        let pool = pool();
+        let outputs: [[u8; 16]; 4] =
+            core::array::from_fn(|i| pool.shards[i].with(|rng| rng.get_random_bytes()));
+        for i in 0..4 {
+            for j in 0..i {
+                assert_ne!(outputs[i], outputs[j]);
+            }
+        }
+    }
+
+    #[test]
+    fn busy_shard_is_skipped() {
+        let pool = pool();
+        // hold every shard but one; `with` must find the free one instead of
+        // spinning forever
+        pool.shards[0].with(|_| {
+            pool.shards[1].with(|_| {
+                pool.shards[3].with(|_| {
+                    pool.with(|rng| rng.get_random_bytes::<8>());
+                })
+            })
+        });
+        assert!(pool.shards[2].try_with(|_| ()).is_some());
+    }
+}
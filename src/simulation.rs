@@ -0,0 +1,142 @@
+use core::fmt;
+use core::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+use crate::TestSeed;
+
+#[cfg(not(debug_assertions))]
+compile_error!(
+    "the `simulation` feature must not be enabled in release builds (without debug \
+     assertions)"
+);
+
+/// Domain separation label absorbed with the index of every derived seed.
+const SIMULATION_LABEL: &[u8] = b"fast-erasure-shake-rng simulation";
+
+/// The active simulation.
+struct Simulation {
+    master: TestSeed,
+    /// Index of the next request for OS entropy, its unique label.
+    next: AtomicU64,
+}
+
+static SIMULATION: OnceLock<Simulation> = OnceLock::new();
+
+/// Error returned by [`enable_simulation`] if a simulation with a different
+/// master seed is already active.
+#[cfg_attr(docsrs, doc(cfg(feature = "simulation")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SimulationActiveError {
+    /// Master seed of the active simulation.
+    pub active: TestSeed,
+}
+
+impl fmt::Display for SimulationActiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "simulation with master seed {} already active",
+            self.active
+        )
+    }
+}
+
+impl std::error::Error for SimulationActiveError {}
+
+/// Enable deterministic simulation for the rest of the process: from now on
+/// every request for OS entropy, e.g. by [`RngState::new_from_getrandom`],
+/// [`OsEntropy`] or a [`ManagedRng`] built with the OS RNG, is derived from
+/// `master` instead. NEVER use this outside of tests.
+///
+/// The master seed is logged to stderr. Every request is derived from the
+/// master seed and its own index as unique label, so with the seed the whole
+/// run can be replayed exactly, provided the program requests OS entropy in
+/// the same order, as in a single-threaded simulation executor in the style
+/// of FoundationDB.
+///
+/// Enabling again with the same seed does nothing. Takes precedence over
+/// `test-seed-override`.
+///
+/// # Errors
+/// Returns an error if a simulation with a different master seed is active.
+///
+/// # Examples
+/// ```no_run
+/// use fast_erasure_shake_rng::{enable_simulation, RngState, TestSeed};
+///
+/// // in the simulation harness, with a seed printed by a failed run to replay
+/// let seed: TestSeed = std::env::args().nth(1).unwrap().parse().unwrap();
+/// enable_simulation(seed).unwrap();
+/// let mut rng = RngState::new_from_getrandom().unwrap();
+/// ```
+///
+/// [`RngState::new_from_getrandom`]: crate::RngState::new_from_getrandom
+/// [`OsEntropy`]: crate::OsEntropy
+/// [`ManagedRng`]: crate::ManagedRng
+#[cfg_attr(docsrs, doc(cfg(feature = "simulation")))]
+pub fn enable_simulation(master: TestSeed) -> Result<(), SimulationActiveError> {
+    let active = SIMULATION.get_or_init(|| {
+        std::eprintln!(
+            "fast-erasure-shake-rng: deterministic simulation with master seed {}",
+            master
+        );
+        Simulation {
+            master,
+            next: AtomicU64::new(0),
+        }
+    });
+    if active.master == master {
+        Ok(())
+    } else {
+        Err(SimulationActiveError {
+            active: active.master,
+        })
+    }
+}
+
+/// The master seed of the active simulation, if any, see
+/// [`enable_simulation`].
+#[cfg_attr(docsrs, doc(cfg(feature = "simulation")))]
+pub fn simulation_seed() -> Option<TestSeed> {
+    SIMULATION.get().map(|simulation| simulation.master)
+}
+
+/// Fill `dest` with the output derived from `master` for request `index`.
+fn derive(master: &TestSeed, index: u64, dest: &mut [u8]) {
+    let mut rng = master.rng();
+    rng.seed(SIMULATION_LABEL);
+    rng.seed(&index.to_le_bytes());
+    rng.fill_random_bytes(dest);
+}
+
+/// Fill `dest` from the master seed instead of the OS RNG if a simulation is
+/// active. Returns whether it is.
+pub(crate) fn fill(dest: &mut [u8]) -> bool {
+    match SIMULATION.get() {
+        Some(simulation) => {
+            let index = simulation.next.fetch_add(1, Ordering::Relaxed);
+            derive(&simulation.master, index, dest);
+            true
+        },
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::derive;
+    use crate::TestSeed;
+
+    #[test]
+    fn derived_requests_are_unique_and_reproducible() {
+        let master = TestSeed::from_bytes([3; 32]);
+        let (mut first, mut second, mut again) = ([0; 32], [0; 32], [0; 32]);
+        derive(&master, 0, &mut first);
+        derive(&master, 1, &mut second);
+        derive(&master, 0, &mut again);
+        assert_ne!(first, second);
+        assert_eq!(first, again);
+        derive(&TestSeed::from_bytes([4; 32]), 0, &mut again);
+        assert_ne!(first, again);
+    }
+}
@@ -0,0 +1,183 @@
+#[cfg(feature = "std")]
+use core::time::Duration;
+
+use crate::ManagedRng;
+
+/// Snapshot of the state of a [`ManagedRng`] for health endpoints and
+/// watchdogs, returned by [`ManagedRng::status`].
+///
+/// Contains no secret information.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct RngStatus {
+    /// Whether any input (entropy source or seed) has been absorbed.
+    pub seeded: bool,
+    /// Whether the RNG is degraded, see [`ManagedRng::is_degraded`].
+    pub degraded: bool,
+    /// Whether the RNG is sandboxed, see [`ManagedRng::is_sandboxed`].
+    pub sandboxed: bool,
+    /// Whether the RNG is poisoned, see [`ManagedRng::is_poisoned`].
+    pub poisoned: bool,
+    /// Total entropy in bits credited so far, see
+    /// [`ManagedRng::credited_entropy`].
+    pub credited_entropy: u64,
+    /// Number of successful (re)seedings from entropy sources, including the
+    /// initial seeding by the builder.
+    pub reseeds: u64,
+    /// Number of output bytes produced since the last successful reseeding
+    /// from an entropy source.
+    pub bytes_since_reseed: u64,
+    /// Time since the last successful reseeding from an entropy source. `None`
+    /// if there was none, if it happened while sandboxed or if the RNG is
+    /// sandboxed now, since reading the clock may need a syscall.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub last_reseed_age: Option<Duration>,
+    /// Whether health tests are enabled, see
+    /// [`RngBuilder::health_tests`](crate::RngBuilder::health_tests).
+    pub health_tests_enabled: bool,
+    /// Number of times entropy source output failed a health test.
+    pub health_test_failures: u64,
+    /// Result of the most recent health test run, `None` if none ran yet.
+    pub last_health_test_passed: Option<bool>,
+}
+
+impl ManagedRng {
+    /// Report the state of the RNG: whether it is seeded and degraded, when it
+    /// was last reseeded, how much output it produced since and the results of
+    /// the health tests.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::ManagedRng;
+    ///
+    /// let mut rng = ManagedRng::builder().build().unwrap();
+    /// let _key = rng.get_random_bytes::<32>();
+    /// let status = rng.status();
+    /// assert!(status.seeded && !status.degraded);
+    /// assert_eq!(status.bytes_since_reseed, 32);
+    /// # }
+    /// ```
+    pub fn status(&self) -> RngStatus {
+        RngStatus {
+            seeded: self.stats.seeded,
+            degraded: self.is_degraded(),
+            sandboxed: self.is_sandboxed(),
+            poisoned: self.is_poisoned(),
+            credited_entropy: self.credited_entropy(),
+            reseeds: self.stats.reseeds,
+            bytes_since_reseed: self.stats.bytes_since_reseed,
+            #[cfg(feature = "std")]
+            last_reseed_age: self
+                .stats
+                .last_reseed
+                .filter(|_| !self.is_sandboxed())
+                .map(|instant| instant.elapsed()),
+            health_tests_enabled: self.health_tests.is_some(),
+            health_test_failures: self.stats.health_test_failures,
+            last_health_test_passed: self.stats.last_health_test_passed,
+        }
+    }
+}
+
+/// Counters of a [`ManagedRng`] reported by [`ManagedRng::status`].
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct RngStats {
+    pub(crate) seeded: bool,
+    pub(crate) reseeds: u64,
+    pub(crate) bytes_since_reseed: u64,
+    #[cfg(feature = "std")]
+    pub(crate) last_reseed: Option<std::time::Instant>,
+    pub(crate) health_test_failures: u64,
+    pub(crate) last_health_test_passed: Option<bool>,
+}
+
+impl RngStats {
+    /// Record a successful reseeding from an entropy source. The time is not
+    /// recorded if `sandboxed`.
+    pub(crate) fn record_reseed(&mut self, sandboxed: bool) {
+        self.seeded = true;
+        self.reseeds = self.reseeds.saturating_add(1);
+        self.bytes_since_reseed = 0;
+        #[cfg(feature = "std")]
+        {
+            self.last_reseed = (!sandboxed).then(std::time::Instant::now);
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = sandboxed;
+    }
+
+    /// Record the result of running health tests.
+    pub(crate) fn record_health_test(&mut self, passed: bool) {
+        if !passed {
+            self.health_test_failures = self.health_test_failures.saturating_add(1);
+        }
+        self.last_health_test_passed = Some(passed);
+    }
+
+    /// Record `len` bytes of output.
+    pub(crate) fn record_output(&mut self, len: usize) {
+        self.bytes_since_reseed = self.bytes_since_reseed.saturating_add(len as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EntropySource, EntropySourceError, HealthTestConfig, ManagedRng, RngError};
+
+    struct Stuck;
+
+    struct Counter(u8);
+
+    impl EntropySource for Counter {
+        fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+            for byte in dest {
+                self.0 = self.0.wrapping_add(1);
+                *byte = self.0;
+            }
+            Ok(())
+        }
+    }
+
+    impl EntropySource for Stuck {
+        fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+            dest.fill(0);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn status_counts_output_and_reseeds() {
+        let mut rng = ManagedRng::new_unseeded();
+        assert!(!rng.status().seeded);
+        rng.seed(b"status tests");
+        let status = rng.status();
+        assert!(status.seeded);
+        assert_eq!(status.reseeds, 0);
+        rng.fill_random_bytes(&mut [0; 100]);
+        assert_eq!(rng.status().bytes_since_reseed, 100);
+        rng.reseed_from(&mut Counter(0)).unwrap();
+        let status = rng.status();
+        assert_eq!(status.reseeds, 1);
+        assert_eq!(status.bytes_since_reseed, 0);
+        #[cfg(feature = "std")]
+        assert!(status.last_reseed_age.is_some());
+    }
+
+    #[test]
+    fn status_records_health_test_failures() {
+        let mut rng = ManagedRng::new_unseeded();
+        rng.health_tests = Some(HealthTestConfig::default());
+        assert_eq!(rng.status().last_health_test_passed, None);
+        assert!(matches!(
+            rng.reseed_from(&mut Stuck),
+            Err(RngError::HealthTest(_))
+        ));
+        let status = rng.status();
+        assert!(status.health_tests_enabled);
+        assert_eq!(status.health_test_failures, 1);
+        assert_eq!(status.last_health_test_passed, Some(false));
+        assert_eq!(status.reseeds, 0);
+    }
+}
@@ -0,0 +1,126 @@
+use core::ops::{Deref, DerefMut};
+
+use rand_core::{CryptoRng, RngCore};
+
+use crate::{FullRateSeed, RngState, Seed};
+
+/// An [`RngState`] which can only be created from a full seed, for projects
+/// which must rule out trivially small seeds at compile time.
+///
+/// Unlike [`RngState`], this type doesn't implement
+/// [`rand_core::SeedableRng`], so code generic over `SeedableRng` can't create
+/// it using `seed_from_u64`: that is a compile error. It implements
+/// [`RngCore`] and [`CryptoRng`], and dereferences to the wrapped
+/// [`RngState`] for everything else.
+///
+/// Calls to `RngState::seed_from_u64` elsewhere in a project can be rejected
+/// by clippy, using the following `clippy.toml`:
+/// ```toml
+/// disallowed-methods = [
+///     { path = "rand_core::SeedableRng::seed_from_u64", reason = "a u64 seed is too small" },
+/// ]
+/// ```
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::{Seed, StrongSeededRng};
+///
+/// let mut rng = StrongSeededRng::from_seed(Seed::from([0x17; 64]));
+/// let key = rng.get_random_bytes::<32>();
+/// ```
+///
+/// ```compile_fail
+/// use fast_erasure_shake_rng::StrongSeededRng;
+/// use rand_core::SeedableRng;
+///
+/// let rng = StrongSeededRng::seed_from_u64(42);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "deny-weak-seeding")))]
+pub struct StrongSeededRng(RngState);
+
+impl StrongSeededRng {
+    /// Create an RNG seeded with a full 64 byte `seed`.
+    pub fn from_seed(seed: Seed) -> Self {
+        Self(<RngState as rand_core::SeedableRng>::from_seed(seed))
+    }
+
+    /// Create an RNG seeded with a full 72 byte `seed`, see
+    /// [`RngState::from_full_rate_seed`].
+    pub fn from_full_rate_seed(seed: FullRateSeed) -> Self {
+        Self(RngState::from_full_rate_seed(seed))
+    }
+
+    /// Create an RNG seeded with output from the cryptographically secure
+    /// RNG `seeder_rng`.
+    pub fn from_rng<R: RngCore + CryptoRng>(mut seeder_rng: R) -> Result<Self, rand_core::Error> {
+        let mut rng = RngState::new_unseeded();
+        rng.seed_with_64(|buf| seeder_rng.try_fill_bytes(buf))?;
+        Ok(Self(rng))
+    }
+
+    /// Create an RNG seeded with entropy from the OS RNG, see
+    /// [`RngState::new_from_getrandom`].
+    #[cfg(feature = "getrandom")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "getrandom")))]
+    pub fn new_from_getrandom() -> Result<Self, getrandom::Error> {
+        RngState::new_from_getrandom().map(Self)
+    }
+
+    /// Unwrap the RNG.
+    pub fn into_inner(self) -> RngState {
+        self.0
+    }
+}
+
+impl Deref for StrongSeededRng {
+    type Target = RngState;
+
+    fn deref(&self) -> &RngState {
+        &self.0
+    }
+}
+
+impl DerefMut for StrongSeededRng {
+    fn deref_mut(&mut self) -> &mut RngState {
+        &mut self.0
+    }
+}
+
+impl RngCore for StrongSeededRng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.0.try_fill_bytes(dest)
+    }
+}
+
+impl CryptoRng for StrongSeededRng {}
+
+#[cfg(test)]
+mod tests {
+    use super::StrongSeededRng;
+    use crate::{RngState, Seed};
+    use rand_core::{RngCore, SeedableRng};
+
+    #[test]
+    fn matches_seeded_rng_state() {
+        let seed = [0x17; 64];
+        let mut rng = StrongSeededRng::from_seed(Seed::from(seed));
+        let mut reference = RngState::from_seed(Seed::from(seed));
+        assert_eq!(rng.next_u64(), reference.next_u64());
+        assert_eq!(
+            rng.get_random_bytes::<32>(),
+            reference.get_random_bytes::<32>()
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use core::cell::UnsafeCell;
+#[cfg(target_has_atomic = "8")]
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::RngState;
+
+/// A raw spin lock, used by [`SyncRngState`] to serialize access to the RNG.
+///
+/// On targets with atomic compare-and-swap, [`AtomicSpinLock`] can be used.
+/// Targets without it, like the RP2040, can implement this trait using a
+/// hardware spin lock.
+///
+/// # Safety
+/// [`Self::try_lock`] must only return `true` if the lock was not held, after
+/// which the lock is held until [`Self::unlock`] is called. Acquiring the lock
+/// must synchronize with the preceding release (acquire/release ordering).
+pub unsafe trait RawSpinLock {
+    /// An unlocked lock.
+    const UNLOCKED: Self;
+
+    /// Try to acquire the lock without blocking. Returns whether the lock was
+    /// acquired.
+    fn try_lock(&self) -> bool;
+
+    /// Release the lock. Only called by the holder of the lock.
+    fn unlock(&self);
+}
+
+/// A [`RawSpinLock`] based on an [`AtomicBool`].
+#[cfg(target_has_atomic = "8")]
+#[derive(Debug)]
+pub struct AtomicSpinLock(AtomicBool);
+
+// SAFETY: a successful compare-and-swap from `false` to `true` guarantees the
+// lock was not held, and the orderings pair the acquire with the release
+#[cfg(target_has_atomic = "8")]
+unsafe impl RawSpinLock for AtomicSpinLock {
+    const UNLOCKED: Self = Self(AtomicBool::new(false));
+
+    fn try_lock(&self) -> bool {
+        self.0
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    fn unlock(&self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+/// An RNG which can be shared between threads or cores without the standard
+/// library, protected by a spin lock. Can be placed in a `static`.
+///
+/// # Usage constraints
+/// - Access is not reentrant: calling [`Self::with`] on the same instance from
+///   within the closure deadlocks.
+/// - Don't access the RNG from an interrupt handler which may preempt code
+///   holding the lock on the same core; the handler would spin forever. Use
+///   [`Self::try_with`] there, which fails instead of spinning.
+/// - Waiting cores busy-wait, so keep the closures short.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "getrandom")] {
+/// use fast_erasure_shake_rng::{AtomicSpinLock, RngState, SyncRngState};
+///
+/// static RNG: SyncRngState<AtomicSpinLock> = SyncRngState::new();
+///
+/// RNG.set(RngState::new_from_getrandom().unwrap());
+/// // on any core or thread:
+/// let key = RNG.with(|rng| rng.get_random_bytes::<32>());
+/// # }
+/// ```
+pub struct SyncRngState<L: RawSpinLock> {
+    lock: L,
+    rng: UnsafeCell<Option<RngState>>,
+}
+
+// SAFETY: `rng` is only accessed while holding `lock`, so it is never accessed
+// concurrently
+unsafe impl<L: RawSpinLock + Sync> Sync for SyncRngState<L> {}
+
+/// Releases the lock when dropped, also when the closure panics.
+struct Unlock<'l, L: RawSpinLock>(&'l L);
+
+impl<L: RawSpinLock> Drop for Unlock<'_, L> {
+    fn drop(&mut self) {
+        self.0.unlock();
+    }
+}
+
+impl<L: RawSpinLock> SyncRngState<L> {
+    /// Create a new empty instance. An RNG must be put in using [`Self::set`]
+    /// before it can be used.
+    pub const fn new() -> Self {
+        Self {
+            lock: L::UNLOCKED,
+            rng: UnsafeCell::new(None),
+        }
+    }
+
+    /// Call `f` with exclusive access to the RNG slot, spinning until the lock
+    /// is acquired.
+    pub(crate) fn with_slot<R, F: FnOnce(&mut Option<RngState>) -> R>(&self, f: F) -> R {
+        while !self.lock.try_lock() {
+            #[cfg(feature = "std")]
+            std::thread::yield_now();
+            #[cfg(not(feature = "std"))]
+            core::hint::spin_loop();
+        }
+        let _unlock = Unlock(&self.lock);
+        // SAFETY: we hold the lock, so no other reference to `rng` exists
+        f(unsafe { &mut *self.rng.get() })
+    }
+
+    /// Put `rng` in this instance, returning the previously contained RNG.
+    pub fn set(&self, rng: RngState) -> Option<RngState> {
+        self.with_slot(|slot| slot.replace(rng))
+    }
+
+    /// Remove the RNG from this instance.
+    pub fn take(&self) -> Option<RngState> {
+        self.with_slot(Option::take)
+    }
+
+    /// Call `f` with exclusive access to the contained RNG, spinning until the
+    /// lock is acquired.
+    ///
+    /// # Panics
+    /// Panics if the instance is empty.
+    pub fn with<R, F: FnOnce(&mut RngState) -> R>(&self, f: F) -> R {
+        self.with_slot(|slot| f(slot.as_mut().expect("SyncRngState is empty")))
+    }
+
+    /// Call `f` with exclusive access to the contained RNG if the lock is free
+    /// and the instance isn't empty. Never spins.
+    pub fn try_with<R, F: FnOnce(&mut RngState) -> R>(&self, f: F) -> Option<R> {
+        self.try_with_slot(|slot| slot.as_mut().map(f)).flatten()
+    }
+
+    /// Call `f` with exclusive access to the RNG slot if the lock is free.
+    /// Never spins.
+    pub(crate) fn try_with_slot<R, F: FnOnce(&mut Option<RngState>) -> R>(
+        &self,
+        f: F,
+    ) -> Option<R> {
+        if !self.lock.try_lock() {
+            return None;
+        }
+        let _unlock = Unlock(&self.lock);
+        // SAFETY: we hold the lock, so no other reference to `rng` exists
+        Some(f(unsafe { &mut *self.rng.get() }))
+    }
+}
+
+impl<L: RawSpinLock> Default for SyncRngState<L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L: RawSpinLock> From<RngState> for SyncRngState<L> {
+    fn from(rng: RngState) -> Self {
+        Self {
+            lock: L::UNLOCKED,
+            rng: UnsafeCell::new(Some(rng)),
+        }
+    }
+}
+
+#[cfg(all(test, target_has_atomic = "8"))]
+mod tests {
+    use super::{AtomicSpinLock, SyncRngState};
+    use crate::RngState;
+
+    fn test_rng() -> RngState {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"sync tests");
+        rng
+    }
+
+    #[test]
+    fn empty_try_with() {
+        let rng = SyncRngState::<AtomicSpinLock>::new();
+        assert_eq!(rng.try_with(|rng| rng.get_random_bytes::<8>()), None);
+        rng.set(test_rng());
+        assert!(rng.try_with(|rng| rng.get_random_bytes::<8>()).is_some());
+    }
+
+    #[test]
+    fn try_with_fails_while_locked() {
+        let rng = SyncRngState::<AtomicSpinLock>::from(test_rng());
+        rng.with(|_| assert!(rng.try_with(|_| ()).is_none()));
+        assert!(rng.try_with(|_| ()).is_some());
+    }
+}
@@ -0,0 +1,176 @@
+use core::fmt;
+use core::str::FromStr;
+
+use crate::RngState;
+
+/// Length in bytes of a [`TestSeed`].
+const TEST_SEED_LEN: usize = 32;
+
+/// Domain separation label absorbed before the test seed.
+const TEST_SEED_LABEL: &[u8] = b"fast-erasure-shake-rng test seed";
+/// Domain separation label absorbed before the fixture name.
+const FIXTURE_LABEL: &[u8] = b"fast-erasure-shake-rng fixture";
+
+/// A printable seed for reproducible randomized tests. NEVER use this outside
+/// of tests.
+///
+/// A test harness picks a random seed, prints it (it displays as 64 hex
+/// digits), and derives its RNG from it using [`Self::rng`]. When a test fails,
+/// the printed seed can be parsed back to rerun the test with the exact same
+/// random values.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::TestSeed;
+///
+/// let seed = TestSeed::random().unwrap();
+/// println!("test seed: {}", seed);
+/// let mut rng = seed.rng();
+///
+/// // to reproduce a failure, parse the printed seed
+/// let replayed: TestSeed = seed.to_string().parse().unwrap();
+/// assert_eq!(
+///     replayed.rng().get_random_bytes::<32>(),
+///     rng.get_random_bytes::<32>()
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TestSeed([u8; TEST_SEED_LEN]);
+
+impl TestSeed {
+    /// Create a test seed from raw bytes.
+    pub const fn from_bytes(bytes: [u8; TEST_SEED_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    /// Pick a fresh random test seed using the OS RNG.
+    #[cfg(feature = "getrandom")]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "test-util", feature = "getrandom"))))]
+    pub fn random() -> Result<Self, getrandom::Error> {
+        let mut bytes = [0; TEST_SEED_LEN];
+        getrandom::getrandom(&mut bytes)?;
+        Ok(Self(bytes))
+    }
+
+    /// The raw bytes of the seed.
+    pub const fn as_bytes(&self) -> &[u8; TEST_SEED_LEN] {
+        &self.0
+    }
+
+    /// Create an RNG deterministically seeded from this seed. Every call
+    /// returns an RNG in the same state.
+    pub fn rng(&self) -> RngState {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(TEST_SEED_LABEL);
+        rng.seed(&self.0);
+        rng
+    }
+}
+
+impl RngState {
+    /// Create an RNG deterministically seeded from `name`, e.g. the name of
+    /// the test using it, for stable randomness per test. NEVER use this
+    /// outside of tests.
+    ///
+    /// The output for a given name is stable across runs and machines of the
+    /// same endianness, but like all output of this crate it depends on the
+    /// target's endianness and the crate version, see the crate level
+    /// documentation. Don't hardcode expected outputs in tests; compare
+    /// against values computed in the same run instead.
+    ///
+    /// # Examples
+    /// ```
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::fixture("parses_random_headers");
+    /// let header = rng.get_random_bytes::<16>();
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+    pub fn fixture(name: &str) -> Self {
+        let mut rng = Self::new_unseeded();
+        rng.seed(FIXTURE_LABEL);
+        rng.seed(name.as_bytes());
+        rng
+    }
+}
+
+impl fmt::Display for TestSeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|b| write!(f, "{:02x}", b))
+    }
+}
+
+/// Error returned when parsing a [`TestSeed`] from a string which isn't 64 hex
+/// digits.
+#[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ParseTestSeedError;
+
+impl fmt::Display for ParseTestSeedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("test seed must consist of 64 hex digits")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseTestSeedError {}
+
+impl FromStr for TestSeed {
+    type Err = ParseTestSeedError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().as_bytes();
+        if s.len() != 2 * TEST_SEED_LEN || !s.iter().all(u8::is_ascii_hexdigit) {
+            return Err(ParseTestSeedError);
+        }
+        let mut bytes = [0; TEST_SEED_LEN];
+        for (b, digits) in bytes.iter_mut().zip(s.chunks_exact(2)) {
+            // all ASCII hex digits, so both conversions succeed
+            let digits = core::str::from_utf8(digits).unwrap();
+            *b = u8::from_str_radix(digits, 16).unwrap();
+        }
+        Ok(Self(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParseTestSeedError, TestSeed};
+
+    #[test]
+    fn display_parse_roundtrip() {
+        extern crate std;
+        use std::string::ToString;
+
+        let seed = TestSeed::from_bytes(core::array::from_fn(|i| u8::try_from(i * 7).unwrap()));
+        let printed = seed.to_string();
+        assert_eq!(printed.len(), 64);
+        assert_eq!(printed.parse(), Ok(seed));
+    }
+
+    #[test]
+    fn parse_rejects_invalid() {
+        assert_eq!("00".parse::<TestSeed>(), Err(ParseTestSeedError));
+        let signed = "+1+1+1+1+1+1+1+1+1+1+1+1+1+1+1+1+1+1+1+1+1+1+1+1+1+1+1+1+1+1+1+1";
+        assert_eq!(signed.parse::<TestSeed>(), Err(ParseTestSeedError));
+    }
+
+    #[test]
+    fn rng_is_reproducible() {
+        let seed = TestSeed::from_bytes([7; 32]);
+        assert_eq!(
+            seed.rng().get_random_bytes::<32>(),
+            seed.rng().get_random_bytes::<32>()
+        );
+    }
+
+    #[test]
+    fn fixtures_are_stable_per_name() {
+        use crate::RngState;
+
+        let a = RngState::fixture("a").get_random_bytes::<32>();
+        assert_eq!(RngState::fixture("a").get_random_bytes::<32>(), a);
+        assert_ne!(RngState::fixture("b").get_random_bytes::<32>(), a);
+    }
+}
@@ -0,0 +1,223 @@
+use core::ops::{Bound, Range, RangeBounds};
+use core::time::Duration;
+
+use crate::RngState;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// Convert a number of nanoseconds back into a [`Duration`]. `nanos` must be
+/// at most `Duration::MAX.as_nanos()`.
+fn duration_from_nanos(nanos: u128) -> Duration {
+    let secs = u64::try_from(nanos / NANOS_PER_SEC).expect("duration out of range");
+    // the remainder is below `NANOS_PER_SEC`, so the cast is lossless
+    #[allow(clippy::cast_possible_truncation)]
+    let subsec_nanos = (nanos % NANOS_PER_SEC) as u32;
+    Duration::new(secs, subsec_nanos)
+}
+
+impl RngState {
+    /// Output a uniformly random duration between `min` and `max` (both
+    /// inclusive), with nanosecond resolution.
+    fn random_duration_inclusive(&mut self, min: Duration, max: Duration) -> Duration {
+        let (min, max) = (min.as_nanos(), max.as_nanos());
+        // can't overflow since `Duration::MAX.as_nanos() < u128::MAX`
+        duration_from_nanos(min + self.random_u128_below(max - min + 1))
+    }
+
+    /// Output a uniformly random [`Duration`] in `range`, with nanosecond
+    /// resolution. The RNG MUST be seeded prior to using this method.
+    ///
+    /// As [`Duration`] lives in `core`, this is available on `no_std` as well;
+    /// convert the result to your platform's timer ticks as needed.
+    ///
+    /// # Panics
+    /// Panics if `range` is empty.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use core::time::Duration;
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let delay = rng.random_duration(Duration::from_millis(10)..Duration::from_millis(50));
+    /// # }
+    /// ```
+    pub fn random_duration(&mut self, range: Range<Duration>) -> Duration {
+        assert!(range.start < range.end, "cannot sample from an empty range");
+        let end = duration_from_nanos(range.end.as_nanos() - 1);
+        self.random_duration_inclusive(range.start, end)
+    }
+
+    /// Output a uniformly random [`Duration`] in `range`, which may be
+    /// inclusive, exclusive or unbounded on either side, with nanosecond
+    /// resolution. The RNG MUST be seeded prior to using this method.
+    ///
+    /// # Panics
+    /// Panics if `range` is empty.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use core::time::Duration;
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let timeout = rng.random_duration_in(Duration::from_secs(1)..=Duration::from_secs(2));
+    /// let delay = rng.random_duration_in(..Duration::from_millis(10));
+    /// # }
+    /// ```
+    pub fn random_duration_in<R: RangeBounds<Duration>>(&mut self, range: R) -> Duration {
+        const NANO: Duration = Duration::from_nanos(1);
+        let min = match range.start_bound() {
+            Bound::Included(&start) => Some(start),
+            Bound::Excluded(&start) => start.checked_add(NANO),
+            Bound::Unbounded => Some(Duration::ZERO),
+        };
+        let max = match range.end_bound() {
+            Bound::Included(&end) => Some(end),
+            Bound::Excluded(&end) => end.checked_sub(NANO),
+            Bound::Unbounded => Some(Duration::MAX),
+        };
+        match (min, max) {
+            (Some(min), Some(max)) if min <= max => self.random_duration_inclusive(min, max),
+            _ => panic!("cannot sample from an empty range"),
+        }
+    }
+
+    /// Output a uniformly random [`SystemTime`](std::time::SystemTime) in
+    /// `range`, which may be inclusive or exclusive but must be bounded, with
+    /// nanosecond resolution. The RNG MUST be seeded prior to using this
+    /// method.
+    ///
+    /// Useful for test data generation and randomized scheduling; sampling is
+    /// unbiased, unlike the common `start + random_u64() % span` arithmetic.
+    ///
+    /// # Panics
+    /// Panics if `range` is empty or unbounded.
+    ///
+    /// # Examples
+    /// ```
+    /// use fast_erasure_shake_rng::RngState;
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let now = SystemTime::now();
+    /// let last_login = rng.random_datetime_in(now - Duration::from_secs(30 * 86400)..now);
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn random_datetime_in<R: RangeBounds<std::time::SystemTime>>(
+        &mut self,
+        range: R,
+    ) -> std::time::SystemTime {
+        let (start, start_bound) = match range.start_bound() {
+            Bound::Included(&start) => (start, Bound::Included(Duration::ZERO)),
+            Bound::Excluded(&start) => (start, Bound::Excluded(Duration::ZERO)),
+            Bound::Unbounded => panic!("cannot sample from an unbounded range"),
+        };
+        let offset = |end: std::time::SystemTime| {
+            end.duration_since(start)
+                .expect("cannot sample from an empty range")
+        };
+        let end_bound = match range.end_bound() {
+            Bound::Included(&end) => Bound::Included(offset(end)),
+            Bound::Excluded(&end) => Bound::Excluded(offset(end)),
+            Bound::Unbounded => panic!("cannot sample from an unbounded range"),
+        };
+        start + self.random_duration_in((start_bound, end_bound))
+    }
+
+    /// Output `base` with uniformly random jitter applied, i.e. a uniformly
+    /// random duration between `base * (1 - factor)` and `base * (1 + factor)`
+    /// (both inclusive). The RNG MUST be seeded prior to using this method.
+    ///
+    /// This is the usual way to randomize retry backoff and scheduling
+    /// intervals, such that many clients don't act in lockstep.
+    ///
+    /// # Panics
+    /// Panics if `factor` is not in the range `0.0..=1.0`, or if `base * (1 +
+    /// factor)` overflows a [`Duration`].
+    ///
+    /// # Examples
+    /// Exponential backoff with 25% jitter:
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use core::time::Duration;
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// for attempt in 0..5 {
+    ///     let delay = rng.jittered(Duration::from_millis(100) * 2u32.pow(attempt), 0.25);
+    ///     // sleep(delay) and retry ...
+    /// }
+    /// # }
+    /// ```
+    pub fn jittered(&mut self, base: Duration, factor: f64) -> Duration {
+        assert!((0.0..=1.0).contains(&factor), "jitter factor out of range");
+        self.random_duration_inclusive(base.mul_f64(1.0 - factor), base.mul_f64(1.0 + factor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::ops::Bound;
+    use core::time::Duration;
+
+    use crate::RngState;
+
+    #[test]
+    fn random_duration_in_range() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"time tests");
+        let range = Duration::from_secs(1)..Duration::from_secs(1) + Duration::from_nanos(3);
+        for _ in 0..16 {
+            assert!(range.contains(&rng.random_duration(range.clone())));
+        }
+        let base = Duration::from_millis(100);
+        let jittered = rng.jittered(base, 0.5);
+        assert!(jittered >= base / 2 && jittered <= base * 3 / 2);
+        assert_eq!(rng.jittered(base, 0.0), base);
+    }
+
+    #[test]
+    fn random_duration_in_bounds() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"time tests");
+        let (min, max) = (
+            Duration::from_secs(1),
+            Duration::from_secs(1) + Duration::from_nanos(2),
+        );
+        for _ in 0..16 {
+            assert!((min..=max).contains(&rng.random_duration_in(min..=max)));
+            assert!((min..max).contains(&rng.random_duration_in(min..max)));
+            assert!(rng.random_duration_in(..min) < min);
+            assert!(rng.random_duration_in(max..) >= max);
+        }
+        assert_eq!(rng.random_duration_in(max..=max), max);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn random_datetime_in_range() {
+        use std::time::SystemTime;
+
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"time tests");
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let range = start..start + Duration::from_nanos(3);
+        for _ in 0..16 {
+            assert!(range.contains(&rng.random_datetime_in(range.clone())));
+        }
+        assert_eq!(rng.random_datetime_in(start..=start), start);
+    }
+
+    #[test]
+    #[should_panic(expected = "empty range")]
+    fn random_duration_in_rejects_empty() {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"time tests");
+        let d = Duration::from_secs(1);
+        rng.random_duration_in((Bound::Excluded(d), Bound::Excluded(d)));
+    }
+}
@@ -0,0 +1,247 @@
+use core::ptr::NonNull;
+
+use crate::{EntropySource, EntropySourceError};
+
+/// An EFI GUID, as defined by the UEFI specification.
+#[cfg_attr(docsrs, doc(cfg(feature = "uefi")))]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EfiGuid {
+    /// First field of the GUID.
+    pub data1: u32,
+    /// Second field of the GUID.
+    pub data2: u16,
+    /// Third field of the GUID.
+    pub data3: u16,
+    /// Last 8 bytes of the GUID.
+    pub data4: [u8; 8],
+}
+
+/// GUID of the `EFI_RNG_PROTOCOL`, to locate it using the boot services.
+#[cfg_attr(docsrs, doc(cfg(feature = "uefi")))]
+pub const EFI_RNG_PROTOCOL_GUID: EfiGuid = EfiGuid {
+    data1: 0x3152_bca5,
+    data2: 0xeade,
+    data3: 0x433d,
+    data4: [0x86, 0x2e, 0xc0, 0x1c, 0xdc, 0x29, 0x1f, 0x44],
+};
+
+/// The `EFI_RNG_PROTOCOL` interface (UEFI specification, section 37.5).
+#[cfg_attr(docsrs, doc(cfg(feature = "uefi")))]
+#[repr(C)]
+pub struct EfiRngProtocol {
+    /// `GetInfo`: list the RNG algorithms supported by the driver.
+    pub get_info: unsafe extern "efiapi" fn(
+        this: *mut EfiRngProtocol,
+        algorithm_list_size: *mut usize,
+        algorithm_list: *mut EfiGuid,
+    ) -> usize,
+    /// `GetRNG`: fill `value` with `value_length` random bytes using
+    /// `algorithm`, or the driver's default algorithm if null.
+    pub get_rng: unsafe extern "efiapi" fn(
+        this: *mut EfiRngProtocol,
+        algorithm: *const EfiGuid,
+        value_length: usize,
+        value: *mut u8,
+    ) -> usize,
+}
+
+/// `EFI_SUCCESS` status code.
+const EFI_SUCCESS: usize = 0;
+
+/// Number of attempts of RDSEED per 64-bit word before giving up; RDSEED
+/// fails transiently while the hardware entropy source refills.
+const RDSEED_RETRIES: usize = 1024;
+
+/// Pre-boot [`EntropySource`] for bootloaders and other UEFI applications:
+/// the firmware's `EFI_RNG_PROTOCOL`, falling back to the RDSEED instruction
+/// on x86 if the protocol is unavailable or fails.
+///
+/// Locate the protocol with the boot services (`LocateProtocol` with
+/// [`EFI_RNG_PROTOCOL_GUID`]), e.g. using the `uefi` crate, and pass the
+/// interface pointer to [`Self::new`]. The source can only be used while the
+/// boot services are active, i.e. before `ExitBootServices`.
+///
+/// # Examples
+/// ```no_run
+/// use fast_erasure_shake_rng::{EfiRngProtocol, RngState, UefiRng};
+///
+/// # let protocol: *mut EfiRngProtocol = core::ptr::null_mut();
+/// // `protocol` located using the boot services
+/// let mut source = unsafe { UefiRng::new(protocol) };
+/// let mut rng = RngState::new_unseeded();
+/// rng.seed_from_entropy_source(&mut source).unwrap();
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "uefi")))]
+#[derive(Debug)]
+pub struct UefiRng {
+    protocol: Option<NonNull<EfiRngProtocol>>,
+}
+
+impl UefiRng {
+    /// Create a source using the `EFI_RNG_PROTOCOL` interface `protocol`,
+    /// falling back to RDSEED. A null `protocol` only uses RDSEED.
+    ///
+    /// # Safety
+    /// `protocol` MUST be null or point to a valid `EFI_RNG_PROTOCOL`
+    /// interface which stays valid (boot services not exited) for the
+    /// lifetime of the source.
+    pub unsafe fn new(protocol: *mut EfiRngProtocol) -> Self {
+        Self {
+            protocol: NonNull::new(protocol),
+        }
+    }
+
+    /// Create a source using only the RDSEED instruction, e.g. on firmware
+    /// without an `EFI_RNG_PROTOCOL` driver.
+    pub fn rdseed_only() -> Self {
+        Self { protocol: None }
+    }
+
+    /// Fill `dest` using the protocol. Returns whether it succeeded.
+    fn fill_from_protocol(&mut self, dest: &mut [u8]) -> bool {
+        let Some(protocol) = self.protocol else {
+            return false;
+        };
+        // SAFETY: by the contract of `new`, `protocol` points to a valid
+        // interface, and `dest` is valid for writes of `dest.len()` bytes
+        let status = unsafe {
+            (protocol.as_ref().get_rng)(
+                protocol.as_ptr(),
+                core::ptr::null(),
+                dest.len(),
+                dest.as_mut_ptr(),
+            )
+        };
+        status == EFI_SUCCESS
+    }
+}
+
+impl EntropySource for UefiRng {
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), EntropySourceError> {
+        if self.fill_from_protocol(dest) {
+            return Ok(());
+        }
+        fill_from_rdseed(dest)
+    }
+
+    fn label(&self) -> &str {
+        "uefi-rng"
+    }
+
+    fn is_full_entropy(&self) -> bool {
+        true
+    }
+}
+
+/// Whether the CPU supports the RDSEED instruction.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[allow(unused_unsafe)] // `__cpuid_count` is safe in newer Rust versions
+fn rdseed_available() -> bool {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::__cpuid_count;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::__cpuid_count;
+
+    // CPUID leaf 7, subleaf 0: EBX bit 18 indicates RDSEED
+    // SAFETY (for Rust versions where `__cpuid_count` is unsafe): CPUID is
+    // available on all targets UEFI runs on
+    let max_leaf = unsafe { __cpuid_count(0, 0) }.eax;
+    max_leaf >= 7 && (unsafe { __cpuid_count(7, 0) }.ebx >> 18) & 1 == 1
+}
+
+/// Fill `dest` using the RDSEED instruction.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn fill_from_rdseed(dest: &mut [u8]) -> Result<(), EntropySourceError> {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::_rdseed32_step as rdseed_step;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::_rdseed64_step as rdseed_step;
+
+    if !rdseed_available() {
+        return Err(EntropySourceError::new(
+            "EFI_RNG_PROTOCOL failed and RDSEED is unavailable",
+        ));
+    }
+    let mut word = zeroize::Zeroizing::new(Default::default());
+    for chunk in dest.chunks_mut(core::mem::size_of_val(&*word)) {
+        // SAFETY: RDSEED is supported, checked above
+        let ok = (0..RDSEED_RETRIES).any(|_| unsafe { rdseed_step(&mut word) } == 1);
+        if !ok {
+            return Err(EntropySourceError::new("RDSEED failed repeatedly"));
+        }
+        chunk.copy_from_slice(&word.to_ne_bytes()[..chunk.len()]);
+    }
+    Ok(())
+}
+
+/// Without RDSEED there is no fallback.
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+fn fill_from_rdseed(_dest: &mut [u8]) -> Result<(), EntropySourceError> {
+    Err(EntropySourceError::new(
+        "EFI_RNG_PROTOCOL failed and RDSEED is unavailable",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EfiGuid, EfiRngProtocol, UefiRng, EFI_SUCCESS};
+    use crate::EntropySource;
+
+    unsafe extern "efiapi" fn get_info(
+        _this: *mut EfiRngProtocol,
+        _algorithm_list_size: *mut usize,
+        _algorithm_list: *mut EfiGuid,
+    ) -> usize {
+        EFI_SUCCESS
+    }
+
+    unsafe extern "efiapi" fn get_rng_fixed(
+        _this: *mut EfiRngProtocol,
+        _algorithm: *const EfiGuid,
+        value_length: usize,
+        value: *mut u8,
+    ) -> usize {
+        // SAFETY: the caller passes a buffer of `value_length` bytes
+        unsafe { core::ptr::write_bytes(value, 0x42, value_length) };
+        EFI_SUCCESS
+    }
+
+    unsafe extern "efiapi" fn get_rng_failing(
+        _this: *mut EfiRngProtocol,
+        _algorithm: *const EfiGuid,
+        _value_length: usize,
+        _value: *mut u8,
+    ) -> usize {
+        // EFI_DEVICE_ERROR
+        (1 << (usize::BITS - 1)) | 7
+    }
+
+    #[test]
+    fn uses_protocol() {
+        let mut protocol = EfiRngProtocol {
+            get_info,
+            get_rng: get_rng_fixed,
+        };
+        // SAFETY: the protocol outlives the source
+        let mut source = unsafe { UefiRng::new(&mut protocol) };
+        let mut buf = [0; 40];
+        source.fill_entropy(&mut buf).unwrap();
+        assert_eq!(buf, [0x42; 40]);
+    }
+
+    #[test]
+    fn falls_back_to_rdseed() {
+        let mut protocol = EfiRngProtocol {
+            get_info,
+            get_rng: get_rng_failing,
+        };
+        // SAFETY: the protocol outlives the source
+        let mut source = unsafe { UefiRng::new(&mut protocol) };
+        let res = source.fill_entropy(&mut [0; 13]);
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        assert_eq!(res.is_ok(), super::rdseed_available());
+        #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+        assert!(res.is_err());
+    }
+}
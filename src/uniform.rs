@@ -0,0 +1,330 @@
+use core::ops::RangeInclusive;
+
+use crate::{u128_slice_as_ne_bytes_mut, RngState, CAPACITY_LANES, RATE_LANES};
+
+/// First code point of the surrogate range, which contains no Unicode scalar
+/// values.
+const SURROGATES_START: u32 = 0xD800;
+/// Number of code points in the surrogate range `0xD800..=0xDFFF`.
+const SURROGATES_LEN: u32 = 0x800;
+/// Number of Unicode scalar values, i.e. code points excluding surrogates.
+const SCALAR_VALUES: u32 = char::MAX as u32 + 1 - SURROGATES_LEN;
+
+/// Number of Unicode scalar values in `range`. Zero if `range` is empty.
+fn scalar_values_in(range: &RangeInclusive<char>) -> u32 {
+    let (start, end) = (u32::from(*range.start()), u32::from(*range.end()));
+    if start > end {
+        return 0;
+    }
+    // `start` and `end` are scalar values themselves, so the range either
+    // contains the full surrogate range or none of it
+    if start < SURROGATES_START && end > SURROGATES_START {
+        end - start + 1 - SURROGATES_LEN
+    } else {
+        end - start + 1
+    }
+}
+
+/// Split `lane` into its low and high halves.
+fn split_lane(lane: u64) -> [u32; 2] {
+    // truncation to the low half is intended
+    #[allow(clippy::cast_possible_truncation)]
+    let halves = [lane as u32, (lane >> 32) as u32];
+    halves
+}
+
+/// The `index`-th Unicode scalar value counting from `start`, skipping the
+/// surrogate range.
+fn nth_scalar_value_from(start: char, index: u32) -> char {
+    let start = u32::from(start);
+    let mut value = start + index;
+    if start < SURROGATES_START && value >= SURROGATES_START {
+        value += SURROGATES_LEN;
+    }
+    char::from_u32(value).expect("index out of range of scalar values")
+}
+
+impl RngState {
+    /// Output a uniformly random `u64`. The RNG MUST be seeded prior to using
+    /// this method.
+    pub(crate) fn random_u64(&mut self) -> u64 {
+        u64::from_ne_bytes(self.get_random_bytes())
+    }
+
+    /// Output a uniformly random `u128`, e.g. a token or key. The RNG MUST be
+    /// seeded prior to using this method.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let token = rng.next_u128();
+    /// # }
+    /// ```
+    pub fn next_u128(&mut self) -> u128 {
+        u128::from_ne_bytes(self.get_random_bytes())
+    }
+
+    /// Fill `dest` with uniformly random `u128`s, in a single output request
+    /// (so with a single erasure). The RNG MUST be seeded prior to using this
+    /// method.
+    pub fn fill_random_u128s(&mut self, dest: &mut [u128]) {
+        self.fill_random_bytes(u128_slice_as_ne_bytes_mut(dest));
+    }
+
+    /// Fill `dest` with uniformly random `u64`s, copied directly from the
+    /// lanes of the state in a single output request (so with a single
+    /// erasure), without a byte buffer or endianness conversion. The output
+    /// equals that of [`Self::fill_random_bytes`] on the native endian bytes
+    /// of `dest`. The RNG MUST be seeded prior to using this method.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let mut samples = [0u64; 1024];
+    /// rng.fill_random_u64s(&mut samples);
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "profiling", track_caller)]
+    pub fn fill_random_u64s(&mut self, dest: &mut [u64]) {
+        #[cfg(feature = "profiling")]
+        let _span = crate::profiling::Span::start(8 * dest.len());
+        let mut pos = 0;
+        self.output_lanes(dest.len(), |lanes| {
+            dest[pos..pos + lanes.len()].copy_from_slice(lanes);
+            pos += lanes.len();
+        });
+    }
+
+    /// Fill `dest` with uniformly random `u32`s, taken directly from the lanes
+    /// of the state (low half first) in a single output request (so with a
+    /// single erasure), without a byte buffer or endianness conversion. The
+    /// RNG MUST be seeded prior to using this method.
+    #[cfg_attr(feature = "profiling", track_caller)]
+    pub fn fill_random_u32s(&mut self, dest: &mut [u32]) {
+        #[cfg(feature = "profiling")]
+        let _span = crate::profiling::Span::start(4 * dest.len());
+        let lanes_len = dest.len().div_ceil(2);
+        let mut words = dest.iter_mut();
+        self.output_lanes(lanes_len, |lanes| {
+            for &lane in lanes {
+                // the halves go first, so `zip` doesn't skip a word
+                for (half, word) in split_lane(lane).into_iter().zip(&mut words) {
+                    *word = half;
+                }
+            }
+        });
+    }
+
+    /// Output `len` lanes of the state to `emit`, block by block, following
+    /// the block structure of [`Self::fill_random_bytes`] with lanes instead
+    /// of bytes.
+    fn output_lanes<F: FnMut(&[u64])>(&mut self, mut len: usize, mut emit: F) {
+        let mut block_lanes = RATE_LANES;
+        loop {
+            let n = core::cmp::min(len, block_lanes);
+            emit(&self.state.get_rate_zeroized_capacity_lanes()[..n]);
+            self.apply_f();
+            len -= n;
+            if len == 0 {
+                break;
+            }
+            block_lanes = RATE_LANES + CAPACITY_LANES;
+        }
+        self.basic_make_forward_secure();
+    }
+
+    /// Output a uniformly random `u64` in the range `0..bound`, using rejection
+    /// sampling so the result is free of modulo bias. The RNG MUST be seeded
+    /// prior to using this method.
+    ///
+    /// # Panics
+    /// Panics if `bound == 0`.
+    pub(crate) fn random_u64_below(&mut self, bound: u64) -> u64 {
+        assert!(bound != 0, "cannot sample from an empty range");
+        // smallest all-ones mask covering `bound - 1`; every candidate is accepted
+        // with probability > 1/2
+        let mask = u64::MAX
+            .checked_shr((bound - 1).leading_zeros())
+            .unwrap_or(0);
+        loop {
+            let candidate = self.random_u64() & mask;
+            if candidate < bound {
+                return candidate;
+            }
+        }
+    }
+
+    /// Output a uniformly random `u128` in the range `0..bound`, see
+    /// [`Self::random_u64_below`].
+    ///
+    /// # Panics
+    /// Panics if `bound == 0`.
+    pub(crate) fn random_u128_below(&mut self, bound: u128) -> u128 {
+        assert!(bound != 0, "cannot sample from an empty range");
+        let mask = u128::MAX
+            .checked_shr((bound - 1).leading_zeros())
+            .unwrap_or(0);
+        loop {
+            let candidate = u128::from_ne_bytes(self.get_random_bytes()) & mask;
+            if candidate < bound {
+                return candidate;
+            }
+        }
+    }
+
+    /// Output a uniformly random `usize` in the range `0..bound`, see
+    /// [`Self::random_u64_below`].
+    ///
+    /// # Panics
+    /// Panics if `bound == 0`.
+    pub(crate) fn random_usize_below(&mut self, bound: usize) -> usize {
+        // the result is below `bound`, so the cast back to `usize` is lossless
+        #[allow(clippy::cast_possible_truncation)]
+        let res = self.random_u64_below(bound as u64) as usize;
+        res
+    }
+
+    /// Output a uniformly random Unicode scalar value, i.e. a uniformly random
+    /// `char`. Surrogate code points are not scalar values and are therefore
+    /// never returned; the remaining code points are all equally likely. The
+    /// RNG MUST be seeded prior to using this method.
+    pub fn random_char(&mut self) -> char {
+        // `index < SCALAR_VALUES` so the cast is lossless
+        #[allow(clippy::cast_possible_truncation)]
+        let index = self.random_u64_below(SCALAR_VALUES.into()) as u32;
+        nth_scalar_value_from('\0', index)
+    }
+
+    /// Output a uniformly random `char` from the union of `ranges`. Every
+    /// Unicode scalar value contained in one of the ranges is equally likely;
+    /// surrogates in a range spanning the surrogate gap are skipped without
+    /// biasing the values around it. The RNG MUST be seeded prior to using
+    /// this method.
+    ///
+    /// The ranges should be disjoint: a scalar value contained in multiple
+    /// ranges is proportionally more likely to be returned.
+    ///
+    /// # Panics
+    /// Panics if `ranges` contains no scalar values at all (e.g. because it is
+    /// empty).
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let c = rng.random_char_in(&['a'..='z', 'A'..='Z', '0'..='9']);
+    /// assert!(c.is_ascii_alphanumeric());
+    /// # }
+    /// ```
+    pub fn random_char_in(&mut self, ranges: &[RangeInclusive<char>]) -> char {
+        let total: u64 = ranges.iter().map(|r| u64::from(scalar_values_in(r))).sum();
+        let mut index = self.random_u64_below(total);
+        for range in ranges {
+            let len = u64::from(scalar_values_in(range));
+            if index < len {
+                // `index < len <= SCALAR_VALUES` so the cast is lossless
+                #[allow(clippy::cast_possible_truncation)]
+                let index = index as u32;
+                return nth_scalar_value_from(*range.start(), index);
+            }
+            index -= len;
+        }
+        unreachable!("index is below the total number of scalar values")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RngState;
+
+    fn test_rng() -> RngState {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"uniform tests");
+        rng
+    }
+
+    #[test]
+    fn u128_output_matches_bytes() {
+        let (mut rng, mut reference) = (RngState::new_unseeded(), RngState::new_unseeded());
+        rng.seed(b"uniform tests");
+        reference.seed(b"uniform tests");
+        let mut out = [0u128; 3];
+        rng.fill_random_u128s(&mut out);
+        let bytes: [u8; 48] = reference.get_random_bytes();
+        for (value, chunk) in out.iter().zip(bytes.chunks_exact(16)) {
+            assert_eq!(value.to_ne_bytes()[..], *chunk);
+        }
+        assert_eq!(
+            rng.next_u128(),
+            u128::from_ne_bytes(reference.get_random_bytes())
+        );
+    }
+
+    #[test]
+    fn word_output_matches_bytes() {
+        for len in [0, 1, 9, 10, 26, 27, 100] {
+            let (mut rng, mut reference) = (test_rng(), test_rng());
+            let mut words = [0u64; 100];
+            rng.fill_random_u64s(&mut words[..len]);
+            let mut bytes = [0u8; 800];
+            reference.fill_random_bytes(&mut bytes[..8 * len]);
+            for (word, chunk) in words[..len].iter().zip(bytes.chunks_exact(8)) {
+                assert_eq!(word.to_ne_bytes()[..], *chunk);
+            }
+            assert_eq!(rng.random_u64(), reference.random_u64());
+
+            let (mut rng, mut reference) = (test_rng(), test_rng());
+            let mut halves = [0u32; 2 * 100];
+            rng.fill_random_u32s(&mut halves[..2 * len]);
+            reference.fill_random_u64s(&mut words[..len]);
+            for (pair, word) in halves.chunks_exact(2).zip(&words[..len]) {
+                assert_eq!(u64::from(pair[0]) | u64::from(pair[1]) << 32, *word);
+            }
+            assert_eq!(rng.random_u64(), reference.random_u64());
+        }
+        // odd lengths use the low half of the last lane
+        let (mut rng, mut reference) = (test_rng(), test_rng());
+        let mut halves = [0u32; 19];
+        rng.fill_random_u32s(&mut halves);
+        let mut words = [0u64; 10];
+        reference.fill_random_u64s(&mut words);
+        assert_eq!(u64::from(halves[18]), words[9] & 0xffff_ffff);
+        assert_eq!(rng.random_u64(), reference.random_u64());
+    }
+
+    #[test]
+    fn random_u64_below_in_range() {
+        let mut rng = test_rng();
+        for bound in [1, 2, 3, 7, 1 << 40, u64::MAX] {
+            assert!(rng.random_u64_below(bound) < bound);
+        }
+    }
+
+    #[test]
+    fn random_char_in_skips_surrogates() {
+        let mut rng = test_rng();
+        let mut seen = [false; 2];
+        for _ in 0..64 {
+            match rng.random_char_in(&['\u{D7FF}'..='\u{E000}']) {
+                '\u{D7FF}' => seen[0] = true,
+                '\u{E000}' => seen[1] = true,
+                c => panic!("sampled {:?} outside of the range", c),
+            }
+        }
+        assert_eq!(seen, [true, true]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn random_char_in_empty() {
+        test_rng().random_char_in(&[]);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::RngState;
+
+/// Set the version nibble and the RFC 9562 variant bits of `uuid`.
+fn set_version_and_variant(uuid: &mut [u8; 16], version: u8) {
+    uuid[6] = (uuid[6] & 0x0f) | (version << 4);
+    uuid[8] = (uuid[8] & 0x3f) | 0x80;
+}
+
+impl RngState {
+    /// Output a random (version 4) UUID (RFC 9562), as its 16 bytes in
+    /// network byte order, e.g. for `uuid::Uuid::from_bytes`. The RNG MUST be
+    /// seeded prior to using this method.
+    pub fn random_uuid_v4(&mut self) -> [u8; 16] {
+        let mut uuid = self.get_random_bytes();
+        set_version_and_variant(&mut uuid, 4);
+        uuid
+    }
+
+    /// Output a time-ordered (version 7) UUID (RFC 9562) for the Unix
+    /// timestamp `timestamp_ms` in milliseconds, as its 16 bytes in network
+    /// byte order. The remaining 74 bits are random. UUIDv7 values sort by
+    /// creation time, which keeps database indexes compact. The RNG MUST be
+    /// seeded prior to using this method.
+    ///
+    /// UUIDs created in the same millisecond are not ordered among each
+    /// other.
+    ///
+    /// # Panics
+    /// Panics if `timestamp_ms` doesn't fit in 48 bits.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "getrandom")] {
+    /// use std::time::{SystemTime, UNIX_EPOCH};
+    ///
+    /// use fast_erasure_shake_rng::RngState;
+    ///
+    /// let mut rng = RngState::new_from_getrandom().unwrap();
+    /// let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    /// let id = rng.random_uuid_v7(now.as_millis().try_into().unwrap());
+    /// assert_eq!(id[6] >> 4, 7);
+    /// # }
+    /// ```
+    pub fn random_uuid_v7(&mut self, timestamp_ms: u64) -> [u8; 16] {
+        assert!(timestamp_ms < 1 << 48, "timestamp doesn't fit in 48 bits");
+        let mut uuid = [0; 16];
+        uuid[..6].copy_from_slice(&timestamp_ms.to_be_bytes()[2..]);
+        self.fill_random_bytes(&mut uuid[6..]);
+        set_version_and_variant(&mut uuid, 7);
+        uuid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RngState;
+
+    fn test_rng() -> RngState {
+        let mut rng = RngState::new_unseeded();
+        rng.seed(b"uuid tests");
+        rng
+    }
+
+    #[test]
+    fn version_and_variant() {
+        let mut rng = test_rng();
+        let v4 = rng.random_uuid_v4();
+        assert_eq!((v4[6] >> 4, v4[8] >> 6), (4, 0b10));
+        let v7 = rng.random_uuid_v7(0x0123_4567_89ab);
+        assert_eq!(v7[..6], [0x01, 0x23, 0x45, 0x67, 0x89, 0xab]);
+        assert_eq!((v7[6] >> 4, v7[8] >> 6), (7, 0b10));
+    }
+
+    #[test]
+    fn v7_sorts_by_time() {
+        let mut rng = test_rng();
+        let ids: [[u8; 16]; 10] = core::array::from_fn(|i| rng.random_uuid_v7(1_000 + i as u64));
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    }
+}
@@ -0,0 +1,136 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::RngState;
+
+/// Domain separation label of the `wasi:random/random` stream.
+const SECURE_LABEL: &[u8] = b"fast-erasure-shake-rng wasi:random/random";
+/// Domain separation label of the `wasi:random/insecure` stream.
+const INSECURE_LABEL: &[u8] = b"fast-erasure-shake-rng wasi:random/insecure";
+/// Domain separation label of the `wasi:random/insecure-seed` value.
+const INSECURE_SEED_LABEL: &[u8] = b"fast-erasure-shake-rng wasi:random/insecure-seed";
+
+/// Provider of the `wasi:random` interfaces (`random`, `insecure` and
+/// `insecure-seed`) of the WASI component model, backed by this RNG, for Wasm
+/// plugin hosts.
+///
+/// The methods mirror the functions of the WIT interfaces; implement the
+/// `Host` traits generated by the host's bindings generator (e.g.
+/// `wasmtime::component::bindgen!`) by forwarding to them, with one provider
+/// per component instance. Every interface gets its own domain separated
+/// stream, so guests can't learn secure output from the insecure interfaces.
+/// Seeding the provider deterministically with [`Self::from_seed`] makes all
+/// guest randomness reproducible, e.g. for replaying plugin executions.
+///
+/// # Examples
+/// ```
+/// use fast_erasure_shake_rng::{RngState, WasiRandom};
+///
+/// let mut host_rng = RngState::new_from_getrandom().unwrap();
+/// let mut provider = WasiRandom::new(&mut host_rng);
+/// // in `impl wasi::random::random::Host for MyState`:
+/// let bytes = provider.get_random_bytes(16);
+/// let value = provider.get_random_u64();
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "wasi")))]
+pub struct WasiRandom {
+    secure: RngState,
+    insecure: RngState,
+    insecure_seed: (u64, u64),
+}
+
+impl WasiRandom {
+    /// Create a provider derived from `rng`, which MUST be seeded.
+    pub fn new(rng: &mut RngState) -> Self {
+        Self::from_parent(rng.fork())
+    }
+
+    /// Create a provider deterministically derived from `seed`, which should
+    /// contain at least 256 bits of entropy unless reproducibility is all
+    /// that matters.
+    pub fn from_seed(seed: &[u8]) -> Self {
+        let mut parent = RngState::new_unseeded();
+        parent.seed(seed);
+        Self::from_parent(parent)
+    }
+
+    fn from_parent(mut parent: RngState) -> Self {
+        let mut derive = |label: &[u8]| {
+            let mut rng = parent.fork();
+            rng.seed(label);
+            rng
+        };
+        let secure = derive(SECURE_LABEL);
+        let insecure = derive(INSECURE_LABEL);
+        let mut seed_rng = derive(INSECURE_SEED_LABEL);
+        let insecure_seed = (seed_rng.random_u64(), seed_rng.random_u64());
+        Self {
+            secure,
+            insecure,
+            insecure_seed,
+        }
+    }
+
+    /// `wasi:random/random.get-random-bytes`: `len` cryptographically secure
+    /// random bytes.
+    ///
+    /// # Panics
+    /// Panics if `len` doesn't fit in a `usize`. Hosts should bound `len`
+    /// (or the guest's memory limits do) to avoid huge allocations.
+    pub fn get_random_bytes(&mut self, len: u64) -> Vec<u8> {
+        let mut bytes = vec![0; usize::try_from(len).expect("request too large")];
+        self.secure.fill_random_bytes(&mut bytes);
+        bytes
+    }
+
+    /// `wasi:random/random.get-random-u64`: a cryptographically secure random
+    /// `u64`.
+    pub fn get_random_u64(&mut self) -> u64 {
+        self.secure.random_u64()
+    }
+
+    /// `wasi:random/insecure.get-insecure-random-bytes`: `len` random bytes
+    /// for non-cryptographic use, from a stream independent of the secure
+    /// one.
+    ///
+    /// # Panics
+    /// Panics if `len` doesn't fit in a `usize`.
+    pub fn get_insecure_random_bytes(&mut self, len: u64) -> Vec<u8> {
+        let mut bytes = vec![0; usize::try_from(len).expect("request too large")];
+        self.insecure.fill_random_bytes(&mut bytes);
+        bytes
+    }
+
+    /// `wasi:random/insecure.get-insecure-random-u64`: a random `u64` for
+    /// non-cryptographic use.
+    pub fn get_insecure_random_u64(&mut self) -> u64 {
+        self.insecure.random_u64()
+    }
+
+    /// `wasi:random/insecure-seed.insecure-seed`: a 128-bit seed for hash
+    /// table DoS protection, fixed for the lifetime of the provider as the
+    /// interface requires.
+    pub fn insecure_seed(&self) -> (u64, u64) {
+        self.insecure_seed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WasiRandom;
+
+    #[test]
+    fn deterministic_and_separated() {
+        let (mut a, mut b) = (
+            WasiRandom::from_seed(b"wasi tests"),
+            WasiRandom::from_seed(b"wasi tests"),
+        );
+        assert_eq!(a.insecure_seed(), b.insecure_seed());
+        assert_eq!(a.insecure_seed(), a.insecure_seed());
+        let secure = a.get_random_bytes(40);
+        assert_eq!(secure.len(), 40);
+        assert_eq!(secure, b.get_random_bytes(40));
+        assert_ne!(secure, a.get_insecure_random_bytes(40));
+        assert_eq!(a.get_random_u64(), b.get_random_u64());
+    }
+}
@@ -0,0 +1,128 @@
+use crate::LANES;
+
+/// Rate of SHAKE256 in bytes.
+const SHAKE256_RATE: usize = 136;
+
+/// The SHAKE256 extendable output function (FIPS 202), built on the same
+/// keccak-f\[1600\] permutation as the RNG.
+///
+/// Unlike the RNG, the output of SHAKE256 is portable: the state is accessed
+/// bytewise in little-endian lane order regardless of the target endianness.
+/// This is used wherever output must be reproducible across platforms, e.g.
+/// for key derivation. The state is zeroized on drop.
+pub(crate) struct Shake256 {
+    state: [u64; LANES],
+    /// Position in the rate part of the state for the next byte to absorb or
+    /// squeeze.
+    pos: usize,
+    squeezing: bool,
+}
+
+impl Shake256 {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: [0; LANES],
+            pos: 0,
+            squeezing: false,
+        }
+    }
+
+    /// Xor `byte` into the state at byte position `pos`.
+    fn xor_byte(&mut self, pos: usize, byte: u8) {
+        self.state[pos / 8] ^= u64::from(byte) << (8 * (pos % 8));
+    }
+
+    /// Absorb `data`.
+    ///
+    /// # Panics
+    /// Panics if output has already been squeezed.
+    pub(crate) fn absorb(&mut self, data: &[u8]) {
+        assert!(!self.squeezing, "absorbing after squeezing");
+        for &byte in data {
+            self.xor_byte(self.pos, byte);
+            self.pos += 1;
+            if self.pos == SHAKE256_RATE {
+                keccak::f1600(&mut self.state);
+                self.pos = 0;
+            }
+        }
+    }
+
+    /// Absorb `data` prefixed with its length as 64-bit little-endian integer,
+    /// giving an injective encoding of sequences of byte strings.
+    pub(crate) fn absorb_prefixed(&mut self, data: &[u8]) {
+        self.absorb(&(data.len() as u64).to_le_bytes());
+        self.absorb(data);
+    }
+
+    /// Squeeze output into `out`. Can be called repeatedly to obtain
+    /// consecutive output.
+    pub(crate) fn squeeze(&mut self, out: &mut [u8]) {
+        if !self.squeezing {
+            // SHAKE domain separation and pad10*1
+            self.xor_byte(self.pos, 0x1f);
+            self.xor_byte(SHAKE256_RATE - 1, 0x80);
+            keccak::f1600(&mut self.state);
+            self.pos = 0;
+            self.squeezing = true;
+        }
+        for byte in out {
+            if self.pos == SHAKE256_RATE {
+                keccak::f1600(&mut self.state);
+                self.pos = 0;
+            }
+            // extract one byte of a lane
+            #[allow(clippy::cast_possible_truncation)]
+            let out_byte = (self.state[self.pos / 8] >> (8 * (self.pos % 8))) as u8;
+            *byte = out_byte;
+            self.pos += 1;
+        }
+    }
+}
+
+impl Drop for Shake256 {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Shake256;
+
+    #[test]
+    fn shake256_empty() {
+        // first 32 bytes of SHAKE256("") from the FIPS 202 test vectors
+        const EXPECTED: [u8; 32] = [
+            0x46, 0xb9, 0xdd, 0x2b, 0x0b, 0xa8, 0x8d, 0x13, 0x23, 0x3b, 0x3f, 0xeb, 0x74, 0x3e,
+            0xeb, 0x24, 0x3f, 0xcd, 0x52, 0xea, 0x62, 0xb8, 0x1b, 0x82, 0xb5, 0x0c, 0x27, 0x64,
+            0x6e, 0xd5, 0x76, 0x2f,
+        ];
+        let mut xof = Shake256::new();
+        let mut out = [0; 32];
+        xof.squeeze(&mut out[..5]);
+        xof.squeeze(&mut out[5..]);
+        assert_eq!(out, EXPECTED);
+    }
+
+    #[test]
+    fn shake256_multi_block() {
+        // absorbing in pieces and squeezing across block boundaries must match
+        // one-shot absorption and squeezing
+        let data = [0xa3; 300];
+        let mut one_shot = Shake256::new();
+        one_shot.absorb(&data);
+        let mut expected = [0; 300];
+        one_shot.squeeze(&mut expected);
+
+        let mut pieces = Shake256::new();
+        pieces.absorb(&data[..135]);
+        pieces.absorb(&data[135..137]);
+        pieces.absorb(&data[137..]);
+        let mut out = [0; 300];
+        for chunk in out.chunks_mut(7) {
+            pieces.squeeze(chunk);
+        }
+        assert_eq!(out, expected);
+    }
+}